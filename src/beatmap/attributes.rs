@@ -15,6 +15,8 @@ pub struct BeatmapAttributes {
     pub clock_rate: f64,
     /// The hit windows for approach rate and overall difficulty.
     pub hit_windows: BeatmapHitWindows,
+    /// The beats per minute with respect to the clock rate.
+    pub bpm: f64,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -35,6 +37,7 @@ pub struct BeatmapAttributesBuilder {
     od: f32,
     cs: f32,
     hp: f32,
+    bpm: f64,
     mods: Option<u32>,
     clock_rate: Option<f64>,
     converted: bool,
@@ -95,6 +98,14 @@ impl BeatmapAttributesBuilder {
         self
     }
 
+    #[inline]
+    /// Specify the beats per minute without mods.
+    pub fn bpm(&mut self, bpm: f64) -> &mut Self {
+        self.bpm = bpm;
+
+        self
+    }
+
     #[inline]
     /// Specify the mods.
     pub fn mods(&mut self, mods: u32) -> &mut Self {
@@ -225,6 +236,7 @@ impl BeatmapAttributesBuilder {
             hp: hp as f64,
             clock_rate,
             hit_windows,
+            bpm: self.bpm * clock_rate,
         }
     }
 }
@@ -238,6 +250,7 @@ impl From<&Beatmap> for BeatmapAttributesBuilder {
             od: map.od,
             cs: map.cs,
             hp: map.hp,
+            bpm: map.bpm(),
             mods: None,
             clock_rate: None,
             converted: false,
@@ -245,7 +258,7 @@ impl From<&Beatmap> for BeatmapAttributesBuilder {
     }
 }
 
-fn difficulty_range(difficulty: f64, min: f64, mid: f64, max: f64) -> f64 {
+pub(crate) fn difficulty_range(difficulty: f64, min: f64, mid: f64, max: f64) -> f64 {
     if difficulty > 5.0 {
         mid + (max - mid) * (difficulty - 5.0) / 5.0
     } else if difficulty < 5.0 {
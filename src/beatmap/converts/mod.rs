@@ -1,2 +1,26 @@
+use std::{error::Error as StdError, fmt};
+
+use super::GameMode;
+
 mod mania;
 mod taiko;
+
+/// Error returned by [`Beatmap::mode_as_converted`](crate::Beatmap::mode_as_converted)
+/// if a map cannot be converted into the given mode.
+///
+/// Only osu!standard maps can be converted into other modes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ConvertError {
+    /// The mode of the map.
+    pub from: GameMode,
+    /// The mode that the map was supposed to be converted into.
+    pub to: GameMode,
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot convert a {:?} map into {:?}", self.from, self.to)
+    }
+}
+
+impl StdError for ConvertError {}
@@ -1,14 +1,21 @@
 use std::{borrow::Cow, cmp::Ordering};
 
-use crate::{parse::HitObject, util::SortedVec};
+use crate::{
+    catch::{self, calculate_catch_width},
+    parse::HitObject,
+    util::SortedVec,
+};
 
 pub use self::{
     attributes::{BeatmapAttributes, BeatmapAttributesBuilder, BeatmapHitWindows},
     breaks::Break,
     control_points::{DifficultyPoint, EffectPoint, TimingPoint},
+    converts::ConvertError,
     mode::GameMode,
 };
 
+pub(crate) use self::attributes::difficulty_range;
+
 mod attributes;
 mod breaks;
 mod control_points;
@@ -70,6 +77,9 @@ pub struct Beatmap {
 
     /// The beatmap ID of the map
     pub beatmap_id: u32,
+
+    /// MD5 hash of the parsed `.osu` file, `None` if the map was not parsed.
+    pub md5_hash: Option<[u8; 16]>,
 }
 
 impl Beatmap {
@@ -79,6 +89,110 @@ impl Beatmap {
         BeatmapAttributesBuilder::new(self)
     }
 
+    /// The approach rate as specified in the `.osu` file, i.e. without any mods applied.
+    #[inline]
+    pub fn approach_rate(&self) -> f32 {
+        self.ar
+    }
+
+    /// The overall difficulty as specified in the `.osu` file, i.e. without any mods applied.
+    #[inline]
+    pub fn overall_difficulty(&self) -> f32 {
+        self.od
+    }
+
+    /// The circle size as specified in the `.osu` file, i.e. without any mods applied.
+    #[inline]
+    pub fn circle_size(&self) -> f32 {
+        self.cs
+    }
+
+    /// The health drain rate as specified in the `.osu` file, i.e. without any mods applied.
+    #[inline]
+    pub fn hp_drain_rate(&self) -> f32 {
+        self.hp
+    }
+
+    /// Overwrite the map's approach rate.
+    ///
+    /// Mods are still applied on top of the new value during calculations.
+    #[inline]
+    pub fn set_ar(&mut self, ar: f32) -> &mut Self {
+        self.ar = ar;
+
+        self
+    }
+
+    /// Overwrite the map's overall difficulty.
+    ///
+    /// Mods are still applied on top of the new value during calculations.
+    #[inline]
+    pub fn set_od(&mut self, od: f32) -> &mut Self {
+        self.od = od;
+
+        self
+    }
+
+    /// Overwrite the map's circle size.
+    ///
+    /// Mods are still applied on top of the new value during calculations.
+    /// Note that for osu!mania maps the circle size denotes the amount of keys.
+    #[inline]
+    pub fn set_cs(&mut self, cs: f32) -> &mut Self {
+        self.cs = cs;
+
+        self
+    }
+
+    /// Overwrite the map's health drain rate.
+    ///
+    /// Mods are still applied on top of the new value during calculations.
+    #[inline]
+    pub fn set_hp(&mut self, hp: f32) -> &mut Self {
+        self.hp = hp;
+
+        self
+    }
+
+    /// The width and height of the playfield in osu!pixels that the calculation assumes.
+    ///
+    /// For osu!catch the width is the horizontal distance that the catcher's centre
+    /// can travel while still catching objects at either edge, i.e. the playfield width
+    /// minus the catcher width. Since the catcher width scales with the CS,
+    /// mods like HR or EZ affect it.
+    ///
+    /// For osu!taiko and osu!mania, object positions are irrelevant so the
+    /// regular playfield size is returned.
+    pub fn playfield_bounds(&self, mode: GameMode, mods: u32) -> (f64, f64) {
+        const WIDTH: f64 = 512.0;
+        const HEIGHT: f64 = 384.0;
+
+        match mode {
+            GameMode::Osu | GameMode::Taiko | GameMode::Mania => (WIDTH, HEIGHT),
+            GameMode::Catch => {
+                let cs = self.attributes().mods(mods).build().cs;
+                let catcher_width = calculate_catch_width(cs as f32) as f64;
+
+                (WIDTH - catcher_width, HEIGHT)
+            }
+        }
+    }
+
+    /// The break periods of the map as specified in the `[Events]` section.
+    #[inline]
+    pub fn breaks(&self) -> &[Break] {
+        &self.breaks
+    }
+
+    /// The MD5 hash of the `.osu` file as lowercase hex, as used by osu! to identify maps.
+    ///
+    /// Returns `None` if the map was not parsed but constructed manually.
+    #[inline]
+    pub fn md5(&self) -> Option<String> {
+        self.md5_hash
+            .map(|hash| hash.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
     /// The beats per minute of the map.
     #[inline]
     pub fn bpm(&self) -> f64 {
@@ -150,6 +264,29 @@ impl Beatmap {
         }
     }
 
+    /// The amount of hit objects that the map consists of after converting it into `mode`.
+    ///
+    /// For osu!catch, fruits, droplets, and tiny droplets are counted individually.
+    /// Unlike a difficulty calculation, no strains are computed.
+    ///
+    /// Returns an error if the map is neither of the given mode nor an osu!standard map.
+    pub fn mode_as_converted(&self, mode: GameMode) -> Result<usize, ConvertError> {
+        if self.mode != mode && self.mode != GameMode::Osu {
+            return Err(ConvertError {
+                from: self.mode,
+                to: mode,
+            });
+        }
+
+        let count = match mode {
+            GameMode::Osu => self.hit_objects.len(),
+            GameMode::Catch => catch::count_objects(self),
+            GameMode::Taiko | GameMode::Mania => self.convert_mode(mode).hit_objects.len(),
+        };
+
+        Ok(count)
+    }
+
     fn clone_without_hit_objects(&self, with_sounds: bool) -> Self {
         Self {
             mode: self.mode,
@@ -172,6 +309,205 @@ impl Beatmap {
             breaks: self.breaks.clone(),
             creator: self.creator.clone(),
             beatmap_id: self.beatmap_id,
+            md5_hash: self.md5_hash,
+        }
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn raw_difficulty_getters() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        assert_eq!(map.approach_rate(), 9.3);
+        assert_eq!(map.overall_difficulty(), 8.8);
+        assert_eq!(map.circle_size(), 4.5);
+        assert_eq!(map.hp_drain_rate(), 5.0);
+    }
+
+    #[test]
+    fn difficulty_setters() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let mut changed = map.clone();
+        changed.set_ar(10.0).set_od(9.0).set_cs(6.0).set_hp(4.0);
+
+        assert_eq!(changed.approach_rate(), 10.0);
+        assert_eq!(changed.overall_difficulty(), 9.0);
+        assert_eq!(changed.circle_size(), 6.0);
+        assert_eq!(changed.hp_drain_rate(), 4.0);
+
+        let attrs = changed.attributes().mods(16).build();
+        assert!((attrs.cs - 7.8).abs() < 1e-6);
+
+        // Smaller circles increase the aim difficulty
+        let aim = crate::OsuStars::new(&map).calculate().aim;
+        let changed_aim = crate::OsuStars::new(&changed).calculate().aim;
+        assert!(changed_aim > aim);
+    }
+
+    #[test]
+    fn playfield_bounds() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+
+        assert_eq!(map.playfield_bounds(GameMode::Osu, 0), (512.0, 384.0));
+        assert_eq!(map.playfield_bounds(GameMode::Osu, 16), (512.0, 384.0));
+
+        let (nomod_width, nomod_height) = map.playfield_bounds(GameMode::Catch, 0);
+        let (hr_width, _) = map.playfield_bounds(GameMode::Catch, 16);
+        let (ez_width, _) = map.playfield_bounds(GameMode::Catch, 2);
+
+        assert_eq!(nomod_height, 384.0);
+        assert!(nomod_width < 512.0);
+
+        // HR shrinks the catcher so it has to travel further
+        assert!(hr_width > nomod_width);
+        assert!(ez_width < nomod_width);
+    }
+
+    #[test]
+    fn parsed_breaks() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let expected = Break {
+            start_time: 48186.0,
+            end_time: 51667.0,
+        };
+
+        assert_eq!(map.breaks(), &[expected]);
+    }
+
+    #[test]
+    fn md5() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let expected = "3ca9a5e23c9a4e9332f4f6e2a48865f7";
+
+        assert_eq!(map.md5().as_deref(), Some(expected));
+
+        let content = std::fs::read("./maps/2785319.osu").unwrap();
+        let from_bytes = Beatmap::from_bytes(&content).unwrap();
+        assert_eq!(from_bytes.md5().as_deref(), Some(expected));
+
+        // Converts keep the hash of the original map
+        let converted = map.convert_mode(GameMode::Taiko);
+        assert_eq!(converted.md5().as_deref(), Some(expected));
+
+        assert_eq!(Beatmap::default().md5(), None);
+    }
+
+    #[test]
+    fn breaks_among_storyboard_events() {
+        let content = b"osu file format v14
+
+[Events]
+//Background and Video events
+0,0,\"bg.jpg\",0,0
+//Break Periods
+2,1000,5000
+Break,6000,9000
+//Storyboard Layer 0 (Background)
+Sprite,Background,Centre,\"sb/bg.png\",320,240
+ F,0,1000,2000,0,1
+ M,0,1000,2000,320,240,0,0
+";
+
+        let map = Beatmap::parse(&content[..]).unwrap();
+
+        let expected = [
+            Break {
+                start_time: 1000.0,
+                end_time: 5000.0,
+            },
+            Break {
+                start_time: 6000.0,
+                end_time: 9000.0,
+            },
+        ];
+
+        assert_eq!(map.breaks(), &expected);
+    }
+
+    #[test]
+    fn mode_as_converted() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let osu = map.mode_as_converted(GameMode::Osu).unwrap();
+        let taiko = map.mode_as_converted(GameMode::Taiko).unwrap();
+        let catch = map.mode_as_converted(GameMode::Catch).unwrap();
+        let mania = map.mode_as_converted(GameMode::Mania).unwrap();
+
+        assert_eq!(osu, map.hit_objects.len());
+        assert_eq!(taiko, map.convert_mode(GameMode::Taiko).hit_objects.len());
+        assert_eq!(mania, map.convert_mode(GameMode::Mania).hit_objects.len());
+        assert_ne!(taiko, catch);
+
+        let attrs = crate::CatchStars::new(&map).calculate();
+        assert_eq!(
+            catch,
+            attrs.n_fruits + attrs.n_droplets + attrs.n_tiny_droplets
+        );
+
+        let taiko_map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        assert!(taiko_map.mode_as_converted(GameMode::Taiko).is_ok());
+
+        let err = taiko_map.mode_as_converted(GameMode::Mania).unwrap_err();
+        assert_eq!(err.from, GameMode::Taiko);
+        assert_eq!(err.to, GameMode::Mania);
+    }
+
+    #[test]
+    fn storyboard_lines_are_skipped() {
+        let bytes = std::fs::read("./maps/2785319.osu").unwrap();
+        let content = String::from_utf8(bytes).unwrap();
+
+        let mut storyboard = String::new();
+
+        for i in 0..20_000 {
+            storyboard.push_str("Sprite,Foreground,Centre,\"sb/star.png\",320,240\r\n");
+            storyboard.push_str(&format!(" F,0,{},{},1,0\r\n", i, i + 500));
+            storyboard.push_str(&format!(" M,0,{},{},0,0,640,480\r\n", i, i + 500));
+        }
+
+        let layer = "//Storyboard Layer 0 (Background)\n";
+        let idx = content.find(layer).unwrap() + layer.len();
+
+        let mut with_storyboard = content.into_bytes();
+        with_storyboard.splice(idx..idx, storyboard.into_bytes());
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let storyboard_map = Beatmap::from_bytes(&with_storyboard).unwrap();
+
+        assert_eq!(storyboard_map.breaks, map.breaks);
+        assert_eq!(storyboard_map.hit_objects, map.hit_objects);
+        assert_eq!(
+            crate::OsuStars::new(&storyboard_map).calculate(),
+            crate::OsuStars::new(&map).calculate()
+        );
+    }
+
+    #[test]
+    fn attributes_without_hit_objects() {
+        let maps = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        for path in maps.iter() {
+            let map = Beatmap::from_path(path).unwrap();
+            let mut builder = BeatmapAttributesBuilder::from_path(path).unwrap();
+
+            assert_eq!(builder, map.attributes(), "{}", path);
+
+            let attrs = builder.mods(64).build();
+            assert_eq!(attrs, map.attributes().mods(64).build(), "{}", path);
+            assert!(map.bpm() > 0.0, "{}", path);
+            assert!((attrs.bpm - map.bpm() * 1.5).abs() < 1e-9, "{}", path);
         }
     }
 }
@@ -17,3 +17,47 @@ impl Default for GameMode {
         Self::Osu
     }
 }
+
+impl GameMode {
+    /// Convert the integer that osu! uses for a mode into a [`GameMode`].
+    ///
+    /// Returns `None` if the integer does not belong to any mode.
+    #[inline]
+    pub fn from_int(mode: u8) -> Option<Self> {
+        match mode {
+            0 => Some(Self::Osu),
+            1 => Some(Self::Taiko),
+            2 => Some(Self::Catch),
+            3 => Some(Self::Mania),
+            _ => None,
+        }
+    }
+
+    /// The integer that osu! uses for the mode.
+    #[inline]
+    pub fn as_int(self) -> u8 {
+        self as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameMode;
+
+    #[test]
+    fn int_round_trip() {
+        assert_eq!(GameMode::Taiko.as_int(), 1);
+        assert_eq!(GameMode::from_int(1), Some(GameMode::Taiko));
+
+        for mode in [
+            GameMode::Osu,
+            GameMode::Taiko,
+            GameMode::Catch,
+            GameMode::Mania,
+        ] {
+            assert_eq!(GameMode::from_int(mode.as_int()), Some(mode));
+        }
+
+        assert_eq!(GameMode::from_int(4), None);
+    }
+}
@@ -0,0 +1,361 @@
+use std::{convert::TryFrom, error::Error as StdError, fmt};
+
+use crate::{
+    catch::CatchDifficultyAttributes, mania::ManiaDifficultyAttributes,
+    osu::OsuDifficultyAttributes, taiko::TaikoDifficultyAttributes, DifficultyAttributes, GameMode,
+};
+
+/// Version of the binary encoding of [`DifficultyAttributes`].
+///
+/// Bumped whenever the layout or the difficulty algorithm changes so that
+/// previously encoded attributes are recognized as stale.
+pub const ATTRIBUTES_VERSION: u16 = 2;
+
+// Version, mode, and mods
+const HEADER_LEN: usize = 2 + 1 + 4;
+
+/// Error returned by [`DifficultyAttributes::from_bytes`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AttributesDecodeError {
+    /// The attributes were encoded with a different [`ATTRIBUTES_VERSION`].
+    Version {
+        /// The current version.
+        expected: u16,
+        /// The version of the encoded attributes.
+        found: u16,
+    },
+    /// The mode byte does not belong to any mode.
+    Mode(u8),
+    /// The bytes ended before all attributes were decoded.
+    Truncated,
+    /// There are bytes left after all attributes were decoded.
+    TrailingBytes,
+    /// An encoded count does not fit into a `usize` on this platform.
+    Overflow(u64),
+}
+
+impl fmt::Display for AttributesDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Version { expected, found } => write!(
+                f,
+                "attributes were encoded with version {} but the current version is {}",
+                found, expected
+            ),
+            Self::Mode(mode) => write!(f, "invalid mode {}", mode),
+            Self::Truncated => f.write_str("encoded attributes are incomplete"),
+            Self::TrailingBytes => f.write_str("encoded attributes are followed by unknown bytes"),
+            Self::Overflow(count) => write!(f, "count {} does not fit into a usize", count),
+        }
+    }
+}
+
+impl StdError for AttributesDecodeError {}
+
+impl DifficultyAttributes {
+    /// Encode the attributes into a compact binary format, e.g. to store them in a database.
+    ///
+    /// The encoding starts with the [`ATTRIBUTES_VERSION`] as little-endian `u16`,
+    /// the mode as `u8`, and the mods as little-endian `u32`.
+    /// Use [`from_bytes`](DifficultyAttributes::from_bytes) to decode them again.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer(Vec::with_capacity(HEADER_LEN + 20 * 8));
+        writer.u16(ATTRIBUTES_VERSION);
+
+        match self {
+            Self::Osu(attrs) => {
+                writer.header(GameMode::Osu, attrs.mods);
+                writer.f64(attrs.aim);
+                writer.f64(attrs.speed);
+                writer.f64(attrs.flashlight);
+                writer.f64(attrs.slider_factor);
+                writer.f64(attrs.flashlight_slider_factor);
+                writer.f64(attrs.aim_angle_bonus);
+                writer.f64(attrs.aim_peak_variation);
+                writer.f64(attrs.flow_aim_ratio);
+                writer.f64(attrs.speed_note_count);
+                writer.f64(attrs.speed_spacing_factor);
+                writer.bool(attrs.has_simultaneous_objects);
+                writer.f64(attrs.ar);
+                writer.f64(attrs.od);
+                writer.f64(attrs.hp);
+                writer.usize(attrs.n_circles);
+                writer.usize(attrs.n_sliders);
+                writer.usize(attrs.n_spinners);
+                writer.f64(attrs.stars);
+                writer.usize(attrs.max_combo);
+            }
+            Self::Taiko(attrs) => {
+                writer.header(GameMode::Taiko, attrs.mods);
+                writer.f64(attrs.stamina);
+                writer.f64(attrs.rhythm);
+                writer.f64(attrs.colour);
+                writer.f64(attrs.peak);
+                writer.f64(attrs.finisher_difficulty);
+                writer.f64(attrs.hit_window);
+                writer.f64(attrs.stars);
+                writer.usize(attrs.max_combo);
+                writer.usize(attrs.n_finishers);
+            }
+            Self::Catch(attrs) => {
+                writer.header(GameMode::Catch, attrs.mods);
+                writer.f64(attrs.stars);
+                writer.f64(attrs.ar);
+                writer.usize(attrs.n_fruits);
+                writer.usize(attrs.n_droplets);
+                writer.usize(attrs.n_tiny_droplets);
+                writer.f64(attrs.hyper_dash_share);
+                writer.usize(attrs.n_hyper_dashes);
+            }
+            Self::Mania(attrs) => {
+                writer.header(GameMode::Mania, attrs.mods);
+                writer.f64(attrs.stars);
+                writer.f64(attrs.hit_window);
+                writer.usize(attrs.max_combo);
+            }
+        }
+
+        writer.0
+    }
+
+    /// Decode attributes that were encoded through [`to_bytes`](DifficultyAttributes::to_bytes).
+    ///
+    /// Attributes of a different [`ATTRIBUTES_VERSION`] are rejected
+    /// since they were calculated with an outdated algorithm.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AttributesDecodeError> {
+        let mut reader = Reader(bytes);
+
+        let version = reader.u16()?;
+
+        if version != ATTRIBUTES_VERSION {
+            return Err(AttributesDecodeError::Version {
+                expected: ATTRIBUTES_VERSION,
+                found: version,
+            });
+        }
+
+        let mode = reader.u8()?;
+        let mode = GameMode::from_int(mode).ok_or(AttributesDecodeError::Mode(mode))?;
+        let mods = reader.u32()?;
+
+        let attrs = match mode {
+            GameMode::Osu => Self::Osu(OsuDifficultyAttributes {
+                aim: reader.f64()?,
+                speed: reader.f64()?,
+                flashlight: reader.f64()?,
+                slider_factor: reader.f64()?,
+                flashlight_slider_factor: reader.f64()?,
+                aim_angle_bonus: reader.f64()?,
+                aim_peak_variation: reader.f64()?,
+                flow_aim_ratio: reader.f64()?,
+                speed_note_count: reader.f64()?,
+                speed_spacing_factor: reader.f64()?,
+                has_simultaneous_objects: reader.bool()?,
+                ar: reader.f64()?,
+                od: reader.f64()?,
+                hp: reader.f64()?,
+                n_circles: reader.usize()?,
+                n_sliders: reader.usize()?,
+                n_spinners: reader.usize()?,
+                stars: reader.f64()?,
+                max_combo: reader.usize()?,
+                mods,
+            }),
+            GameMode::Taiko => Self::Taiko(TaikoDifficultyAttributes {
+                stamina: reader.f64()?,
+                rhythm: reader.f64()?,
+                colour: reader.f64()?,
+                peak: reader.f64()?,
+                finisher_difficulty: reader.f64()?,
+                hit_window: reader.f64()?,
+                stars: reader.f64()?,
+                max_combo: reader.usize()?,
+                n_finishers: reader.usize()?,
+                mods,
+            }),
+            GameMode::Catch => Self::Catch(CatchDifficultyAttributes {
+                stars: reader.f64()?,
+                ar: reader.f64()?,
+                n_fruits: reader.usize()?,
+                n_droplets: reader.usize()?,
+                n_tiny_droplets: reader.usize()?,
+                hyper_dash_share: reader.f64()?,
+                n_hyper_dashes: reader.usize()?,
+                mods,
+            }),
+            GameMode::Mania => Self::Mania(ManiaDifficultyAttributes {
+                stars: reader.f64()?,
+                hit_window: reader.f64()?,
+                max_combo: reader.usize()?,
+                mods,
+            }),
+        };
+
+        if reader.0.is_empty() {
+            Ok(attrs)
+        } else {
+            Err(AttributesDecodeError::TrailingBytes)
+        }
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn header(&mut self, mode: GameMode, mods: u32) {
+        self.0.push(mode.as_int());
+        self.u32(mods);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.0.push(value as u8);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    // Counts are stored as `u64` so that they're the same across platforms
+    fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+struct Reader<'b>(&'b [u8]);
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], AttributesDecodeError> {
+        if self.0.len() < N {
+            return Err(AttributesDecodeError::Truncated);
+        }
+
+        let (bytes, rest) = self.0.split_at(N);
+        self.0 = rest;
+
+        let mut buf = [0; N];
+        buf.copy_from_slice(bytes);
+
+        Ok(buf)
+    }
+
+    fn bool(&mut self) -> Result<bool, AttributesDecodeError> {
+        self.u8().map(|byte| byte != 0)
+    }
+
+    fn u8(&mut self) -> Result<u8, AttributesDecodeError> {
+        self.take::<1>().map(|[byte]| byte)
+    }
+
+    fn u16(&mut self) -> Result<u16, AttributesDecodeError> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, AttributesDecodeError> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, AttributesDecodeError> {
+        self.take().map(u64::from_le_bytes)
+    }
+
+    fn usize(&mut self) -> Result<usize, AttributesDecodeError> {
+        let value = self.u64()?;
+
+        usize::try_from(value).map_err(|_| AttributesDecodeError::Overflow(value))
+    }
+
+    fn f64(&mut self) -> Result<f64, AttributesDecodeError> {
+        self.take().map(f64::from_le_bytes)
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use crate::{Beatmap, BeatmapExt};
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let maps = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        for path in maps.iter() {
+            let map = Beatmap::from_path(path).unwrap();
+            let attrs = map.stars().mods(8 + 64).calculate();
+            let bytes = attrs.to_bytes();
+
+            assert_eq!(&bytes[..2], &ATTRIBUTES_VERSION.to_le_bytes());
+            assert_eq!(bytes[2], map.mode.as_int(), "{}", path);
+
+            let decoded = DifficultyAttributes::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded.to_bytes(), bytes, "{}", path);
+            assert_eq!(decoded.stars(), attrs.stars(), "{}", path);
+            assert_eq!(decoded.max_combo(), attrs.max_combo(), "{}", path);
+
+            let err = DifficultyAttributes::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err();
+            assert_eq!(err, AttributesDecodeError::Truncated);
+
+            let mut trailing = bytes.clone();
+            trailing.push(0);
+            let err = DifficultyAttributes::from_bytes(&trailing).unwrap_err();
+            assert_eq!(err, AttributesDecodeError::TrailingBytes);
+        }
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn large_counts() {
+        let attrs = ManiaDifficultyAttributes {
+            max_combo: u32::MAX as usize + 1,
+            ..Default::default()
+        };
+
+        let bytes = DifficultyAttributes::Mania(attrs).to_bytes();
+
+        match DifficultyAttributes::from_bytes(&bytes) {
+            Ok(DifficultyAttributes::Mania(decoded)) => {
+                assert_eq!(decoded.max_combo, u32::MAX as usize + 1)
+            }
+            other => panic!("expected mania attributes, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_stale_versions() {
+        let mut bytes = DifficultyAttributes::Mania(Default::default()).to_bytes();
+        bytes[..2].copy_from_slice(&(ATTRIBUTES_VERSION - 1).to_le_bytes());
+
+        let err = DifficultyAttributes::from_bytes(&bytes).unwrap_err();
+
+        let expected = AttributesDecodeError::Version {
+            expected: ATTRIBUTES_VERSION,
+            found: ATTRIBUTES_VERSION - 1,
+        };
+
+        assert_eq!(err, expected);
+
+        bytes[..2].copy_from_slice(&ATTRIBUTES_VERSION.to_le_bytes());
+        bytes[2] = 4;
+
+        let err = DifficultyAttributes::from_bytes(&bytes).unwrap_err();
+        assert_eq!(err, AttributesDecodeError::Mode(4));
+    }
+}
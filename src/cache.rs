@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+
+use crate::{
+    mods::difficulty_mods, recalc::set_mods, AnyStars, Beatmap, DifficultyAttributes, GameMode,
+    Mods,
+};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct CacheKey {
+    md5: [u8; 16],
+    mode: GameMode,
+    mods: u32,
+    clock_rate: u64,
+    // Bits of AR, OD, CS, and HP so that overwritten values are not mixed up
+    difficulty: [u32; 4],
+    n_objects: usize,
+}
+
+/// Difficulty calculator that memoizes the resulting attributes.
+///
+/// Attributes are keyed by the map's MD5 hash, mode, difficulty settings, amount of
+/// hit objects, clock rate, and the mods that affect the difficulty in the map's mode.
+/// Once the capacity is reached, the least recently used attributes are discarded.
+///
+/// Maps without MD5 hash, i.e. maps that were not parsed from a file or bytes,
+/// are never cached. If the hit objects of a map are modified without changing
+/// their amount, its `md5_hash` should be set to `None`.
+///
+/// # Example
+///
+/// ```no_run
+/// use rosu_pp::{Beatmap, CachedCalculator};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+///
+/// let mut calculator = CachedCalculator::new(100);
+///
+/// let attrs = calculator.calculate(&map, 8 + 64, None);
+///
+/// // Calculated only once
+/// let cached = calculator.calculate(&map, 8 + 64, None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CachedCalculator {
+    capacity: usize,
+    // Most recently used attributes are at the front
+    entries: VecDeque<(CacheKey, DifficultyAttributes)>,
+}
+
+impl CachedCalculator {
+    /// Create a new calculator that caches up to `capacity` many attributes.
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity.min(1024)),
+        }
+    }
+
+    /// Calculate the difficulty attributes of the map or return
+    /// them from the cache if they were calculated before.
+    ///
+    /// If no clock rate is specified, the clock rate of the mods is used.
+    pub fn calculate(
+        &mut self,
+        map: &Beatmap,
+        mods: u32,
+        clock_rate: Option<f64>,
+    ) -> DifficultyAttributes {
+        let clock_rate = clock_rate.unwrap_or_else(|| mods.clock_rate());
+
+        let calculate = || {
+            AnyStars::new(map)
+                .mods(mods)
+                .clock_rate(clock_rate)
+                .calculate()
+        };
+
+        let key = match map.md5_hash {
+            Some(md5) => CacheKey {
+                md5,
+                mode: map.mode,
+                mods: difficulty_mods(mods, map.mode),
+                clock_rate: clock_rate.to_bits(),
+                difficulty: [
+                    map.ar.to_bits(),
+                    map.od.to_bits(),
+                    map.cs.to_bits(),
+                    map.hp.to_bits(),
+                ],
+                n_objects: map.hit_objects.len(),
+            },
+            None => return calculate(),
+        };
+
+        if let Some(idx) = self.entries.iter().position(|(entry, _)| *entry == key) {
+            let entry = self.entries.remove(idx).unwrap();
+            let mut attrs = entry.1.clone();
+            self.entries.push_front(entry);
+            set_mods(&mut attrs, mods);
+
+            return attrs;
+        }
+
+        let attrs = calculate();
+
+        if self.capacity > 0 {
+            self.entries.truncate(self.capacity - 1);
+            self.entries.push_front((key, attrs.clone()));
+        }
+
+        attrs
+    }
+
+    /// The maximum amount of cached attributes.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The amount of currently cached attributes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no attributes are cached.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Discard all cached attributes.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cached_attributes() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let mut calculator = CachedCalculator::new(2);
+
+        let first = calculator.calculate(&map, 8 + 64, None);
+        assert_eq!(calculator.len(), 1);
+
+        // NC is the same as DT
+        let cached = calculator.calculate(&map, 8 + 64 + 512, None);
+        assert_eq!(calculator.len(), 1);
+        assert_eq!(cached.stars(), first.stars());
+
+        match cached {
+            DifficultyAttributes::Osu(attrs) => assert_eq!(attrs.mods, 8 + 64 + 512),
+            _ => unreachable!(),
+        }
+
+        // Different mods are recomputed
+        let hard_rock = calculator.calculate(&map, 8 + 16, None);
+        assert_eq!(calculator.len(), 2);
+        assert!(hard_rock.stars() < first.stars());
+
+        // Explicit clock rate equal to the mods' rate is the same key
+        calculator.calculate(&map, 8 + 64, Some(1.5));
+        assert_eq!(calculator.len(), 2);
+
+        // Least recently used entry, i.e. HR, is evicted
+        calculator.calculate(&map, 0, None);
+        assert_eq!(calculator.len(), 2);
+
+        let recomputed = calculator.calculate(&map, 8 + 16, None);
+        assert_eq!(recomputed.stars(), hard_rock.stars());
+    }
+
+    #[test]
+    fn mutated_maps() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let mut calculator = CachedCalculator::new(4);
+
+        let first = calculator.calculate(&map, 0, None);
+
+        // Same hash but different content so the attributes are recomputed
+        let mut truncated = map.clone();
+        truncated.hit_objects.truncate(10);
+
+        let attrs = calculator.calculate(&truncated, 0, None);
+        assert_eq!(calculator.len(), 2);
+        assert!(attrs.max_combo() < first.max_combo());
+
+        let mut high_ar = map.clone();
+        high_ar.set_ar(10.0);
+
+        let attrs = calculator.calculate(&high_ar, 0, None);
+        assert_eq!(calculator.len(), 3);
+
+        match (attrs, first) {
+            (DifficultyAttributes::Osu(attrs), DifficultyAttributes::Osu(first)) => {
+                assert!(attrs.ar > first.ar)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn uncached_maps() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let mut calculator = CachedCalculator::new(0);
+        calculator.calculate(&map, 0, None);
+        assert!(calculator.is_empty());
+
+        let unhashed = Beatmap {
+            md5_hash: None,
+            ..map
+        };
+
+        let mut calculator = CachedCalculator::new(4);
+        calculator.calculate(&unhashed, 0, None);
+        assert!(calculator.is_empty());
+    }
+}
@@ -68,6 +68,7 @@ impl<'map> CatchGradualDifficultyAttributes<'map> {
 
         let attributes = CatchDifficultyAttributes {
             ar: map_attributes.ar,
+            mods,
             ..Default::default()
         };
 
@@ -154,6 +155,8 @@ impl Iterator for CatchGradualDifficultyAttributes<'_> {
         }
 
         let mut attributes = self.hit_objects.attributes();
+        attributes.hyper_dash_share = self.movement.hyper_dash_share();
+        attributes.n_hyper_dashes = self.movement.n_hyper_dashes;
         attributes.stars =
             Movement::difficulty_value(&mut self.strain_peak_buf).sqrt() * STAR_SCALING_FACTOR;
 
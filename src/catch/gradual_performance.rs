@@ -7,6 +7,7 @@ use super::{CatchGradualDifficultyAttributes, CatchPerformanceAttributes};
 ///
 /// This struct is used for [`CatchGradualPerformanceAttributes`].
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchScoreState {
     /// Maximum combo that the score has had so far.
     /// **Not** the maximum possible combo of the map so far.
@@ -14,9 +14,14 @@ pub use gradual_performance::*;
 use movement::Movement;
 pub use pp::*;
 
-use crate::{catch::fruit_or_juice::FruitParams, curve::CurveBuffers, Beatmap, Mods, OsuStars};
+use crate::{
+    catch::fruit_or_juice::FruitParams, curve::CurveBuffers, Beatmap, Mods, OsuStars,
+    PerformanceBreakdown, PerformanceComponent,
+};
 
 const SECTION_LENGTH: f64 = 750.0;
+// Lower bound for custom section lengths so that sections always advance.
+const MIN_SECTION_LEN: f64 = 1.0;
 const STAR_SCALING_FACTOR: f64 = 0.153;
 
 const ALLOWED_CATCH_RANGE: f32 = 0.8;
@@ -42,10 +47,10 @@ const CATCHER_SIZE: f32 = 106.75;
 /// ```
 #[derive(Clone, Debug)]
 pub struct CatchStars<'map> {
-    map: &'map Beatmap,
-    mods: u32,
+    pub(crate) map: &'map Beatmap,
+    pub(crate) mods: u32,
     passed_objects: Option<usize>,
-    clock_rate: Option<f64>,
+    pub(crate) clock_rate: Option<f64>,
 }
 
 impl<'map> CatchStars<'map> {
@@ -95,7 +100,9 @@ impl<'map> CatchStars<'map> {
     /// Calculate all difficulty related values, including stars.
     #[inline]
     pub fn calculate(self) -> CatchDifficultyAttributes {
-        let (mut movement, mut attributes) = calculate_movement(self);
+        let (mut movement, mut attributes) = calculate_movement(self, SECTION_LENGTH);
+        attributes.hyper_dash_share = movement.hyper_dash_share();
+        attributes.n_hyper_dashes = movement.n_hyper_dashes;
         attributes.stars =
             Movement::difficulty_value(&mut movement.strain_peaks).sqrt() * STAR_SCALING_FACTOR;
 
@@ -107,23 +114,63 @@ impl<'map> CatchStars<'map> {
     /// Suitable to plot the difficulty of a map over time.
     #[inline]
     pub fn strains(self) -> CatchStrains {
-        let (movement, _) = calculate_movement(self);
+        self.strains_with_section_len(SECTION_LENGTH)
+    }
+
+    /// Same as [`strains`](CatchStars::strains) but each strain peak covers
+    /// `section_len` ms instead of the default 750ms.
+    ///
+    /// The section length is clamped to be at least 1ms.
+    pub fn strains_with_section_len(self, section_len: f64) -> CatchStrains {
+        let section_len = section_len.max(MIN_SECTION_LEN);
+        let (movement, _) = calculate_movement(self, section_len);
 
         CatchStrains {
-            section_len: SECTION_LENGTH,
+            section_len,
             movement: movement.strain_peaks,
+            hyper_dash: movement.hyper_dash_sections,
         }
     }
+
+    /// End time of the first strain section, adjusted by the clock rate.
+    pub(crate) fn first_section_end(&self, section_len: f64) -> Option<f64> {
+        let take = self.passed_objects.unwrap_or(usize::MAX);
+        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+
+        let mut params = FruitParams {
+            attributes: CatchDifficultyAttributes::default(),
+            curve_bufs: CurveBuffers::default(),
+            last_pos: None,
+            last_time: 0.0,
+            map: self.map,
+            ticks: Vec::new(),
+            with_hr: self.mods.hr(),
+        };
+
+        // Sections start with the second object since the first one has no strain
+        self.map
+            .hit_objects
+            .iter()
+            .filter_map(|h| FruitOrJuice::new(h, &mut params))
+            .flatten()
+            .take(take)
+            .nth(1)
+            .map(|h| (h.time / clock_rate / section_len).ceil() * section_len)
+    }
 }
 
 /// The result of calculating the strains on a osu!catch map.
 /// Suitable to plot the difficulty of a map over time.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchStrains {
     /// Time in ms inbetween two strains.
     pub section_len: f64,
     /// Strain peaks of the movement skill.
     pub movement: Vec<f64>,
+    /// Whether the section of the corresponding movement peak
+    /// contains an object that is reached through a hyperdash.
+    pub hyper_dash: Vec<bool>,
 }
 
 impl CatchStrains {
@@ -135,7 +182,10 @@ impl CatchStrains {
     }
 }
 
-fn calculate_movement(params: CatchStars<'_>) -> (Movement, CatchDifficultyAttributes) {
+fn calculate_movement(
+    params: CatchStars<'_>,
+    section_len: f64,
+) -> (Movement, CatchDifficultyAttributes) {
     let CatchStars {
         map,
         mods,
@@ -149,6 +199,7 @@ fn calculate_movement(params: CatchStars<'_>) -> (Movement, CatchDifficultyAttri
 
     let attributes = CatchDifficultyAttributes {
         ar: map_attributes.ar,
+        mods,
         ..Default::default()
     };
 
@@ -185,7 +236,7 @@ fn calculate_movement(params: CatchStars<'_>) -> (Movement, CatchDifficultyAttri
         (None, Some(_)) => unreachable!(),
     };
 
-    let mut curr_section_end = (curr.time / clock_rate / SECTION_LENGTH).ceil() * SECTION_LENGTH;
+    let mut curr_section_end = (curr.time / clock_rate / section_len).ceil() * section_len;
 
     prev.init_hyper_dash(
         half_catcher_width,
@@ -216,7 +267,7 @@ fn calculate_movement(params: CatchStars<'_>) -> (Movement, CatchDifficultyAttri
         while base_time > curr_section_end {
             movement.save_current_peak();
             movement.start_new_section_from(curr_section_end);
-            curr_section_end += SECTION_LENGTH;
+            curr_section_end += section_len;
         }
 
         movement.process(&h);
@@ -228,6 +279,28 @@ fn calculate_movement(params: CatchStars<'_>) -> (Movement, CatchDifficultyAttri
     (movement, params.attributes)
 }
 
+/// The amount of fruits, droplets, and tiny droplets in the map.
+pub(crate) fn count_objects(map: &Beatmap) -> usize {
+    let mut params = FruitParams {
+        attributes: CatchDifficultyAttributes::default(),
+        curve_bufs: CurveBuffers::default(),
+        last_pos: None,
+        last_time: 0.0,
+        map,
+        ticks: Vec::new(),
+        with_hr: false,
+    };
+
+    for h in map.hit_objects.iter() {
+        // Counts are tracked while creating the objects
+        let _ = FruitOrJuice::new(h, &mut params);
+    }
+
+    let attrs = params.attributes;
+
+    attrs.n_fruits + attrs.n_droplets + attrs.n_tiny_droplets
+}
+
 #[inline]
 pub(crate) fn calculate_catch_width(cs: f32) -> f32 {
     let scale = 1.0 - 0.7 * (cs - 5.0) / 5.0;
@@ -237,6 +310,7 @@ pub(crate) fn calculate_catch_width(cs: f32) -> f32 {
 
 /// The result of a difficulty calculation on an osu!catch map.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchDifficultyAttributes {
     /// The final star rating
     pub stars: f64,
@@ -248,6 +322,15 @@ pub struct CatchDifficultyAttributes {
     pub n_droplets: usize,
     /// The amount of tiny droplets.
     pub n_tiny_droplets: usize,
+    /// The share between `0.0` and `1.0` of the movement strain
+    /// that stems from objects which are reached through a hyperdash.
+    pub hyper_dash_share: f64,
+    /// The amount of objects that are reached through a hyperdash.
+    ///
+    /// For partial plays only hyperdashes between passed objects are counted.
+    pub n_hyper_dashes: usize,
+    /// The mods that the attributes were calculated with.
+    pub mods: u32,
 }
 
 impl CatchDifficultyAttributes {
@@ -260,11 +343,20 @@ impl CatchDifficultyAttributes {
 
 /// The result of a performance calculation on an osu!catch map.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchPerformanceAttributes {
     /// The difficulty attributes that were used for the performance calculation
     pub difficulty: CatchDifficultyAttributes,
     /// The final performance points.
     pub pp: f64,
+    /// The portion of the final pp for movement without hyperdashes.
+    pub pp_dash: f64,
+    /// The portion of the final pp for hyperdashes.
+    pub pp_hyper_dash: f64,
+    /// The accuracy between `0.0` and `1.0` of the hitresults that were used.
+    pub accuracy: f64,
+    /// The combo of the score that was used.
+    pub combo: usize,
 }
 
 impl CatchPerformanceAttributes {
@@ -280,6 +372,18 @@ impl CatchPerformanceAttributes {
         self.pp
     }
 
+    /// Return the portions of the final pp per skill.
+    #[inline]
+    pub fn breakdown(&self) -> PerformanceBreakdown {
+        PerformanceBreakdown {
+            pp: self.pp,
+            components: vec![
+                (PerformanceComponent::Dash, self.pp_dash),
+                (PerformanceComponent::HyperDash, self.pp_hyper_dash),
+            ],
+        }
+    }
+
     /// Return the maximum combo of the map.
     #[inline]
     pub fn max_combo(&self) -> usize {
@@ -302,6 +406,7 @@ impl<'map> From<OsuStars<'map>> for CatchStars<'map> {
             mods,
             passed_objects,
             clock_rate,
+            ..
         } = osu;
 
         Self {
@@ -312,3 +417,88 @@ impl<'map> From<OsuStars<'map>> for CatchStars<'map> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::{HitObject, HitObjectKind, Pos2};
+
+    #[test]
+    fn hyper_dash_sections() {
+        let calm = (0..=6).map(|i| (256.0, i as f64 * 500.0));
+        let jumps = [
+            (0.0, 3150.0),
+            (512.0, 3300.0),
+            (0.0, 3450.0),
+            (512.0, 3600.0),
+        ];
+        let calm_again = (9..=14).map(|i| (256.0, i as f64 * 500.0));
+
+        let hit_objects = calm
+            .chain(jumps)
+            .chain(calm_again)
+            .map(|(x, start_time)| HitObject {
+                pos: Pos2 { x, y: 192.0 },
+                start_time,
+                kind: HitObjectKind::Circle,
+            })
+            .collect();
+
+        let map = Beatmap {
+            version: 14,
+            ar: 9.0,
+            od: 8.0,
+            cs: 4.0,
+            hp: 5.0,
+            slider_mult: 1.4,
+            tick_rate: 1.0,
+            stack_leniency: 0.7,
+            hit_objects,
+            ..Default::default()
+        };
+
+        let strains = CatchStars::new(&map).strains();
+        assert_eq!(strains.hyper_dash.len(), strains.movement.len());
+
+        // Sections end at multiples of 750ms, starting with 750ms, so all
+        // hyperdashes between 3150ms and 3600ms land in the fifth section
+        let marked: Vec<_> = strains
+            .hyper_dash
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &hyper_dash)| hyper_dash.then_some(i))
+            .collect();
+
+        assert_eq!(marked, vec![4]);
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn hyper_dashes_of_passed_objects() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+
+        let full = CatchStars::new(&map).mods(16).calculate();
+        assert!(full.n_hyper_dashes > 0);
+
+        // Hyperdash counts after every processed object, including droplets within sliders
+        let prefix_counts: Vec<_> = CatchGradualDifficultyAttributes::new(&map, 16)
+            .map(|attrs| attrs.n_hyper_dashes)
+            .collect();
+
+        assert_eq!(prefix_counts.last(), Some(&full.n_hyper_dashes));
+
+        for passed_objects in (1..prefix_counts.len()).step_by(37) {
+            let partial = CatchStars::new(&map)
+                .mods(16)
+                .passed_objects(passed_objects)
+                .calculate();
+
+            assert_eq!(
+                partial.n_hyper_dashes,
+                prefix_counts[passed_objects - 1],
+                "passed objects: {}",
+                passed_objects
+            );
+        }
+    }
+}
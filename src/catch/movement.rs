@@ -22,7 +22,15 @@ pub(crate) struct Movement {
     pub(crate) curr_section_peak: f64,
 
     pub(crate) strain_peaks: Vec<f64>,
+    /// Whether each section contains an object that is reached through a hyperdash.
+    pub(crate) hyper_dash_sections: Vec<bool>,
+    curr_section_hyper_dash: bool,
+    /// The amount of processed objects that are reached through a hyperdash.
+    pub(crate) n_hyper_dashes: usize,
     prev_time: Option<f64>,
+
+    total_strain: f64,
+    hyper_dash_strain: f64,
 }
 
 impl Movement {
@@ -42,27 +50,53 @@ impl Movement {
             curr_section_peak: 1.0,
 
             strain_peaks: Vec::with_capacity(128),
+            hyper_dash_sections: Vec::with_capacity(128),
+            curr_section_hyper_dash: false,
+            n_hyper_dashes: 0,
             prev_time: None,
+
+            total_strain: 0.0,
+            hyper_dash_strain: 0.0,
         }
     }
 
     #[inline]
     pub(crate) fn save_current_peak(&mut self) {
         self.strain_peaks.push(self.curr_section_peak);
+        self.hyper_dash_sections.push(self.curr_section_hyper_dash);
     }
 
     #[inline]
     pub(crate) fn start_new_section_from(&mut self, time: f64) {
         self.curr_section_peak = self.peak_strain(time - self.prev_time.unwrap());
+        self.curr_section_hyper_dash = false;
     }
 
     pub(crate) fn process(&mut self, current: &DifficultyObject<'_>) {
+        let strain = self.strain_value_of(current) * SKILL_MULTIPLIER;
+        self.total_strain += strain;
+
+        if current.last.hyper_dash {
+            self.hyper_dash_strain += strain;
+            self.curr_section_hyper_dash = true;
+            self.n_hyper_dashes += 1;
+        }
+
         self.current_strain *= strain_decay(current.delta);
-        self.current_strain += self.strain_value_of(current) * SKILL_MULTIPLIER;
+        self.current_strain += strain;
         self.curr_section_peak = self.current_strain.max(self.curr_section_peak);
         self.prev_time.replace(current.start_time);
     }
 
+    /// The share of all strain so far that was added by objects reached through a hyperdash.
+    pub(crate) fn hyper_dash_share(&self) -> f64 {
+        if self.total_strain > 0.0 {
+            self.hyper_dash_strain / self.total_strain
+        } else {
+            0.0
+        }
+    }
+
     pub(crate) fn difficulty_value(strain_peaks: &mut [f64]) -> f64 {
         let mut difficulty = 0.0;
         let mut weight = 1.0;
@@ -1,5 +1,7 @@
 use super::{CatchDifficultyAttributes, CatchPerformanceAttributes, CatchScoreState, CatchStars};
-use crate::{Beatmap, DifficultyAttributes, Mods, OsuPP, PerformanceAttributes};
+use crate::{
+    Beatmap, DifficultyAttributes, GameMode, Mods, ModsMismatch, OsuPP, PerformanceAttributes,
+};
 
 /// Performance calculator on osu!catch maps.
 ///
@@ -37,6 +39,7 @@ pub struct CatchPP<'map> {
     attributes: Option<CatchDifficultyAttributes>,
     mods: u32,
     combo: Option<usize>,
+    acc: Option<f64>,
 
     pub(crate) n_fruits: Option<usize>,
     pub(crate) n_droplets: Option<usize>,
@@ -56,6 +59,7 @@ impl<'map> CatchPP<'map> {
             attributes: None,
             mods: 0,
             combo: None,
+            acc: None,
 
             n_fruits: None,
             n_droplets: None,
@@ -122,6 +126,9 @@ impl<'map> CatchPP<'map> {
     }
 
     /// Specify the amount of tiny droplet misses of a play i.e. n_katu.
+    ///
+    /// Just like on stable, missed tiny droplets only lower the accuracy
+    /// and neither break combo nor count towards the miss penalty.
     #[inline]
     pub fn tiny_droplet_misses(mut self, n_tiny_droplet_misses: usize) -> Self {
         self.n_tiny_droplet_misses.replace(n_tiny_droplet_misses);
@@ -130,6 +137,9 @@ impl<'map> CatchPP<'map> {
     }
 
     /// Specify the amount of fruit / droplet misses of the play.
+    ///
+    /// Missed tiny droplets should be specified through
+    /// [`tiny_droplet_misses`](CatchPP::tiny_droplet_misses) instead.
     #[inline]
     pub fn misses(mut self, n_misses: usize) -> Self {
         self.n_misses = Some(n_misses);
@@ -160,6 +170,10 @@ impl<'map> CatchPP<'map> {
     }
 
     /// Provide parameters through an [`CatchScoreState`].
+    ///
+    /// Values can be overridden by calling the individual setters afterwards,
+    /// e.g. `.state(state).combo(max_combo)` to calculate an if-FC
+    /// with the same hitresults.
     #[inline]
     pub fn state(mut self, state: CatchScoreState) -> Self {
         let CatchScoreState {
@@ -181,83 +195,124 @@ impl<'map> CatchPP<'map> {
         self
     }
 
-    // TODO: adjust this on the next rework
-    /// Generate the hit results with respect to the given accuracy between `0.0` and `100.0`.
+    /// Specify the accuracy of a play between `0.0` and `100.0`.
+    /// This will be used to generate matching hitresults.
     ///
-    /// Be sure to set `misses` beforehand! Also, if available, set `attributes` beforehand.
-    pub fn accuracy(mut self, mut acc: f64) -> Self {
-        if self.attributes.is_none() {
-            let mut calculator = CatchStars::new(self.map).mods(self.mods);
+    /// Only tiny droplets are generated to match the accuracy, fruits and droplets
+    /// are filled up to the map's maximum combo minus the misses.
+    #[inline]
+    pub fn accuracy(mut self, acc: f64) -> Self {
+        self.acc = Some(acc / 100.0);
 
-            if let Some(passed_objects) = self.passed_objects {
-                calculator = calculator.passed_objects(passed_objects);
-            }
+        self
+    }
 
-            if let Some(clock_rate) = self.clock_rate {
-                calculator = calculator.clock_rate(clock_rate);
+    /// Generate the hitresults that [`calculate`](CatchPP::calculate) would use.
+    ///
+    /// Fruits and droplets that were not specified are filled up to the map's
+    /// maximum combo minus the misses, and tiny droplets up to the map's amount.
+    /// Since hits and misses of fruits and droplets are weighted the same,
+    /// there is no [`HitResultPriority`](crate::HitResultPriority) for osu!catch.
+    /// If no attributes were provided, they're calculated and kept
+    /// so that a subsequent calculation can re-use them.
+    pub fn generate_state(&mut self) -> CatchScoreState {
+        let attributes = match self.attributes {
+            Some(ref attributes) => attributes.clone(),
+            None => {
+                let attributes = self.difficulty();
+                self.attributes = Some(attributes.clone());
+
+                attributes
             }
-
-            self.attributes = Some(calculator.calculate());
-        }
-
-        let attributes = self.attributes.as_ref().unwrap();
-
-        let n_droplets = self.n_droplets.unwrap_or_else(|| {
-            attributes
-                .n_droplets
-                .saturating_sub(self.n_misses.unwrap_or(0))
-        });
+        };
 
         let max_combo = attributes.max_combo();
+        let inner = self.assert_hitresults(attributes);
+
+        CatchScoreState {
+            max_combo: inner.combo.unwrap_or(max_combo),
+            n_fruits: inner.n_fruits,
+            n_droplets: inner.n_droplets,
+            n_tiny_droplets: inner.n_tiny_droplets,
+            n_tiny_droplet_misses: inner.n_tiny_droplet_misses,
+            n_misses: inner.n_misses,
+        }
+    }
 
-        let n_fruits = self.n_fruits.unwrap_or_else(|| {
-            max_combo
-                .saturating_sub(self.n_misses.unwrap_or(0))
-                .saturating_sub(n_droplets)
-        });
-
-        let max_tiny_droplets = attributes.n_tiny_droplets;
-        acc /= 100.0;
-
-        let n_tiny_droplets = self.n_tiny_droplets.unwrap_or_else(|| {
-            ((acc * (max_combo + max_tiny_droplets) as f64).round() as usize)
-                .saturating_sub(n_fruits)
-                .saturating_sub(n_droplets)
-        });
+    fn difficulty(&self) -> CatchDifficultyAttributes {
+        let mut calculator = CatchStars::new(self.map).mods(self.mods);
 
-        let n_tiny_droplet_misses = max_tiny_droplets.saturating_sub(n_tiny_droplets);
+        if let Some(passed_objects) = self.passed_objects {
+            calculator = calculator.passed_objects(passed_objects);
+        }
 
-        self.n_fruits.replace(n_fruits);
-        self.n_droplets.replace(n_droplets);
-        self.n_tiny_droplets.replace(n_tiny_droplets);
-        self.n_tiny_droplet_misses.replace(n_tiny_droplet_misses);
+        if let Some(clock_rate) = self.clock_rate {
+            calculator = calculator.clock_rate(clock_rate);
+        }
 
-        self
+        calculator.calculate()
     }
 
-    fn assert_hitresults(self, attributes: CatchDifficultyAttributes) -> CatchPPInner {
+    fn assert_hitresults(&self, attributes: CatchDifficultyAttributes) -> CatchPPInner {
         let max_combo = attributes.max_combo();
 
-        let correct_combo_hits = self
-            .n_fruits
-            .and_then(|f| self.n_droplets.map(|d| f + d + self.n_misses.unwrap_or(0)))
+        let (n_fruits, n_droplets, n_tiny_droplets, n_tiny_droplet_misses) = match self.acc {
+            Some(acc) => {
+                let n_droplets = self.n_droplets.unwrap_or_else(|| {
+                    attributes
+                        .n_droplets
+                        .saturating_sub(self.n_misses.unwrap_or(0))
+                });
+
+                let n_fruits = self.n_fruits.unwrap_or_else(|| {
+                    max_combo
+                        .saturating_sub(self.n_misses.unwrap_or(0))
+                        .saturating_sub(n_droplets)
+                });
+
+                let max_tiny_droplets = attributes.n_tiny_droplets;
+
+                let n_tiny_droplets = self.n_tiny_droplets.unwrap_or_else(|| {
+                    ((acc * (max_combo + max_tiny_droplets) as f64).round() as usize)
+                        .saturating_sub(n_fruits)
+                        .saturating_sub(n_droplets)
+                });
+
+                let n_tiny_droplet_misses = max_tiny_droplets.saturating_sub(n_tiny_droplets);
+
+                (
+                    Some(n_fruits),
+                    Some(n_droplets),
+                    Some(n_tiny_droplets),
+                    Some(n_tiny_droplet_misses),
+                )
+            }
+            None => (
+                self.n_fruits,
+                self.n_droplets,
+                self.n_tiny_droplets,
+                self.n_tiny_droplet_misses,
+            ),
+        };
+
+        let correct_combo_hits = n_fruits
+            .and_then(|f| n_droplets.map(|d| f + d + self.n_misses.unwrap_or(0)))
             .filter(|h| *h == max_combo);
 
-        let correct_fruits = self.n_fruits.filter(|f| {
+        let correct_fruits = n_fruits.filter(|f| {
             *f >= attributes
                 .n_fruits
                 .saturating_sub(self.n_misses.unwrap_or(0))
         });
 
-        let correct_droplets = self.n_droplets.filter(|d| {
+        let correct_droplets = n_droplets.filter(|d| {
             *d >= attributes
                 .n_droplets
                 .saturating_sub(self.n_misses.unwrap_or(0))
         });
 
-        let correct_tinies = self
-            .n_tiny_droplets
-            .and_then(|t| self.n_tiny_droplet_misses.map(|m| t + m))
+        let correct_tinies = n_tiny_droplets
+            .and_then(|t| n_tiny_droplet_misses.map(|m| t + m))
             .filter(|h| *h == attributes.n_tiny_droplets);
 
         if correct_combo_hits
@@ -266,10 +321,10 @@ impl<'map> CatchPP<'map> {
             .and(correct_tinies)
             .is_none()
         {
-            let mut n_fruits = self.n_fruits.unwrap_or(0);
-            let mut n_droplets = self.n_droplets.unwrap_or(0);
-            let mut n_tiny_droplets = self.n_tiny_droplets.unwrap_or(0);
-            let n_tiny_droplet_misses = self.n_tiny_droplet_misses.unwrap_or(0);
+            let mut n_fruits = n_fruits.unwrap_or(0);
+            let mut n_droplets = n_droplets.unwrap_or(0);
+            let mut n_tiny_droplets = n_tiny_droplets.unwrap_or(0);
+            let n_tiny_droplet_misses = n_tiny_droplet_misses.unwrap_or(0);
 
             let missing = max_combo
                 .saturating_sub(n_fruits)
@@ -302,29 +357,27 @@ impl<'map> CatchPP<'map> {
             attributes,
             mods: self.mods,
             combo: self.combo,
-            n_fruits: self.n_fruits.unwrap_or(0),
-            n_droplets: self.n_droplets.unwrap_or(0),
-            n_tiny_droplets: self.n_tiny_droplets.unwrap_or(0),
-            n_tiny_droplet_misses: self.n_tiny_droplet_misses.unwrap_or(0),
+            n_fruits: n_fruits.unwrap_or(0),
+            n_droplets: n_droplets.unwrap_or(0),
+            n_tiny_droplets: n_tiny_droplets.unwrap_or(0),
+            n_tiny_droplet_misses: n_tiny_droplet_misses.unwrap_or(0),
             n_misses: self.n_misses.unwrap_or(0),
         }
     }
 
-    /// Calculate all performance related values, including pp and stars.
-    pub fn calculate(mut self) -> CatchPerformanceAttributes {
-        let attributes = self.attributes.take().unwrap_or_else(|| {
-            let mut calculator = CatchStars::new(self.map).mods(self.mods);
-
-            if let Some(passed_objects) = self.passed_objects {
-                calculator = calculator.passed_objects(passed_objects);
-            }
+    /// Same as [`CatchPP::calculate`] but returns an error if the attributes
+    /// provided through [`CatchPP::attributes`] were calculated with different mods.
+    pub fn try_calculate(self) -> Result<CatchPerformanceAttributes, ModsMismatch> {
+        if let Some(ref attrs) = self.attributes {
+            ModsMismatch::check(attrs.mods, self.mods, GameMode::Catch)?;
+        }
 
-            if let Some(clock_rate) = self.clock_rate {
-                calculator = calculator.clock_rate(clock_rate);
-            }
+        Ok(self.calculate())
+    }
 
-            calculator.calculate()
-        });
+    /// Calculate all performance related values, including pp and stars.
+    pub fn calculate(mut self) -> CatchPerformanceAttributes {
+        let attributes = self.attributes.take().unwrap_or_else(|| self.difficulty());
 
         self.assert_hitresults(attributes).calculate()
     }
@@ -357,9 +410,12 @@ impl CatchPPInner {
         }
 
         // Longer maps are worth more
-        let len_bonus = 0.95
-            + 0.3 * (combo_hits as f64 / 2500.0).min(1.0)
-            + (combo_hits > 2500) as u8 as f64 * (combo_hits as f64 / 2500.0).log10() * 0.475;
+        let mut len_bonus = 0.95 + 0.3 * (combo_hits as f64 / 2500.0).min(1.0);
+
+        // Checking beforehand so empty maps don't multiply 0 with `log10(0.0)`
+        if combo_hits > 2500 {
+            len_bonus += (combo_hits as f64 / 2500.0).log10() * 0.475;
+        }
 
         pp *= len_bonus;
 
@@ -396,16 +452,24 @@ impl CatchPPInner {
         }
 
         // Accuracy scaling
-        pp *= self.acc().powf(5.5);
+        let accuracy = self.acc();
+        pp *= accuracy.powf(5.5);
 
         // NF penalty
         if self.mods.nf() {
             pp *= 0.9;
         }
 
+        // Catch pp is entirely based on movement so it's split by the strain share
+        let pp_hyper_dash = pp * self.attributes.hyper_dash_share;
+
         CatchPerformanceAttributes {
             difficulty: self.attributes,
             pp,
+            pp_dash: pp - pp_hyper_dash,
+            pp_hyper_dash,
+            accuracy,
+            combo: self.combo.unwrap_or(max_combo),
         }
     }
 
@@ -446,6 +510,7 @@ impl<'map> From<OsuPP<'map>> for CatchPP<'map> {
             mods,
             acc,
             combo,
+            combo_cap: _,
             n300,
             n100,
             n50,
@@ -455,11 +520,12 @@ impl<'map> From<OsuPP<'map>> for CatchPP<'map> {
             ..
         } = osu;
 
-        let res = Self {
+        Self {
             map,
             attributes: None,
             mods,
             combo,
+            acc,
             n_fruits: n300,
             n_droplets: n100,
             n_tiny_droplets: n50,
@@ -467,11 +533,6 @@ impl<'map> From<OsuPP<'map>> for CatchPP<'map> {
             n_misses,
             passed_objects,
             clock_rate,
-        };
-
-        match acc {
-            Some(acc) => res.accuracy(acc),
-            None => res,
         }
     }
 }
@@ -542,17 +603,14 @@ mod test {
         let total_objects = attributes.n_fruits + attributes.n_droplets;
         let target_acc = 97.5;
 
-        let calculator = CatchPP::new(&map)
+        let state = CatchPP::new(&map)
             .attributes(attributes)
             .passed_objects(total_objects)
-            .accuracy(target_acc);
-
-        let numerator = calculator.n_fruits.unwrap_or(0)
-            + calculator.n_droplets.unwrap_or(0)
-            + calculator.n_tiny_droplets.unwrap_or(0);
-        let denominator = numerator
-            + calculator.n_tiny_droplet_misses.unwrap_or(0)
-            + calculator.n_misses.unwrap_or(0);
+            .accuracy(target_acc)
+            .generate_state();
+
+        let numerator = state.n_fruits + state.n_droplets + state.n_tiny_droplets;
+        let denominator = numerator + state.n_tiny_droplet_misses + state.n_misses;
         let acc = 100.0 * numerator as f64 / denominator as f64;
 
         assert!(
@@ -573,27 +631,22 @@ mod test {
         let n_droplets = 550;
         let n_tiny_droplets = 2222;
 
-        let calculator = CatchPP::new(&map)
+        let state = CatchPP::new(&map)
             .attributes(attributes)
             .passed_objects(total_objects)
             .droplets(n_droplets)
             .tiny_droplets(n_tiny_droplets)
-            .accuracy(target_acc);
+            .accuracy(target_acc)
+            .generate_state();
 
         assert_eq!(
-            n_droplets,
-            calculator.n_droplets.unwrap(),
+            n_droplets, state.n_droplets,
             "Expected: {} | Actual: {}",
-            n_droplets,
-            calculator.n_droplets.unwrap()
+            n_droplets, state.n_droplets
         );
 
-        let numerator = calculator.n_fruits.unwrap_or(0)
-            + calculator.n_droplets.unwrap_or(0)
-            + calculator.n_tiny_droplets.unwrap_or(0);
-        let denominator = numerator
-            + calculator.n_tiny_droplet_misses.unwrap_or(0)
-            + calculator.n_misses.unwrap_or(0);
+        let numerator = state.n_fruits + state.n_droplets + state.n_tiny_droplets;
+        let denominator = numerator + state.n_tiny_droplet_misses + state.n_misses;
         let acc = 100.0 * numerator as f64 / denominator as f64;
 
         assert!(
@@ -650,4 +703,77 @@ mod test {
             calculator.n_tiny_droplets + calculator.n_tiny_droplet_misses,
         );
     }
+
+    #[test]
+    fn tiny_droplet_misses_cheaper_than_misses() {
+        let map = Beatmap::default();
+        let attributes = attributes();
+
+        let full = CatchPP::new(&map)
+            .attributes(attributes.clone())
+            .assert_hitresults(attributes.clone());
+
+        let tiny_misses = CatchPP::new(&map)
+            .attributes(attributes.clone())
+            .tiny_droplet_misses(5)
+            .assert_hitresults(attributes.clone());
+
+        let misses = CatchPP::new(&map)
+            .attributes(attributes.clone())
+            .misses(5)
+            .assert_hitresults(attributes);
+
+        assert_eq!(tiny_misses.n_tiny_droplet_misses, 5);
+        assert_eq!(tiny_misses.n_misses, 0);
+        assert_eq!(tiny_misses.total_hits(), full.total_hits());
+        assert!(tiny_misses.acc() < full.acc());
+
+        // Every missed object weighs the same for accuracy...
+        assert!((tiny_misses.acc() - misses.acc()).abs() < f64::EPSILON);
+
+        // ... but only fruit and droplet misses are penalized on top of that
+        assert!(tiny_misses.calculate().pp > misses.calculate().pp);
+    }
+
+    #[test]
+    fn dash_and_hyper_dash_pp() {
+        use crate::parse::{HitObject, HitObjectKind, Pos2};
+
+        let jumps = |left: f32, right: f32| Beatmap {
+            mode: crate::GameMode::Catch,
+            version: 14,
+            ar: 9.0,
+            od: 8.0,
+            cs: 4.0,
+            hp: 5.0,
+            slider_mult: 1.4,
+            tick_rate: 1.0,
+            hit_objects: (0..200)
+                .map(|i| HitObject {
+                    pos: Pos2 {
+                        x: if i & 1 == 0 { left } else { right },
+                        y: 192.0,
+                    },
+                    start_time: 1000.0 + i as f64 * 250.0,
+                    kind: HitObjectKind::Circle,
+                })
+                .collect(),
+            sounds: vec![0; 200],
+            ..Default::default()
+        };
+
+        let hyper_map = jumps(0.0, 512.0);
+        let dash_map = jumps(156.0, 356.0);
+
+        let hyper = CatchPP::new(&hyper_map).calculate();
+        let dash = CatchPP::new(&dash_map).calculate();
+
+        assert!(hyper.pp_hyper_dash > dash.pp_hyper_dash);
+        assert!(dash.pp_dash > hyper.pp_dash);
+        assert_eq!(dash.pp_hyper_dash, 0.0);
+
+        for attrs in [hyper, dash] {
+            assert!((attrs.pp_dash + attrs.pp_hyper_dash - attrs.pp).abs() < 1e-9);
+        }
+    }
 }
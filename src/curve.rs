@@ -72,6 +72,24 @@ impl<'bufs> Curve<'bufs> {
         }
     }
 
+    /// Approximate the curve by straight lines between the control points
+    /// instead of sampling the actual path type.
+    pub(crate) fn linear(
+        points: &[PathControlPoint],
+        expected_len: Option<f64>,
+        bufs: &'bufs mut CurveBuffers,
+    ) -> Self {
+        bufs.path.clear();
+        bufs.path.extend(points.iter().map(|p| p.pos));
+        bufs.path.dedup();
+        Self::calculate_length(points, bufs, expected_len);
+
+        Self {
+            path: &bufs.path,
+            lengths: &bufs.lengths,
+        }
+    }
+
     pub(crate) fn position_at(&self, progress: f64) -> Pos2 {
         let d = self.progress_to_dist(progress);
         let i = self.idx_of_dist(d);
@@ -1,3 +1,5 @@
+use std::{error::Error as StdError, fmt};
+
 use crate::{
     catch::{CatchGradualDifficultyAttributes, CatchGradualPerformanceAttributes, CatchScoreState},
     mania::{ManiaGradualDifficultyAttributes, ManiaGradualPerformanceAttributes, ManiaScoreState},
@@ -91,6 +93,7 @@ impl Iterator for GradualDifficultyAttributes<'_> {
 ///
 /// This struct is used for [`GradualPerformanceAttributes`].
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScoreState {
     /// Maximum combo that the score has had so far.
     /// **Not** the maximum possible combo of the map so far.
@@ -118,6 +121,248 @@ impl ScoreState {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Create a score state from the hitresults of an osu!lazer score,
+    /// e.g. the `statistics` of a score from osu!api v2.
+    ///
+    /// The hitresults are mapped according to the given mode.
+    /// Since osu!lazer provides the score's maximum combo separately,
+    /// it must be specified as well.
+    ///
+    /// For osu!standard, the score state has no room for the slider tick and
+    /// slider tail judgements so `large_tick_hit` and `slider_tail_hit` are not
+    /// carried over. They only matter for ScoreV2 and must be specified separately
+    /// through [`OsuPP::large_tick_hits`](crate::OsuPP::large_tick_hits) and
+    /// [`OsuPP::slider_end_hits`](crate::OsuPP::slider_end_hits).
+    pub fn from_lazer_statistics(
+        mode: GameMode,
+        max_combo: usize,
+        statistics: &LazerStatistics,
+    ) -> Self {
+        let mut state = Self {
+            max_combo,
+            n300: statistics.great,
+            n_misses: statistics.miss,
+            ..Default::default()
+        };
+
+        match mode {
+            GameMode::Osu => {
+                state.n100 = statistics.ok;
+                state.n50 = statistics.meh;
+            }
+            GameMode::Taiko => state.n100 = statistics.ok,
+            GameMode::Catch => {
+                state.n100 = statistics.large_tick_hit;
+                state.n50 = statistics.small_tick_hit;
+                state.n_katu = statistics.small_tick_miss;
+                state.n_misses += statistics.large_tick_miss;
+            }
+            GameMode::Mania => {
+                state.n_geki = statistics.perfect;
+                state.n_katu = statistics.good;
+                state.n100 = statistics.ok;
+                state.n50 = statistics.meh;
+            }
+        }
+
+        state
+    }
+
+    /// Check whether the hitresults and the combo are possible on the map
+    /// whose attributes are given.
+    ///
+    /// Hitresults are only checked against upper bounds so states of scores
+    /// that did not reach the end of the map, e.g. failed scores, are valid too.
+    /// For osu!mania, the maximum combo serves as upper bound for the
+    /// hitresults since it's the only object count of the attributes.
+    pub fn validate(&self, attrs: &DifficultyAttributes) -> Result<(), InvalidScoreState> {
+        let max_combo = attrs.max_combo();
+
+        if self.max_combo > max_combo {
+            return Err(InvalidScoreState::Combo {
+                combo: self.max_combo,
+                max: max_combo,
+            });
+        }
+
+        let (hits, max) = match attrs {
+            DifficultyAttributes::Osu(attrs) => (
+                self.n300 + self.n100 + self.n50 + self.n_misses,
+                attrs.n_circles + attrs.n_sliders + attrs.n_spinners,
+            ),
+            DifficultyAttributes::Taiko(attrs) => {
+                (self.n300 + self.n100 + self.n_misses, attrs.max_combo)
+            }
+            DifficultyAttributes::Catch(attrs) => {
+                let tiny_droplets = self.n50 + self.n_katu;
+
+                if tiny_droplets > attrs.n_tiny_droplets {
+                    return Err(InvalidScoreState::TinyDroplets {
+                        count: tiny_droplets,
+                        max: attrs.n_tiny_droplets,
+                    });
+                }
+
+                (self.n300 + self.n100 + self.n_misses, attrs.max_combo())
+            }
+            DifficultyAttributes::Mania(attrs) => (
+                self.n_geki + self.n_katu + self.n300 + self.n100 + self.n50 + self.n_misses,
+                attrs.max_combo,
+            ),
+        };
+
+        if hits > max {
+            return Err(InvalidScoreState::HitResults { count: hits, max });
+        }
+
+        Ok(())
+    }
+}
+
+/// Error returned by [`ScoreState::validate`] if a score state is impossible on a map.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InvalidScoreState {
+    /// The combo exceeds the map's maximum combo.
+    Combo {
+        /// The combo of the score state.
+        combo: usize,
+        /// The map's maximum combo.
+        max: usize,
+    },
+    /// There are more hitresults than hit objects.
+    ///
+    /// For osu!catch, only fruits, droplets, and misses are considered.
+    HitResults {
+        /// The amount of hitresults of the score state.
+        count: usize,
+        /// The maximum amount of hitresults.
+        max: usize,
+    },
+    /// There are more hit and missed tiny droplets than the osu!catch map contains.
+    TinyDroplets {
+        /// The amount of hit and missed tiny droplets of the score state.
+        count: usize,
+        /// The map's amount of tiny droplets.
+        max: usize,
+    },
+}
+
+impl fmt::Display for InvalidScoreState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Combo { combo, max } => {
+                write!(f, "combo {} exceeds the maximum combo {}", combo, max)
+            }
+            Self::HitResults { count, max } => write!(
+                f,
+                "{} hitresults exceed the {} hit objects of the map",
+                count, max
+            ),
+            Self::TinyDroplets { count, max } => write!(
+                f,
+                "{} tiny droplets exceed the {} tiny droplets of the map",
+                count, max
+            ),
+        }
+    }
+}
+
+impl StdError for InvalidScoreState {}
+
+/// Hitresults of an osu!lazer score.
+///
+/// With the `serde` feature, this can be deserialized directly from the
+/// `statistics` of a score from osu!api v2. Missing hitresults default
+/// to `0` and hitresults that are irrelevant for performance calculation,
+/// e.g. bonus results, are ignored.
+///
+/// Use [`ScoreState::from_lazer_statistics`] to convert them into a score state
+/// which in turn converts into the score state of each mode.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LazerStatistics {
+    /// Amount of perfect hits (n320 for osu!mania).
+    pub perfect: usize,
+    /// Amount of great hits (300s, fruits for osu!catch).
+    pub great: usize,
+    /// Amount of good hits (n200 for osu!mania).
+    pub good: usize,
+    /// Amount of ok hits (100s).
+    pub ok: usize,
+    /// Amount of meh hits (50s).
+    pub meh: usize,
+    /// Amount of misses.
+    pub miss: usize,
+    /// Amount of hit large ticks (slider ticks and repeats for osu!standard,
+    /// droplets for osu!catch).
+    pub large_tick_hit: usize,
+    /// Amount of missed large ticks (droplets for osu!catch).
+    pub large_tick_miss: usize,
+    /// Amount of hit small ticks (tiny droplets for osu!catch).
+    pub small_tick_hit: usize,
+    /// Amount of missed small ticks (tiny droplets for osu!catch).
+    pub small_tick_miss: usize,
+    /// Amount of hit slider tails.
+    pub slider_tail_hit: usize,
+}
+
+impl From<OsuScoreState> for ScoreState {
+    #[inline]
+    fn from(state: OsuScoreState) -> Self {
+        Self {
+            max_combo: state.max_combo,
+            n300: state.n300,
+            n100: state.n100,
+            n50: state.n50,
+            n_misses: state.n_misses,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<TaikoScoreState> for ScoreState {
+    #[inline]
+    fn from(state: TaikoScoreState) -> Self {
+        Self {
+            max_combo: state.max_combo,
+            n300: state.n300,
+            n100: state.n100,
+            n_misses: state.n_misses,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<CatchScoreState> for ScoreState {
+    #[inline]
+    fn from(state: CatchScoreState) -> Self {
+        Self {
+            max_combo: state.max_combo,
+            n300: state.n_fruits,
+            n100: state.n_droplets,
+            n50: state.n_tiny_droplets,
+            n_katu: state.n_tiny_droplet_misses,
+            n_misses: state.n_misses,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<ManiaScoreState> for ScoreState {
+    #[inline]
+    fn from(state: ManiaScoreState) -> Self {
+        Self {
+            n_geki: state.n320,
+            n300: state.n300,
+            n_katu: state.n200,
+            n100: state.n100,
+            n50: state.n50,
+            n_misses: state.n_misses,
+            ..Default::default()
+        }
+    }
 }
 
 impl From<ScoreState> for OsuScoreState {
@@ -324,3 +569,133 @@ impl<'map> GradualPerformanceAttributes<'map> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lazer_statistics() {
+        let statistics = LazerStatistics {
+            perfect: 1,
+            great: 2,
+            good: 3,
+            ok: 4,
+            meh: 5,
+            miss: 6,
+            large_tick_hit: 7,
+            large_tick_miss: 8,
+            small_tick_hit: 9,
+            small_tick_miss: 10,
+            slider_tail_hit: 11,
+        };
+
+        let state = |mode| ScoreState::from_lazer_statistics(mode, 100, &statistics);
+
+        let osu = OsuScoreState::from(state(GameMode::Osu));
+        assert_eq!((osu.max_combo, osu.n300, osu.n100, osu.n50), (100, 2, 4, 5));
+        assert_eq!(osu.n_misses, 6);
+
+        let taiko = TaikoScoreState::from(state(GameMode::Taiko));
+        assert_eq!((taiko.n300, taiko.n100, taiko.n_misses), (2, 4, 6));
+
+        let catch = CatchScoreState::from(state(GameMode::Catch));
+        assert_eq!(
+            (catch.n_fruits, catch.n_droplets, catch.n_tiny_droplets),
+            (2, 7, 9)
+        );
+        assert_eq!((catch.n_tiny_droplet_misses, catch.n_misses), (10, 6 + 8));
+
+        let mania = ManiaScoreState::from(state(GameMode::Mania));
+        assert_eq!((mania.n320, mania.n300, mania.n200), (1, 2, 3));
+        assert_eq!((mania.n100, mania.n50, mania.n_misses), (4, 5, 6));
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn validate() {
+        use crate::BeatmapExt;
+
+        let maps = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        for path in maps.iter() {
+            let map = Beatmap::from_path(path).unwrap();
+            let attrs = map.stars().calculate();
+            let max_combo = attrs.max_combo();
+
+            let fc = match &attrs {
+                DifficultyAttributes::Osu(_) => ScoreState {
+                    max_combo,
+                    n300: map.hit_objects.len(),
+                    ..Default::default()
+                },
+                DifficultyAttributes::Taiko(_) => ScoreState {
+                    max_combo,
+                    n300: max_combo,
+                    ..Default::default()
+                },
+                DifficultyAttributes::Catch(attrs) => ScoreState {
+                    max_combo,
+                    n300: attrs.n_fruits,
+                    n100: attrs.n_droplets,
+                    n50: attrs.n_tiny_droplets,
+                    ..Default::default()
+                },
+                DifficultyAttributes::Mania(_) => ScoreState {
+                    n300: map.hit_objects.len(),
+                    ..Default::default()
+                },
+            };
+
+            assert_eq!(fc.validate(&attrs), Ok(()), "{}", path);
+
+            let combo = ScoreState {
+                max_combo: max_combo + 1,
+                ..fc.clone()
+            };
+
+            let err = InvalidScoreState::Combo {
+                combo: max_combo + 1,
+                max: max_combo,
+            };
+
+            assert_eq!(combo.validate(&attrs), Err(err), "{}", path);
+
+            let misses = ScoreState {
+                n_misses: max_combo + 1,
+                ..fc
+            };
+
+            assert!(
+                matches!(
+                    misses.validate(&attrs),
+                    Err(InvalidScoreState::HitResults { .. })
+                ),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_lazer_statistics() {
+        let json = r#"{"great":512,"ok":10,"miss":2,"slider_tail_hit":80,"large_bonus":4}"#;
+        let statistics: LazerStatistics = serde_json::from_str(json).unwrap();
+
+        let expected = LazerStatistics {
+            great: 512,
+            ok: 10,
+            miss: 2,
+            slider_tail_hit: 80,
+            ..Default::default()
+        };
+
+        assert_eq!(statistics, expected);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{AnyPP, AttributeProvider, Beatmap, GameMode, HitResultPriority, ScoreState};
+
+/// Generator for the hitresults of a score on maps of any mode.
+///
+/// Hitresults that are not specified are generated from the accuracy and
+/// distributed according to the [`HitResultPriority`]. The performance calculators
+/// generate hitresults the same way so the result is exactly what a performance
+/// calculation with the same parameters would be based on.
+///
+/// # Example
+///
+/// ```
+/// use rosu_pp::{AnyPP, Beatmap, HitResultGenerator, HitResultPriority};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+///
+/// // The hitresults of an FC with 98% accuracy
+/// let state = HitResultGenerator::new(&map)
+///     .mods(8 + 64)
+///     .accuracy(98.0)
+///     .priority(HitResultPriority::Balanced)
+///     .generate();
+///
+/// let pp_result = AnyPP::new(&map).mods(8 + 64).state(state).calculate();
+///
+/// println!("PP: {}", pp_result.pp());
+/// ```
+#[derive(Clone, Debug)]
+pub struct HitResultGenerator<'map> {
+    inner: AnyPP<'map>,
+}
+
+impl<'map> HitResultGenerator<'map> {
+    /// Create a new hitresult generator for the map in its own mode.
+    #[inline]
+    pub fn new(map: &'map Beatmap) -> Self {
+        Self {
+            inner: AnyPP::new(map),
+        }
+    }
+
+    /// Generate the hitresults of the given parameters.
+    ///
+    /// Unless attributes were provided, the difficulty
+    /// attributes of the map are calculated first.
+    #[inline]
+    pub fn generate(mut self) -> ScoreState {
+        self.inner.generate_state()
+    }
+
+    /// Provide the result of a previous difficulty or performance calculation
+    /// so that the attributes don't have to be recalculated.
+    #[inline]
+    pub fn attributes(self, attributes: impl AttributeProvider) -> Self {
+        Self {
+            inner: self.inner.attributes(attributes),
+        }
+    }
+
+    /// If the map is an osu!standard map, convert it to another mode.
+    #[inline]
+    pub fn mode(self, mode: GameMode) -> Self {
+        Self {
+            inner: self.inner.mode(mode),
+        }
+    }
+
+    /// Specify mods through their bit values.
+    #[inline]
+    pub fn mods(self, mods: u32) -> Self {
+        Self {
+            inner: self.inner.mods(mods),
+        }
+    }
+
+    /// Amount of passed objects for partial plays, e.g. a fail.
+    #[inline]
+    pub fn passed_objects(self, passed_objects: usize) -> Self {
+        Self {
+            inner: self.inner.passed_objects(passed_objects),
+        }
+    }
+
+    /// Specify how the generated hitresults should be distributed.
+    ///
+    /// Defaults to [`HitResultPriority::BestCase`].
+    /// Irrelevant for osu!catch since fruits and droplets weigh the same.
+    #[inline]
+    pub fn priority(self, priority: HitResultPriority) -> Self {
+        Self {
+            inner: self.inner.hitresult_priority(priority),
+        }
+    }
+
+    /// Set the accuracy between `0.0` and `100.0`.
+    #[inline]
+    pub fn accuracy(self, acc: f64) -> Self {
+        Self {
+            inner: self.inner.accuracy(acc),
+        }
+    }
+
+    /// Specify the max combo of the score.
+    ///
+    /// If not specified, a full combo is assumed.
+    #[inline]
+    pub fn combo(self, combo: usize) -> Self {
+        Self {
+            inner: self.inner.combo(combo),
+        }
+    }
+
+    /// Specify the amount of misses.
+    #[inline]
+    pub fn n_misses(self, n_misses: usize) -> Self {
+        Self {
+            inner: self.inner.n_misses(n_misses),
+        }
+    }
+
+    /// Specify the amount of 300s.
+    #[inline]
+    pub fn n300(self, n300: usize) -> Self {
+        Self {
+            inner: self.inner.n300(n300),
+        }
+    }
+
+    /// Specify the amount of 100s.
+    #[inline]
+    pub fn n100(self, n100: usize) -> Self {
+        Self {
+            inner: self.inner.n100(n100),
+        }
+    }
+
+    /// Specify the amount of 50s.
+    #[inline]
+    pub fn n50(self, n50: usize) -> Self {
+        Self {
+            inner: self.inner.n50(n50),
+        }
+    }
+
+    /// Specify the amount of katus, i.e. tiny droplet misses for osu!catch
+    /// and n200 for osu!mania.
+    #[inline]
+    pub fn n_katu(self, n_katu: usize) -> Self {
+        Self {
+            inner: self.inner.n_katu(n_katu),
+        }
+    }
+
+    /// Specify the amount of gekis, i.e. n320 for osu!mania.
+    #[inline]
+    pub fn n_geki(self, n_geki: usize) -> Self {
+        Self {
+            inner: self.inner.n_geki(n_geki),
+        }
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn total_hits(state: &ScoreState) -> usize {
+        state.n_geki + state.n_katu + state.n300 + state.n100 + state.n50 + state.n_misses
+    }
+
+    fn accuracy(state: &ScoreState, mode: GameMode) -> f64 {
+        let (numerator, denominator) = match mode {
+            GameMode::Osu => (
+                6 * state.n300 + 2 * state.n100 + state.n50,
+                6 * (state.n300 + state.n100 + state.n50 + state.n_misses),
+            ),
+            GameMode::Taiko => (
+                2 * state.n300 + state.n100,
+                2 * (state.n300 + state.n100 + state.n_misses),
+            ),
+            GameMode::Mania => (
+                6 * (state.n_geki + state.n300) + 4 * state.n_katu + 2 * state.n100 + state.n50,
+                6 * (state.n_geki
+                    + state.n300
+                    + state.n_katu
+                    + state.n100
+                    + state.n50
+                    + state.n_misses),
+            ),
+            GameMode::Catch => unreachable!(),
+        };
+
+        numerator as f64 / denominator as f64
+    }
+
+    #[test]
+    fn matches_performance_calculators() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        for &mode in [GameMode::Osu, GameMode::Taiko, GameMode::Catch].iter() {
+            for &priority in [HitResultPriority::BestCase, HitResultPriority::WorstCase].iter() {
+                let state = HitResultGenerator::new(&map)
+                    .mode(mode)
+                    .accuracy(95.0)
+                    .n_misses(3)
+                    .priority(priority)
+                    .generate();
+
+                let expected = AnyPP::new(&map)
+                    .mode(mode)
+                    .accuracy(95.0)
+                    .n_misses(3)
+                    .hitresult_priority(priority)
+                    .generate_state();
+
+                assert_eq!(state, expected, "{:?} {:?}", mode, priority);
+            }
+        }
+    }
+
+    #[test]
+    fn balanced_priority() {
+        let cases = [
+            ("./maps/2785319.osu", GameMode::Osu),
+            ("./maps/2785319.osu", GameMode::Taiko),
+            ("./maps/1974394.osu", GameMode::Mania),
+        ];
+
+        for &(path, mode) in cases.iter() {
+            let map = Beatmap::from_path(path).unwrap();
+
+            let generate = |priority: HitResultPriority| {
+                HitResultGenerator::new(&map)
+                    .mode(mode)
+                    .accuracy(95.0)
+                    .n_misses(2)
+                    .n100(30)
+                    .priority(priority)
+                    .generate()
+            };
+
+            let best = generate(HitResultPriority::BestCase);
+            let worst = generate(HitResultPriority::WorstCase);
+            let balanced = generate(HitResultPriority::Balanced);
+
+            assert_eq!(total_hits(&balanced), total_hits(&best), "{:?}", mode);
+            assert_eq!(balanced.n100, 30, "{:?}", mode);
+            assert_eq!(balanced.n_misses, 2, "{:?}", mode);
+
+            let (low, high) = if best.n300 < worst.n300 {
+                (best.n300, worst.n300)
+            } else {
+                (worst.n300, best.n300)
+            };
+
+            assert!(low <= balanced.n300 && balanced.n300 <= high, "{:?}", mode);
+
+            let best_acc = accuracy(&best, mode);
+            let worst_acc = accuracy(&worst, mode);
+            let balanced_acc = accuracy(&balanced, mode);
+
+            assert!(
+                (balanced_acc - (best_acc + worst_acc) / 2.0).abs() < 1e-3,
+                "{:?}",
+                mode
+            );
+        }
+    }
+}
@@ -166,6 +166,11 @@
 //! | `default` | Beatmap parsing will be non-async |
 //! | `async_tokio` | Beatmap parsing will be async through [tokio](https://github.com/tokio-rs/tokio) |
 //! | `async_std` | Beatmap parsing will be async through [async-std](https://github.com/async-rs/async-std) |
+//! | `cache` | Enables `CachedCalculator` to memoize difficulty attributes |
+//! | `parallel` | Enables `AnyStars::calculate_many` to calculate many maps on multiple threads |
+//! | `replay` | Enables `Replay` to calculate the performance of `.osr` replay files |
+//! | `serde` | Implements `Serialize` and `Deserialize` for strain types through [serde](https://github.com/serde-rs/serde) |
+//! | `zip` | Enables `Beatmap::from_osz_path` to parse maps from `.osz` archives; only for non-async parsing |
 //!
 
 #![cfg_attr(docsrs, feature(doc_cfg), deny(broken_intra_doc_links))]
@@ -201,13 +206,36 @@ pub mod beatmap;
 pub use beatmap::{Beatmap, GameMode};
 
 mod gradual;
-pub use gradual::{GradualDifficultyAttributes, GradualPerformanceAttributes, ScoreState};
+pub use gradual::{
+    GradualDifficultyAttributes, GradualPerformanceAttributes, InvalidScoreState, LazerStatistics,
+    ScoreState,
+};
+
+mod bytes;
+pub use bytes::{AttributesDecodeError, ATTRIBUTES_VERSION};
 
 mod pp;
-pub use pp::{AnyPP, AttributeProvider, HitResultPriority};
+pub use pp::{AnyPP, AttributeProvider, HitResultPriority, ModsMismatch, PpGradient};
+
+mod hitresults;
+pub use hitresults::HitResultGenerator;
+
+mod recalc;
+pub use recalc::Recalculator;
 
 mod stars;
-pub use stars::AnyStars;
+pub use stars::{AnyStars, NonFiniteStat};
+
+#[cfg(feature = "cache")]
+mod cache;
+#[cfg(feature = "cache")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+pub use cache::CachedCalculator;
+
+#[cfg(feature = "replay")]
+mod replay;
+#[cfg(feature = "replay")]
+pub use replay::{Replay, ReplayError};
 
 mod curve;
 mod mods;
@@ -218,7 +246,7 @@ pub use mania::{ManiaPP, ManiaStars};
 pub use osu::{OsuPP, OsuStars};
 pub use taiko::{TaikoPP, TaikoStars};
 
-pub use mods::Mods;
+pub use mods::{ModCategory, Mods, UnknownAcronym};
 pub use parse::{ParseError, ParseResult};
 pub use util::SortedVec;
 
@@ -311,7 +339,11 @@ impl BeatmapExt for Beatmap {
 
 /// The result of calculating the strains on a map.
 /// Suitable to plot the difficulty of a map over time.
+///
+/// With the `serde` feature, the mode is serialized as `"mode"` field alongside the strains.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "mode", rename_all = "snake_case"))]
 pub enum Strains {
     /// osu!standard strain values.
     Osu(osu::OsuStrains),
@@ -348,8 +380,54 @@ impl Strains {
     }
 }
 
+/// Strains alongside the song time of their sections, see [`AnyStars::strains_with_time`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedStrains {
+    /// Song time in ms at which the first section starts.
+    pub start_time: f64,
+    /// Song time in ms covered by each section, i.e. the section length scaled by the clock rate.
+    pub section_len: f64,
+    /// The strain peaks.
+    pub strains: Strains,
+}
+
+impl TimedStrains {
+    /// Song time in ms at which the section of the given index starts.
+    #[inline]
+    pub fn time(&self, idx: usize) -> f64 {
+        self.start_time + idx as f64 * self.section_len
+    }
+
+    /// Pair each of the given strain peaks with the song time of its section.
+    ///
+    /// The peaks should be one of the skills of [`strains`](TimedStrains::strains).
+    #[inline]
+    pub fn points<'s>(&'s self, peaks: &'s [f64]) -> impl Iterator<Item = StrainPoint> + 's {
+        peaks
+            .iter()
+            .enumerate()
+            .map(move |(i, &value)| StrainPoint {
+                time: self.time(i),
+                value,
+            })
+    }
+}
+
+/// A strain peak alongside the song time in ms at which its section starts.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StrainPoint {
+    /// Song time in ms at which the section starts.
+    pub time: f64,
+    /// The strain peak of the section.
+    pub value: f64,
+}
+
 /// The result of a difficulty calculation based on the mode.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "mode", rename_all = "snake_case"))]
 pub enum DifficultyAttributes {
     /// osu!standard difficulty calculation result.
     Osu(osu::OsuDifficultyAttributes),
@@ -383,6 +461,36 @@ impl DifficultyAttributes {
             Self::Mania(attrs) => attrs.max_combo,
         }
     }
+
+    /// Calculate the performance of an SS on the map i.e. a full combo
+    /// with only perfect hitresults.
+    ///
+    /// The attributes must have been calculated for the given map and mods.
+    #[inline]
+    pub fn max_pp(self, map: &Beatmap, mods: u32) -> PerformanceAttributes {
+        match self {
+            Self::Osu(attrs) => OsuPP::new(map)
+                .mods(mods)
+                .attributes(attrs)
+                .calculate()
+                .into(),
+            Self::Taiko(attrs) => TaikoPP::new(map)
+                .mods(mods)
+                .attributes(attrs)
+                .calculate()
+                .into(),
+            Self::Catch(attrs) => CatchPP::new(map)
+                .mods(mods)
+                .attributes(attrs)
+                .calculate()
+                .into(),
+            Self::Mania(attrs) => ManiaPP::new(map)
+                .mods(mods)
+                .attributes(attrs)
+                .calculate()
+                .into(),
+        }
+    }
 }
 
 impl From<osu::OsuDifficultyAttributes> for DifficultyAttributes {
@@ -415,6 +523,8 @@ impl From<mania::ManiaDifficultyAttributes> for DifficultyAttributes {
 
 /// The result of a performance calculation based on the mode.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "mode", rename_all = "snake_case"))]
 pub enum PerformanceAttributes {
     /// osu!standard performance calculation result.
     Osu(osu::OsuPerformanceAttributes),
@@ -470,6 +580,87 @@ impl PerformanceAttributes {
             Self::Mania(attrs) => attrs.difficulty.max_combo,
         }
     }
+
+    /// The accuracy between `0.0` and `1.0` of the hitresults that were used.
+    #[inline]
+    pub fn accuracy(&self) -> f64 {
+        match self {
+            Self::Osu(attrs) => attrs.accuracy,
+            Self::Taiko(attrs) => attrs.accuracy,
+            Self::Catch(attrs) => attrs.accuracy,
+            Self::Mania(attrs) => attrs.accuracy,
+        }
+    }
+
+    /// The combo of the score that was used.
+    ///
+    /// Returns `None` for osu!mania since its calculation does not consider combo.
+    #[inline]
+    pub fn combo(&self) -> Option<usize> {
+        match self {
+            Self::Osu(attrs) => Some(attrs.combo),
+            Self::Taiko(attrs) => Some(attrs.combo),
+            Self::Catch(attrs) => Some(attrs.combo),
+            Self::Mania(_) => None,
+        }
+    }
+
+    /// The portions of the final pp per skill.
+    #[inline]
+    pub fn breakdown(&self) -> PerformanceBreakdown {
+        match self {
+            Self::Osu(attrs) => attrs.breakdown(),
+            Self::Taiko(attrs) => attrs.breakdown(),
+            Self::Catch(attrs) => attrs.breakdown(),
+            Self::Mania(attrs) => attrs.breakdown(),
+        }
+    }
+}
+
+/// A skill that contributes to the final pp of a score.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PerformanceComponent {
+    /// Cursor movement in osu!standard.
+    Aim,
+    /// Tapping speed in osu!standard.
+    Speed,
+    /// Timing accuracy in osu!standard and osu!taiko.
+    Accuracy,
+    /// Memorization with FL in osu!standard.
+    Flashlight,
+    /// The overall strain in osu!taiko and osu!mania.
+    Difficulty,
+    /// Movement without hyperdashes in osu!catch.
+    Dash,
+    /// Hyperdashes in osu!catch.
+    HyperDash,
+}
+
+/// The pp of a score split into the portions of each skill,
+/// see [`PerformanceAttributes::breakdown`].
+///
+/// Except for osu!catch, the portions are combined non-linearly
+/// and scaled by multipliers so they don't sum up to the final pp.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerformanceBreakdown {
+    /// The final performance points.
+    pub pp: f64,
+    /// The portions of the final pp in the order of the mode's calculation.
+    pub components: Vec<(PerformanceComponent, f64)>,
+}
+
+impl PerformanceBreakdown {
+    /// The portion of the given component or `None` if the mode does not consider it.
+    #[inline]
+    pub fn get(&self, component: PerformanceComponent) -> Option<f64> {
+        self.components
+            .iter()
+            .find(|(c, _)| *c == component)
+            .map(|(_, pp)| *pp)
+    }
 }
 
 impl From<PerformanceAttributes> for DifficultyAttributes {
@@ -512,5 +703,146 @@ impl From<mania::ManiaPerformanceAttributes> for PerformanceAttributes {
     }
 }
 
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_strains() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+        let strains = map.strains(0);
+
+        let value = serde_json::to_value(&strains).unwrap();
+        assert_eq!(value["mode"], "catch");
+        assert_eq!(value["section_len"], strains.section_len());
+        assert_eq!(value["movement"].as_array().unwrap().len(), strains.len());
+
+        let deserialized: Strains = serde_json::from_value(value).unwrap();
+        assert!(matches!(deserialized, Strains::Catch(_)));
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let value = serde_json::to_value(map.strains(0)).unwrap();
+        assert_eq!(value["mode"], "osu");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_attributes() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        let attrs = map.pp().mods(8).accuracy(97.0).n_misses(2).calculate();
+
+        let value = serde_json::to_value(&attrs).unwrap();
+        assert_eq!(value["mode"], "taiko");
+        assert_eq!(value["pp"], attrs.pp());
+
+        let deserialized: PerformanceAttributes = serde_json::from_value(value).unwrap();
+        assert!(matches!(deserialized, PerformanceAttributes::Taiko(_)));
+        assert_eq!(deserialized.pp(), attrs.pp());
+
+        let difficulty = DifficultyAttributes::from(deserialized);
+        let value = serde_json::to_value(&difficulty).unwrap();
+        assert_eq!(value["mode"], "taiko");
+
+        let deserialized: DifficultyAttributes = serde_json::from_value(value).unwrap();
+        assert_eq!(deserialized.stars(), attrs.stars());
+
+        let state = ScoreState {
+            max_combo: 100,
+            n300: 90,
+            n_misses: 1,
+            ..Default::default()
+        };
+
+        let value = serde_json::to_value(&state).unwrap();
+        assert_eq!(serde_json::from_value::<ScoreState>(value).unwrap(), state);
+    }
+
+    #[test]
+    fn max_pp_is_upper_bound() {
+        let maps = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        for path in maps {
+            let map = Beatmap::from_path(path).unwrap();
+            let mods = 8 + 16; // HDHR
+            let attrs = map.stars().mods(mods).calculate();
+            let max_pp = attrs.clone().max_pp(&map, mods).pp();
+
+            let score_pp = map
+                .pp()
+                .mods(mods)
+                .attributes(attrs)
+                .accuracy(97.0)
+                .n_misses(1)
+                .calculate()
+                .pp();
+
+            assert!(max_pp > 0.0, "{}", path);
+            assert!(max_pp >= score_pp, "{}: {} < {}", path, max_pp, score_pp);
+            assert!((max_pp - map.max_pp(mods).pp()).abs() < 1e-9, "{}", path);
+        }
+    }
+
+    #[test]
+    fn breakdown() {
+        let maps = [
+            ("./maps/2785319.osu", PerformanceComponent::Aim),
+            ("./maps/1028484.osu", PerformanceComponent::Difficulty),
+            ("./maps/2118524.osu", PerformanceComponent::Dash),
+            ("./maps/1974394.osu", PerformanceComponent::Difficulty),
+        ];
+
+        for (path, component) in maps {
+            let map = Beatmap::from_path(path).unwrap();
+            let attrs = map.pp().accuracy(98.0).calculate();
+            let breakdown = attrs.breakdown();
+
+            assert_eq!(breakdown.pp, attrs.pp(), "{}", path);
+            assert!(breakdown.get(component).unwrap() > 0.0, "{}", path);
+
+            let is_catch = matches!(attrs, PerformanceAttributes::Catch(_));
+            let hyper_dash = breakdown.get(PerformanceComponent::HyperDash);
+            assert_eq!(hyper_dash.is_some(), is_catch, "{}", path);
+
+            for (_, pp) in breakdown.components.iter() {
+                assert!(*pp >= 0.0 && *pp <= breakdown.pp, "{}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_maps() {
+        let modes = [
+            GameMode::Osu,
+            GameMode::Taiko,
+            GameMode::Catch,
+            GameMode::Mania,
+        ];
+
+        for mode in modes {
+            let map = Beatmap {
+                mode,
+                ..Default::default()
+            };
+
+            let attrs = map.stars().mods(8 + 64).calculate();
+
+            assert_eq!(attrs.stars(), 0.0, "{:?}", mode);
+            assert_eq!(attrs.max_combo(), 0, "{:?}", mode);
+
+            let perf = map.pp().attributes(attrs).calculate();
+
+            assert!(perf.pp().is_finite(), "{:?}", mode);
+            assert!(perf.stars().is_finite(), "{:?}", mode);
+        }
+    }
+}
+
 #[cfg(all(feature = "async_tokio", feature = "async_std"))]
 compile_error!("Only one of the features `async_tokio` and `async_std` should be enabled");
@@ -53,6 +53,7 @@ pub struct ManiaGradualDifficultyAttributes<'map> {
     diff_objects: Vec<ManiaDifficultyObject>,
     curr_combo: usize,
     clock_rate: f64,
+    mods: u32,
 }
 
 impl<'map> ManiaGradualDifficultyAttributes<'map> {
@@ -84,6 +85,7 @@ impl<'map> ManiaGradualDifficultyAttributes<'map> {
                     diff_objects: Vec::new(),
                     curr_combo: 0,
                     clock_rate,
+                    mods,
                 }
             }
         };
@@ -109,6 +111,7 @@ impl<'map> ManiaGradualDifficultyAttributes<'map> {
             diff_objects,
             curr_combo,
             clock_rate,
+            mods,
         }
     }
 
@@ -148,6 +151,7 @@ impl Iterator for ManiaGradualDifficultyAttributes<'_> {
             stars: self.strain.clone().difficulty_value() * STAR_SCALING_FACTOR,
             hit_window: self.hit_window,
             max_combo: self.curr_combo,
+            mods: self.mods,
         })
     }
 
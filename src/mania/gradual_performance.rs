@@ -7,6 +7,7 @@ use super::{ManiaGradualDifficultyAttributes, ManiaPerformanceAttributes};
 ///
 /// This struct is used for [`ManiaGradualPerformanceAttributes`].
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ManiaScoreState {
     /// Amount of current 320s.
     pub n320: usize,
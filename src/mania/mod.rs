@@ -7,7 +7,10 @@ mod skills;
 
 use std::borrow::Cow;
 
-use crate::{beatmap::BeatmapHitWindows, util::FloatExt, Beatmap, GameMode, Mods, OsuStars};
+use crate::{
+    beatmap::BeatmapHitWindows, util::FloatExt, Beatmap, GameMode, Mods, OsuStars,
+    PerformanceBreakdown, PerformanceComponent,
+};
 
 pub use self::{gradual_difficulty::*, gradual_performance::*, pp::*};
 
@@ -16,10 +19,12 @@ pub(crate) use self::mania_object::ManiaObject;
 use self::{
     difficulty_object::ManiaDifficultyObject,
     mania_object::ObjectParameters,
-    skills::{Skill, Strain},
+    skills::{Skill, Strain, StrainDecaySkill},
 };
 
 const SECTION_LEN: f64 = 400.0;
+// Lower bound for custom section lengths so that sections always advance.
+const MIN_SECTION_LEN: f64 = 1.0;
 const STAR_SCALING_FACTOR: f64 = 0.018;
 
 /// Difficulty calculator on osu!mania maps.
@@ -42,10 +47,10 @@ const STAR_SCALING_FACTOR: f64 = 0.018;
 /// ```
 #[derive(Clone, Debug)]
 pub struct ManiaStars<'map> {
-    map: Cow<'map, Beatmap>,
-    mods: u32,
+    pub(crate) map: Cow<'map, Beatmap>,
+    pub(crate) mods: u32,
     passed_objects: Option<usize>,
-    clock_rate: Option<f64>,
+    pub(crate) clock_rate: Option<f64>,
     is_convert: bool,
 }
 
@@ -120,12 +125,14 @@ impl<'map> ManiaStars<'map> {
             .clock_rate(clock_rate)
             .hit_windows();
 
-        let ManiaResult { strain, max_combo } = calculate_result(self);
+        let mods = self.mods;
+        let ManiaResult { strain, max_combo } = calculate_result(self, SECTION_LEN);
 
         ManiaDifficultyAttributes {
             stars: strain.difficulty_value() * STAR_SCALING_FACTOR,
             hit_window,
             max_combo,
+            mods,
         }
     }
 
@@ -134,23 +141,114 @@ impl<'map> ManiaStars<'map> {
     /// Suitable to plot the difficulty of a map over time.
     #[inline]
     pub fn strains(self) -> ManiaStrains {
-        let ManiaResult { strain, .. } = calculate_result(self);
+        self.strains_with_section_len(SECTION_LEN)
+    }
+
+    /// Same as [`strains`](ManiaStars::strains) but each strain peak covers
+    /// `section_len` ms instead of the default 400ms.
+    ///
+    /// The section length is clamped to be at least 1ms.
+    pub fn strains_with_section_len(self, section_len: f64) -> ManiaStrains {
+        let section_len = section_len.max(MIN_SECTION_LEN);
+        let ManiaResult { strain, .. } = calculate_result(self, section_len);
 
         ManiaStrains {
-            section_len: SECTION_LEN,
+            section_len,
             strains: strain.strain_peaks,
+            jack: strain.jack_peaks,
+            chord: strain.chord_peaks,
         }
     }
+
+    /// Calculate the strain for every hit object.
+    ///
+    /// Contrary to [`strains`](ManiaStars::strains), which only keeps the peak of each
+    /// section, this is suitable to find the hardest objects of a map or to draw
+    /// a heatmap with per-object precision.
+    pub fn object_strains(self) -> ManiaObjectStrains {
+        let DifficultyObjects {
+            diff_objects,
+            total_columns,
+            ..
+        } = difficulty_objects(&self);
+
+        let take = self
+            .passed_objects
+            .unwrap_or(self.map.hit_objects.len())
+            .min(self.map.hit_objects.len());
+
+        let times: Vec<_> = self.map.hit_objects[..take]
+            .iter()
+            .map(|h| h.start_time)
+            .collect();
+
+        let mut strain = Strain::new(total_columns);
+
+        // The first object doesn't generate a strain
+        let init = || {
+            let mut strains = Vec::with_capacity(take);
+            strains.extend(times.first().map(|_| 0.0));
+
+            strains
+        };
+
+        let mut strains = init();
+        let mut jack = init();
+        let mut chord = init();
+
+        for curr in diff_objects.iter() {
+            strain.process(curr, &diff_objects);
+
+            strains.push(strain.curr_strain());
+            jack.push(strain.individual_strain());
+            chord.push(strain.overall_strain());
+        }
+
+        ManiaObjectStrains {
+            times,
+            strains,
+            jack,
+            chord,
+        }
+    }
+
+    /// End time of the first strain section, adjusted by the clock rate.
+    pub(crate) fn first_section_end(&self, section_len: f64) -> Option<f64> {
+        let take = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+
+        // Sections start with the second object since the first one has no strain
+        self.map
+            .hit_objects
+            .iter()
+            .take(take)
+            .nth(1)
+            .map(|h| (h.start_time / clock_rate / section_len).ceil() * section_len)
+    }
 }
 
-/// The result of calculating the strains on a osu!taiko map.
+/// The result of calculating the strains on a osu!mania map.
 /// Suitable to plot the difficulty of a map over time.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ManiaStrains {
     /// Time in ms inbetween two strains.
     pub section_len: f64,
     /// Strain peaks of the strain skill.
+    ///
+    /// These are the section values from which the star rating is built i.e.
+    /// [`ManiaDifficultyAttributes::stars`] is their decaying weighted sum,
+    /// including the final unfinished section, scaled by `0.018`.
     pub strains: Vec<f64>,
+    /// Peaks of the column-individual part of the strain.
+    ///
+    /// It only builds up through repeated notes in the same column, i.e. jacks.
+    pub jack: Vec<f64>,
+    /// Peaks of the overall part of the strain.
+    ///
+    /// It builds up through every note regardless of its column so
+    /// simultaneous notes, i.e. chords, increase it the most.
+    pub chord: Vec<f64>,
 }
 
 impl ManiaStrains {
@@ -162,7 +260,52 @@ impl ManiaStrains {
     }
 }
 
-fn calculate_result(params: ManiaStars<'_>) -> ManiaResult {
+/// The strain for every hit object of an osu!mania map,
+/// see [`ManiaStars::object_strains`].
+///
+/// All vectors contain one value per hit object.
+/// The first object has no strain so its values are always `0.0`.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManiaObjectStrains {
+    /// Start time in ms of each hit object as specified in the map,
+    /// i.e. the clock rate is not applied.
+    pub times: Vec<f64>,
+    /// Strains of the strain skill.
+    pub strains: Vec<f64>,
+    /// The column-individual part of the strains.
+    pub jack: Vec<f64>,
+    /// The overall part of the strains.
+    pub chord: Vec<f64>,
+}
+
+impl ManiaObjectStrains {
+    /// Returns the number of hit objects.
+    #[inline]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+}
+
+fn calculate_result(params: ManiaStars<'_>, section_len: f64) -> ManiaResult {
+    let DifficultyObjects {
+        diff_objects,
+        total_columns,
+        max_combo,
+    } = difficulty_objects(&params);
+
+    let mut strain = Strain::new(total_columns);
+    strain.section_len = section_len;
+
+    for curr in diff_objects.iter() {
+        strain.process(curr, &diff_objects);
+    }
+
+    ManiaResult { strain, max_combo }
+}
+
+fn difficulty_objects(params: &ManiaStars<'_>) -> DifficultyObjects {
     let ManiaStars {
         map,
         mods,
@@ -175,15 +318,15 @@ fn calculate_result(params: ManiaStars<'_>) -> ManiaResult {
     let total_columns = map.cs.round_even().max(1.0);
 
     let clock_rate = clock_rate.unwrap_or_else(|| mods.clock_rate());
-    let mut strain = Strain::new(total_columns as usize);
     let mut params = ObjectParameters::new(map.as_ref());
     let mut hit_objects = map.hit_objects.iter().take(take);
 
     let first = match hit_objects.next() {
         Some(h) => ManiaObject::new(h, total_columns, &mut params),
         None => {
-            return ManiaResult {
-                strain,
+            return DifficultyObjects {
+                diff_objects: Vec::new(),
+                total_columns: total_columns as usize,
                 max_combo: 0,
             }
         }
@@ -200,16 +343,19 @@ fn calculate_result(params: ManiaStars<'_>) -> ManiaResult {
     let mut diff_objects = Vec::with_capacity(map.hit_objects.len().min(take).saturating_sub(1));
     diff_objects.extend(diff_objects_iter);
 
-    for curr in diff_objects.iter() {
-        strain.process(curr, &diff_objects);
-    }
-
-    ManiaResult {
-        strain,
+    DifficultyObjects {
+        diff_objects,
+        total_columns: total_columns as usize,
         max_combo: params.max_combo,
     }
 }
 
+struct DifficultyObjects {
+    diff_objects: Vec<ManiaDifficultyObject>,
+    total_columns: usize,
+    max_combo: usize,
+}
+
 struct ManiaResult {
     strain: Strain,
     max_combo: usize,
@@ -217,6 +363,7 @@ struct ManiaResult {
 
 /// The result of a difficulty calculation on an osu!mania map.
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ManiaDifficultyAttributes {
     /// The final star rating.
     pub stars: f64,
@@ -224,6 +371,8 @@ pub struct ManiaDifficultyAttributes {
     pub hit_window: f64,
     /// The maximum achievable combo.
     pub max_combo: usize,
+    /// The mods that the attributes were calculated with.
+    pub mods: u32,
 }
 
 impl ManiaDifficultyAttributes {
@@ -236,6 +385,7 @@ impl ManiaDifficultyAttributes {
 
 /// The result of a performance calculation on an osu!mania map.
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ManiaPerformanceAttributes {
     /// The difficulty attributes that were used for the performance calculation.
     pub difficulty: ManiaDifficultyAttributes,
@@ -243,6 +393,8 @@ pub struct ManiaPerformanceAttributes {
     pub pp: f64,
     /// The difficulty portion of the final pp.
     pub pp_difficulty: f64,
+    /// The accuracy between `0.0` and `1.0` of the hitresults that were used.
+    pub accuracy: f64,
 }
 
 impl ManiaPerformanceAttributes {
@@ -258,6 +410,15 @@ impl ManiaPerformanceAttributes {
         self.pp
     }
 
+    /// Return the portions of the final pp per skill.
+    #[inline]
+    pub fn breakdown(&self) -> PerformanceBreakdown {
+        PerformanceBreakdown {
+            pp: self.pp,
+            components: vec![(PerformanceComponent::Difficulty, self.pp_difficulty)],
+        }
+    }
+
     /// Return the maximum combo of the map.
     #[inline]
     pub fn max_combo(&self) -> usize {
@@ -280,6 +441,7 @@ impl<'map> From<OsuStars<'map>> for ManiaStars<'map> {
             mods,
             passed_objects,
             clock_rate,
+            ..
         } = osu;
 
         Self {
@@ -291,3 +453,122 @@ impl<'map> From<OsuStars<'map>> for ManiaStars<'map> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::{HitObject, HitObjectKind, Pos2};
+
+    fn mania_map(notes: impl Iterator<Item = (f64, usize)>) -> Beatmap {
+        let hit_objects = notes
+            .map(|(start_time, column)| HitObject {
+                pos: Pos2 {
+                    x: 64.0 + column as f32 * 128.0,
+                    y: 192.0,
+                },
+                start_time,
+                kind: HitObjectKind::Circle,
+            })
+            .collect();
+
+        Beatmap {
+            mode: GameMode::Mania,
+            version: 14,
+            cs: 4.0,
+            od: 8.0,
+            hp: 8.0,
+            hit_objects,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn jack_and_chord_strains() {
+        // 160 notes in the first column, one every 100ms
+        let jacks = mania_map((0..160).map(|i| (1000.0 + i as f64 * 100.0, 0)));
+
+        // 40 chords across all four columns, one every 400ms
+        let chords = mania_map((0..160).map(|i| (1000.0 + (i / 4) as f64 * 400.0, i % 4)));
+
+        let jack_strains = ManiaStars::new(&jacks).strains();
+        let chord_strains = ManiaStars::new(&chords).strains();
+
+        assert_eq!(jack_strains.jack.len(), jack_strains.len());
+        assert_eq!(jack_strains.chord.len(), jack_strains.len());
+
+        let max = |peaks: &[f64]| peaks.iter().copied().fold(0.0, f64::max);
+
+        assert!(max(&jack_strains.jack) > max(&chord_strains.jack));
+        assert!(max(&chord_strains.chord) > max(&jack_strains.chord));
+    }
+
+    #[test]
+    fn strains_relate_to_stars() {
+        fn weighted_sum(strains: &ManiaStrains) -> f64 {
+            let mut peaks: Vec<_> = strains
+                .strains
+                .iter()
+                .copied()
+                .filter(|&peak| peak > 0.0)
+                .collect();
+            peaks.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap());
+
+            peaks
+                .iter()
+                .zip(std::iter::successors(Some(1.0), |weight| {
+                    Some(weight * 0.9)
+                }))
+                .map(|(peak, weight)| peak * weight)
+                .sum()
+        }
+
+        let max = |peaks: &[f64]| peaks.iter().copied().fold(0.0, f64::max);
+
+        let easy = mania_map((0..160).map(|i| (1000.0 + i as f64 * 250.0, i % 4)));
+        let hard = mania_map((0..640).map(|i| (1000.0 + i as f64 * 60.0, (i * 3) % 4)));
+
+        let easy_strains = ManiaStars::new(&easy).strains();
+        let hard_strains = ManiaStars::new(&hard).strains();
+        let easy_attrs = ManiaStars::new(&easy).calculate();
+        let hard_attrs = ManiaStars::new(&hard).calculate();
+
+        assert!(hard_attrs.stars > easy_attrs.stars);
+        assert!(max(&hard_strains.strains) > max(&easy_strains.strains));
+
+        for (strains, attrs) in [(easy_strains, easy_attrs), (hard_strains, hard_attrs)] {
+            // The final section is not part of the strains so the stars are slightly higher
+            let stars = weighted_sum(&strains) * STAR_SCALING_FACTOR;
+            assert!(stars <= attrs.stars);
+            assert!(stars > attrs.stars * 0.95, "{} vs {}", stars, attrs.stars);
+        }
+    }
+
+    #[test]
+    fn object_strains() {
+        let map = mania_map((0..640).map(|i| (1000.0 + i as f64 * 60.0, (i * 3) % 4)));
+
+        let objects = ManiaStars::new(&map).object_strains();
+        let peaks = ManiaStars::new(&map).strains();
+
+        assert_eq!(objects.len(), map.hit_objects.len());
+        assert_eq!(objects.jack.len(), objects.len());
+        assert_eq!(objects.chord.len(), objects.len());
+        assert_eq!(objects.strains[0], 0.0);
+        assert_eq!(objects.times[1], map.hit_objects[1].start_time);
+
+        // The strain of an object consists of its jack and chord part
+        for i in 1..objects.len() {
+            let sum = objects.jack[i] + objects.chord[i];
+            assert!((objects.strains[i] - sum).abs() < 1e-10);
+        }
+
+        // Section peaks are the highest object strain or a decayed strain of a previous object
+        let max = |strains: &[f64]| strains.iter().copied().fold(0.0, f64::max);
+
+        assert!((max(&objects.strains) - max(&peaks.strains)).abs() < 1e-10);
+
+        let passed = ManiaStars::new(&map).passed_objects(10).object_strains();
+        assert_eq!(passed.len(), 10);
+        assert_eq!(passed.strains[..], objects.strains[..10]);
+    }
+}
@@ -2,7 +2,8 @@ use std::borrow::Cow;
 
 use super::{ManiaDifficultyAttributes, ManiaPerformanceAttributes, ManiaScoreState, ManiaStars};
 use crate::{
-    Beatmap, DifficultyAttributes, GameMode, HitResultPriority, Mods, OsuPP, PerformanceAttributes,
+    pp::balance_hitresults, Beatmap, DifficultyAttributes, GameMode, HitResultPriority, Mods,
+    ModsMismatch, OsuPP, PerformanceAttributes,
 };
 
 /// Performance calculator on osu!mania maps.
@@ -191,6 +192,9 @@ impl<'map> ManiaPP<'map> {
     }
 
     /// Provide parameters through an [`ManiaScoreState`].
+    ///
+    /// Values can be overridden by calling the individual setters afterwards,
+    /// e.g. `.state(state).n_misses(0)`.
     #[inline]
     pub fn state(mut self, state: ManiaScoreState) -> Self {
         let ManiaScoreState {
@@ -212,6 +216,16 @@ impl<'map> ManiaPP<'map> {
         self
     }
 
+    /// Same as [`ManiaPP::calculate`] but returns an error if the attributes
+    /// provided through [`ManiaPP::attributes`] were calculated with different mods.
+    pub fn try_calculate(self) -> Result<ManiaPerformanceAttributes, ModsMismatch> {
+        if let Some(ref attrs) = self.attributes {
+            ModsMismatch::check(attrs.mods, self.mods, GameMode::Mania)?;
+        }
+
+        Ok(self.calculate())
+    }
+
     /// Calculate all performance related values, including pp and stars.
     pub fn calculate(self) -> ManiaPerformanceAttributes {
         let attrs = self.attributes.unwrap_or_else(|| {
@@ -239,9 +253,42 @@ impl<'map> ManiaPP<'map> {
         inner.calculate()
     }
 
+    /// Generate the hitresults that [`calculate`](ManiaPP::calculate) would use.
+    ///
+    /// Hitresults that were not specified are generated to match the accuracy,
+    /// if given, and distributed according to the [`HitResultPriority`].
+    #[inline]
+    pub fn generate_state(&self) -> ManiaScoreState {
+        self.generate_hitresults()
+    }
+
     fn generate_hitresults(&self) -> ManiaScoreState {
+        match self.hitresult_priority.unwrap_or_default() {
+            HitResultPriority::BestCase => self.generate_hitresults_with(true),
+            HitResultPriority::WorstCase => self.generate_hitresults_with(false),
+            HitResultPriority::Balanced => {
+                let best = self.generate_hitresults_with(true);
+                let worst = self.generate_hitresults_with(false);
+
+                let [n320, n300, n200, n100, n50] = balance_hitresults(
+                    [best.n320, best.n300, best.n200, best.n100, best.n50],
+                    [worst.n320, worst.n300, worst.n200, worst.n100, worst.n50],
+                );
+
+                ManiaScoreState {
+                    n320,
+                    n300,
+                    n200,
+                    n100,
+                    n50,
+                    ..best
+                }
+            }
+        }
+    }
+
+    fn generate_hitresults_with(&self, best_case: bool) -> ManiaScoreState {
         let n_objects = self.passed_objects.unwrap_or(self.map.hit_objects.len());
-        let priority = self.hitresult_priority.unwrap_or_default();
 
         let mut n320 = self.n320.unwrap_or(0);
         let mut n300 = self.n300.unwrap_or(0);
@@ -258,9 +305,10 @@ impl<'map> ManiaPP<'map> {
                     let remaining =
                         n_objects.saturating_sub(n320 + n300 + n200 + n100 + n50 + n_misses);
 
-                    match priority {
-                        HitResultPriority::BestCase => n320 += remaining,
-                        HitResultPriority::WorstCase => n50 += remaining,
+                    if best_case {
+                        n320 += remaining;
+                    } else {
+                        n50 += remaining;
                     }
                 }
                 (Some(_), None, Some(_), Some(_), Some(_)) => {
@@ -303,9 +351,10 @@ impl<'map> ManiaPP<'map> {
                 (Some(_), _, None, None, Some(_)) | (_, Some(_), None, None, Some(_)) => {
                     let remaining = n_objects.saturating_sub(n320 + n300 + n50 + n_misses);
 
-                    match priority {
-                        HitResultPriority::BestCase => n100 = remaining,
-                        HitResultPriority::WorstCase => n200 = remaining,
+                    if best_case {
+                        n100 = remaining;
+                    } else {
+                        n200 = remaining;
                     }
                 }
                 (Some(_), _, None, Some(_), Some(_)) | (_, Some(_), None, Some(_), Some(_)) => {
@@ -317,18 +366,20 @@ impl<'map> ManiaPP<'map> {
                 (None, None, Some(_), Some(_), Some(_)) => {
                     let remaining = n_objects.saturating_sub(n200 + n100 + n50 + n_misses);
 
-                    match priority {
-                        HitResultPriority::BestCase => n320 = remaining,
-                        HitResultPriority::WorstCase => n300 = remaining,
+                    if best_case {
+                        n320 = remaining;
+                    } else {
+                        n300 = remaining;
                     }
                 }
                 (None, None, None, Some(_), Some(_)) => {
                     let delta =
                         (target_total - n_objects.saturating_sub(n_misses)).saturating_sub(n100);
 
-                    match priority {
-                        HitResultPriority::BestCase => n320 = delta / 5,
-                        HitResultPriority::WorstCase => n300 = delta / 5,
+                    if best_case {
+                        n320 = delta / 5;
+                    } else {
+                        n300 = delta / 5;
                     }
 
                     n200 = n_objects.saturating_sub(n320 + n100 + n50 + n_misses);
@@ -339,17 +390,19 @@ impl<'map> ManiaPP<'map> {
                         let n = n200.min((target_total - curr_total) / 2);
                         n200 -= n;
 
-                        match priority {
-                            HitResultPriority::BestCase => n320 += n,
-                            HitResultPriority::WorstCase => n300 += n,
+                        if best_case {
+                            n320 += n;
+                        } else {
+                            n300 += n;
                         }
                     } else {
                         let n = (n320 + n300).min((curr_total - target_total) / 2);
                         n200 += n;
 
-                        match priority {
-                            HitResultPriority::BestCase => n320 -= n,
-                            HitResultPriority::WorstCase => n300 -= n,
+                        if best_case {
+                            n320 -= n;
+                        } else {
+                            n300 -= n;
                         }
                     }
                 }
@@ -357,9 +410,10 @@ impl<'map> ManiaPP<'map> {
                     let delta = (target_total - n_objects.saturating_sub(n_misses))
                         .saturating_sub(n200 * 3);
 
-                    match priority {
-                        HitResultPriority::BestCase => n320 = delta / 5,
-                        HitResultPriority::WorstCase => n300 = delta / 5,
+                    if best_case {
+                        n320 = delta / 5;
+                    } else {
+                        n300 = delta / 5;
                     }
 
                     n100 = delta % 5;
@@ -377,7 +431,7 @@ impl<'map> ManiaPP<'map> {
                         n50 += n;
                     }
 
-                    if let HitResultPriority::BestCase = priority {
+                    if best_case {
                         // Shift n50 to n100
                         let n = n320.min(n50 / 4);
 
@@ -390,9 +444,10 @@ impl<'map> ManiaPP<'map> {
                     let delta = (target_total - n_objects.saturating_sub(n_misses))
                         .saturating_sub(n200 * 3 + n100);
 
-                    match priority {
-                        HitResultPriority::BestCase => n320 = delta / 5,
-                        HitResultPriority::WorstCase => n300 = delta / 5,
+                    if best_case {
+                        n320 = delta / 5;
+                    } else {
+                        n300 = delta / 5;
                     }
 
                     n50 = n_objects.saturating_sub(n320 + n300 + n200 + n100 + n_misses);
@@ -401,9 +456,10 @@ impl<'map> ManiaPP<'map> {
                     let delta =
                         target_total - n_objects.saturating_sub(n_misses).saturating_sub(n200 * 3);
 
-                    match priority {
-                        HitResultPriority::BestCase => n320 = delta / 5,
-                        HitResultPriority::WorstCase => n300 = delta / 5,
+                    if best_case {
+                        n320 = delta / 5;
+                    } else {
+                        n300 = delta / 5;
                     }
 
                     n100 = delta % 5;
@@ -415,32 +471,35 @@ impl<'map> ManiaPP<'map> {
                         let n = n100.min((target_total - curr_total) / 4);
                         n100 -= n;
 
-                        match priority {
-                            HitResultPriority::BestCase => n320 += n,
-                            HitResultPriority::WorstCase => n300 += n,
+                        if best_case {
+                            n320 += n;
+                        } else {
+                            n300 += n;
                         }
                     } else {
                         let n = (n320 + n300).min((curr_total - target_total) / 4);
                         n100 += n;
 
-                        match priority {
-                            HitResultPriority::BestCase => n320 -= n,
-                            HitResultPriority::WorstCase => n300 -= n,
+                        if best_case {
+                            n320 -= n;
+                        } else {
+                            n300 -= n;
                         }
                     }
                 }
                 (None, None, None, None, None) => {
                     let delta = target_total - n_objects.saturating_sub(n_misses);
 
-                    match priority {
-                        HitResultPriority::BestCase => n320 = delta / 5,
-                        HitResultPriority::WorstCase => n300 = delta / 5,
+                    if best_case {
+                        n320 = delta / 5;
+                    } else {
+                        n300 = delta / 5;
                     }
 
                     n100 = delta % 5;
                     n50 = n_objects.saturating_sub(n320 + n300 + n100 + n_misses);
 
-                    if let HitResultPriority::BestCase = priority {
+                    if best_case {
                         // Shift n50 to n100
                         let n = n320.min(n50 / 4);
                         n320 -= n;
@@ -452,36 +511,33 @@ impl<'map> ManiaPP<'map> {
         } else {
             let remaining = n_objects.saturating_sub(n320 + n300 + n200 + n100 + n50 + n_misses);
 
-            match priority {
-                HitResultPriority::BestCase => {
-                    if self.n320.is_none() {
-                        n320 = remaining;
-                    } else if self.n300.is_none() {
-                        n300 = remaining;
-                    } else if self.n200.is_none() {
-                        n200 = remaining;
-                    } else if self.n100.is_none() {
-                        n100 = remaining;
-                    } else if self.n50.is_none() {
-                        n50 = remaining;
-                    } else {
-                        n320 += remaining;
-                    }
+            if best_case {
+                if self.n320.is_none() {
+                    n320 = remaining;
+                } else if self.n300.is_none() {
+                    n300 = remaining;
+                } else if self.n200.is_none() {
+                    n200 = remaining;
+                } else if self.n100.is_none() {
+                    n100 = remaining;
+                } else if self.n50.is_none() {
+                    n50 = remaining;
+                } else {
+                    n320 += remaining;
                 }
-                HitResultPriority::WorstCase => {
-                    if self.n50.is_none() {
-                        n50 = remaining;
-                    } else if self.n100.is_none() {
-                        n100 = remaining;
-                    } else if self.n200.is_none() {
-                        n200 = remaining;
-                    } else if self.n300.is_none() {
-                        n300 = remaining;
-                    } else if self.n320.is_none() {
-                        n320 = remaining;
-                    } else {
-                        n50 += remaining;
-                    }
+            } else {
+                if self.n50.is_none() {
+                    n50 = remaining;
+                } else if self.n100.is_none() {
+                    n100 = remaining;
+                } else if self.n200.is_none() {
+                    n200 = remaining;
+                } else if self.n300.is_none() {
+                    n300 = remaining;
+                } else if self.n320.is_none() {
+                    n320 = remaining;
+                } else {
+                    n50 += remaining;
                 }
             }
         }
@@ -524,6 +580,7 @@ impl ManiaPpInner {
             difficulty: self.attrs,
             pp,
             pp_difficulty: difficulty_value,
+            accuracy: self.state.accuracy(),
         }
     }
 
@@ -572,6 +629,7 @@ impl<'map> From<OsuPP<'map>> for ManiaPP<'map> {
             mods,
             acc,
             combo: _,
+            combo_cap: _,
             n300,
             n100,
             n50,
@@ -579,6 +637,14 @@ impl<'map> From<OsuPP<'map>> for ManiaPP<'map> {
             passed_objects,
             clock_rate,
             hitresult_priority,
+            relax: _,
+            autopilot: _,
+            score_v2: _,
+            slider_head_accuracy: _,
+            large_tick_hits: _,
+            slider_end_hits: _,
+            hidden_ar_scaling: _,
+            relax_variant: _,
         } = osu;
 
         Self {
@@ -655,6 +721,7 @@ mod tests {
             stars: 4.824631127426499,
             hit_window: 40.0,
             max_combo: 5064,
+            mods: 0,
         };
 
         (map, attrs)
@@ -1060,4 +1127,35 @@ mod tests {
         assert_eq!(state, expected);
         assert_eq!(state.total_hits(), 3238);
     }
+
+    #[test]
+    fn hr_and_ez_only_affect_hit_windows() {
+        let (map, _) = test_data();
+
+        let calculate = |mods: u32| {
+            ManiaPP::new(&map)
+                .mods(mods)
+                .n320(3000)
+                .n300(150)
+                .n200(50)
+                .n100(20)
+                .n50(10)
+                .n_misses(8)
+                .calculate()
+        };
+
+        let nomod = calculate(0);
+        let hr = calculate(u32::HR);
+        let ez = calculate(u32::EZ);
+
+        // OD8 with a 40ms window is scaled by 1/1.4 for HR and by 1.4 for EZ
+        assert_eq!(nomod.difficulty.hit_window, 40.0);
+        assert_eq!(hr.difficulty.hit_window, 28.0);
+        assert_eq!(ez.difficulty.hit_window, 56.0);
+
+        // The pp only depend on the judgements, not on the windows they were hit in
+        assert_eq!(hr.pp, nomod.pp);
+        assert_eq!(hr.accuracy, nomod.accuracy);
+        assert!(ez.pp < nomod.pp);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::mania::difficulty_object::ManiaDifficultyObject;
+use crate::mania::{difficulty_object::ManiaDifficultyObject, SECTION_LEN};
 
 use super::{previous, Skill, StrainDecaySkill, StrainSkill};
 
@@ -14,8 +14,13 @@ pub(crate) struct Strain {
     curr_strain: f64,
     curr_section_peak: f64,
     curr_section_end: f64,
+    curr_section_jack: f64,
+    curr_section_chord: f64,
+    pub(crate) section_len: f64,
 
     pub(crate) strain_peaks: Vec<f64>,
+    pub(crate) jack_peaks: Vec<f64>,
+    pub(crate) chord_peaks: Vec<f64>,
 }
 
 impl Strain {
@@ -33,10 +38,49 @@ impl Strain {
             curr_strain: 0.0,
             curr_section_peak: 0.0,
             curr_section_end: 0.0,
+            curr_section_jack: 0.0,
+            curr_section_chord: 0.0,
+            section_len: SECTION_LEN,
             strain_peaks: Vec::new(),
+            jack_peaks: Vec::new(),
+            chord_peaks: Vec::new(),
         }
     }
 
+    /// The column-individual strain of the most recently processed object.
+    pub(crate) fn individual_strain(&self) -> f64 {
+        self.individual_strain
+    }
+
+    /// The overall strain of the most recently processed object.
+    pub(crate) fn overall_strain(&self) -> f64 {
+        self.overall_strain
+    }
+
+    /// The individual and overall strain decayed until the given time.
+    fn decayed_strains(
+        &self,
+        time: f64,
+        curr: &ManiaDifficultyObject,
+        diff_objects: &[ManiaDifficultyObject],
+    ) -> (f64, f64) {
+        let prev_start = previous(diff_objects, curr.idx, 0).map_or(0.0, |h| h.start_time);
+
+        let individual_decay = Self::apply_decay(
+            self.individual_strain,
+            time - prev_start,
+            Self::INDIVIDUAL_DECAY_BASE,
+        );
+
+        let overall_decay = Self::apply_decay(
+            self.overall_strain,
+            time - prev_start,
+            Self::OVERALL_DECAY_BASE,
+        );
+
+        (individual_decay, overall_decay)
+    }
+
     fn apply_decay(value: f64, delta_time: f64, decay_base: f64) -> f64 {
         value * decay_base.powf(delta_time / 1000.0)
     }
@@ -67,6 +111,11 @@ impl StrainSkill for Strain {
         &mut self.curr_section_end
     }
 
+    #[inline]
+    fn section_len(&self) -> f64 {
+        self.section_len
+    }
+
     #[inline]
     fn curr_section_peak(&self) -> f64 {
         self.curr_section_peak
@@ -87,6 +136,25 @@ impl StrainSkill for Strain {
         <Self as StrainDecaySkill>::strain_value_at(self, curr)
     }
 
+    fn save_curr_peak(&mut self) {
+        self.strain_peaks.push(self.curr_section_peak);
+        self.jack_peaks.push(self.curr_section_jack);
+        self.chord_peaks.push(self.curr_section_chord);
+    }
+
+    fn start_new_section_from(
+        &mut self,
+        time: f64,
+        curr: &ManiaDifficultyObject,
+        diff_objects: &[ManiaDifficultyObject],
+    ) {
+        let (individual_decay, overall_decay) = self.decayed_strains(time, curr, diff_objects);
+
+        self.curr_section_peak = individual_decay + overall_decay;
+        self.curr_section_jack = individual_decay;
+        self.curr_section_chord = overall_decay;
+    }
+
     #[inline]
     fn calculate_initial_strain(
         &self,
@@ -177,6 +245,9 @@ impl StrainDecaySkill for Strain {
         );
         self.overall_strain += (1.0 + hold_addition) * hold_factor;
 
+        self.curr_section_jack = self.curr_section_jack.max(self.individual_strain);
+        self.curr_section_chord = self.curr_section_chord.max(self.overall_strain);
+
         // * Update startTimes and endTimes arrays
         self.start_times[col] = start_time;
         self.end_times[col] = end_time;
@@ -191,19 +262,7 @@ impl StrainDecaySkill for Strain {
         curr: &ManiaDifficultyObject,
         diff_objects: &[ManiaDifficultyObject],
     ) -> f64 {
-        let prev_start = previous(diff_objects, curr.idx, 0).map_or(0.0, |h| h.start_time);
-
-        let individual_decay = Self::apply_decay(
-            self.individual_strain,
-            offset - prev_start,
-            Self::INDIVIDUAL_DECAY_BASE,
-        );
-
-        let overall_decay = Self::apply_decay(
-            self.overall_strain,
-            offset - prev_start,
-            Self::OVERALL_DECAY_BASE,
-        );
+        let (individual_decay, overall_decay) = self.decayed_strains(offset, curr, diff_objects);
 
         individual_decay + overall_decay
     }
@@ -1,6 +1,6 @@
 use std::{cmp::Ordering, mem};
 
-use crate::mania::{difficulty_object::ManiaDifficultyObject, SECTION_LEN};
+use crate::mania::difficulty_object::ManiaDifficultyObject;
 
 pub(crate) trait Skill {
     fn process(&mut self, curr: &ManiaDifficultyObject, diff_objects: &[ManiaDifficultyObject]);
@@ -13,6 +13,8 @@ pub(crate) trait StrainSkill: Sized + Skill {
     fn curr_section_end(&self) -> f64;
     fn curr_section_end_mut(&mut self) -> &mut f64;
 
+    fn section_len(&self) -> f64;
+
     fn curr_section_peak(&self) -> f64;
     fn curr_section_peak_mut(&mut self) -> &mut f64;
 
@@ -23,13 +25,14 @@ pub(crate) trait StrainSkill: Sized + Skill {
     fn process(&mut self, curr: &ManiaDifficultyObject, diff_objects: &[ManiaDifficultyObject]) {
         // * The first object doesn't generate a strain, so we begin with an incremented section end
         if curr.idx == 0 {
-            *self.curr_section_end_mut() = (curr.start_time / SECTION_LEN).ceil() * SECTION_LEN;
+            let section_len = self.section_len();
+            *self.curr_section_end_mut() = (curr.start_time / section_len).ceil() * section_len;
         }
 
         while curr.start_time > self.curr_section_end() {
             self.save_curr_peak();
             self.start_new_section_from(self.curr_section_end(), curr, diff_objects);
-            *self.curr_section_end_mut() += SECTION_LEN;
+            *self.curr_section_end_mut() += self.section_len();
         }
 
         *self.curr_section_peak_mut() = self.strain_value_at(curr).max(self.curr_section_peak());
@@ -1,3 +1,7 @@
+use std::{error::Error as StdError, fmt};
+
+use crate::GameMode;
+
 macro_rules! impl_mods {
     ($func_name:ident, $const_name:ident) => {
         #[inline]
@@ -7,6 +11,128 @@ macro_rules! impl_mods {
     };
 }
 
+/// Acronyms of all legacy mods alongside their bit values.
+const ACRONYMS: [(&str, u32); 31] = [
+    ("NF", 1 << 0),
+    ("EZ", 1 << 1),
+    ("TD", 1 << 2),
+    ("HD", 1 << 3),
+    ("HR", 1 << 4),
+    ("SD", 1 << 5),
+    ("DT", 1 << 6),
+    ("RX", 1 << 7),
+    ("HT", 1 << 8),
+    ("NC", 1 << 9),
+    ("FL", 1 << 10),
+    ("AT", 1 << 11),
+    ("SO", 1 << 12),
+    ("AP", 1 << 13),
+    ("PF", 1 << 14),
+    ("4K", 1 << 15),
+    ("5K", 1 << 16),
+    ("6K", 1 << 17),
+    ("7K", 1 << 18),
+    ("8K", 1 << 19),
+    ("FI", 1 << 20),
+    ("RD", 1 << 21),
+    ("CN", 1 << 22),
+    ("TP", 1 << 23),
+    ("9K", 1 << 24),
+    ("CO", 1 << 25),
+    ("1K", 1 << 26),
+    ("3K", 1 << 27),
+    ("2K", 1 << 28),
+    ("V2", 1 << 29),
+    ("MR", 1 << 30),
+];
+
+/// Bit value of the mod with the given acronym, ignoring case.
+pub(crate) fn acronym_bits(acronym: &str) -> Option<u32> {
+    ACRONYMS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(acronym))
+        .map(|(_, bits)| *bits)
+}
+
+/// Error returned by [`Mods::from_acronyms`] if an acronym does not belong to any mod.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnknownAcronym(pub String);
+
+impl fmt::Display for UnknownAcronym {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown mod acronym `{}`", self.0)
+    }
+}
+
+impl StdError for UnknownAcronym {}
+
+/// The category of a mod, following the grouping of osu!lazer's mod select.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ModCategory {
+    /// Mods that make the game easier, e.g. `EZ`, `NF`, or `HT`.
+    DifficultyReduction,
+    /// Mods that make the game harder, e.g. `HR`, `DT`, or `HD`.
+    DifficultyIncrease,
+    /// Mods that take over part of the gameplay, e.g. `RX`, `AP`, or `SO`.
+    Automation,
+    /// Mods that change the map's content, e.g. `4K`, `MR`, or `TP`.
+    Conversion,
+    /// Mods that don't affect gameplay itself, i.e. `TD` and `V2`.
+    System,
+}
+
+impl ModCategory {
+    fn of_acronym(acronym: &str) -> Self {
+        match acronym {
+            "NF" | "EZ" | "HT" => Self::DifficultyReduction,
+            "HR" | "SD" | "DT" | "NC" | "HD" | "FL" | "PF" | "FI" => Self::DifficultyIncrease,
+            "RX" | "AT" | "SO" | "AP" | "CN" => Self::Automation,
+            "TD" | "V2" => Self::System,
+            _ => Self::Conversion,
+        }
+    }
+}
+
+/// Mods that only apply to osu!standard: TD, SO, AP, and TP.
+const OSU_ONLY: u32 = (1 << 2) | (1 << 12) | (1 << 13) | (1 << 23);
+
+/// Mods that only apply to osu!mania: key mods, FI, RD, CO, and MR.
+const MANIA_ONLY: u32 =
+    (0b1_1111 << 15) | (1 << 20) | (1 << 21) | (1 << 24) | (1 << 25) | (0b111 << 26) | (1 << 30);
+
+/// Mods that are ranked on osu!stable: NF, EZ, TD, HD, HR, SD, DT, HT, NC, FL, SO, PF,
+/// the 4K to 9K key mods, FI, CO, and MR.
+///
+/// RX, AT, AP, RD, CN, TP, the 1K to 3K key mods, ScoreV2, and unknown bits are unranked.
+const RANKED: u32 = 0b111_0111_1111
+    | (1 << 12)
+    | (1 << 14)
+    | (0b1_1111 << 15)
+    | (1 << 20)
+    | (1 << 24)
+    | (1 << 25)
+    | (1 << 30);
+
+/// Reduce mods to the bits that affect difficulty attributes in the given mode.
+///
+/// NC is treated as DT since both result in the same clock rate. In osu!standard
+/// HD only affects the flashlight rating so it's only considered alongside FL.
+pub(crate) fn difficulty_mods(mods: u32, mode: GameMode) -> u32 {
+    let relevant = match mode {
+        GameMode::Osu if mods.fl() => {
+            u32::EZ | u32::TD | u32::HD | u32::HR | u32::DT | u32::RX | u32::HT | u32::FL | u32::AP
+        }
+        GameMode::Osu => u32::EZ | u32::TD | u32::HR | u32::DT | u32::RX | u32::HT | u32::AP,
+        GameMode::Taiko | GameMode::Catch | GameMode::Mania => {
+            u32::EZ | u32::HR | u32::DT | u32::HT
+        }
+    };
+
+    let mods = if mods.nc() { mods | u32::DT } else { mods };
+
+    mods & relevant
+}
+
 /// Abstract type to define mods.
 #[allow(missing_docs)]
 pub trait Mods: Copy {
@@ -18,10 +144,25 @@ pub trait Mods: Copy {
     const DT: u32 = 1 << 6;
     const RX: u32 = 1 << 7;
     const HT: u32 = 1 << 8;
+    const NC: u32 = 1 << 9;
     const FL: u32 = 1 << 10;
     const SO: u32 = 1 << 12;
     const AP: u32 = 1 << 13;
+    const V2: u32 = 1 << 29;
 
+    /// Combine the mods of the given acronyms, e.g. `["HD", "HR"]`.
+    ///
+    /// Acronyms are case-insensitive. The first unknown acronym is returned as error.
+    fn from_acronyms(acronyms: &[&str]) -> Result<Self, UnknownAcronym>;
+    /// The acronyms of all contained mods, ordered by their bit value.
+    ///
+    /// Unknown bits are skipped.
+    fn acronyms(self) -> Vec<&'static str>;
+    /// The acronyms of all contained mods alongside their category,
+    /// ordered by their bit value.
+    ///
+    /// Unknown bits are skipped.
+    fn categories(self) -> Vec<(&'static str, ModCategory)>;
     /// If the clock rate is affected by the mods.
     fn change_speed(self) -> bool;
     /// If object time's or positions are affected by the mods.
@@ -30,6 +171,24 @@ pub trait Mods: Copy {
     fn clock_rate(self) -> f64;
     /// Multiplier for beatmap attributes with respect to the mods.
     fn od_ar_hp_multiplier(self) -> f64;
+    /// If all mods of the given acronyms are contained, e.g. `["HD", "HR"]`.
+    ///
+    /// Acronyms are case-insensitive. If any acronym is unknown, `false` is returned.
+    fn contains_all(self, acronyms: &[&str]) -> bool;
+    /// If any mod of the given acronyms is contained, e.g. `["DT", "NC"]`.
+    ///
+    /// Acronyms are case-insensitive. Unknown acronyms are never considered contained.
+    fn contains_any(self, acronyms: &[&str]) -> bool;
+    /// If none of the mods are exclusive to a different mode than the given one,
+    /// e.g. `7K` is not valid for [`GameMode::Osu`] and `AP` is not valid for [`GameMode::Mania`].
+    fn is_valid_for(self, mode: GameMode) -> bool;
+    /// If any of the mods is unranked, e.g. `RX`, `AT`, or `TP`.
+    fn has_unranked_mods(self) -> bool;
+    /// If all mods are also contained in `other`, e.g. `HD` is a subset of `HDHR`.
+    ///
+    /// `NC` is considered to contain `DT` and `PF` to contain `SD`
+    /// so `DT` is a subset of `NC` but not the other way around.
+    fn is_subset_of(self, other: u32) -> bool;
     fn nf(self) -> bool;
     fn ez(self) -> bool;
     fn td(self) -> bool;
@@ -38,25 +197,54 @@ pub trait Mods: Copy {
     fn dt(self) -> bool;
     fn rx(self) -> bool;
     fn ht(self) -> bool;
+    fn nc(self) -> bool;
     fn fl(self) -> bool;
     fn so(self) -> bool;
     fn ap(self) -> bool;
+    fn v2(self) -> bool;
 }
 
 impl Mods for u32 {
+    #[inline]
+    fn from_acronyms(acronyms: &[&str]) -> Result<Self, UnknownAcronym> {
+        acronyms.iter().try_fold(0, |mods, acronym| {
+            acronym_bits(acronym)
+                .map(|bits| mods | bits)
+                .ok_or_else(|| UnknownAcronym((*acronym).to_owned()))
+        })
+    }
+
+    #[inline]
+    fn acronyms(self) -> Vec<&'static str> {
+        ACRONYMS
+            .iter()
+            .filter(|(_, bits)| self & bits > 0)
+            .map(|(name, _)| *name)
+            .collect()
+    }
+
+    #[inline]
+    fn categories(self) -> Vec<(&'static str, ModCategory)> {
+        self.acronyms()
+            .into_iter()
+            .map(|acronym| (acronym, ModCategory::of_acronym(acronym)))
+            .collect()
+    }
+
     #[inline]
     fn change_speed(self) -> bool {
-        self & (Self::HT | Self::DT) > 0
+        self & (Self::HT | Self::DT | Self::NC) > 0
     }
 
     #[inline]
     fn change_map(self) -> bool {
-        self & (Self::HT | Self::DT | Self::HR | Self::EZ) > 0
+        self & (Self::HT | Self::DT | Self::NC | Self::HR | Self::EZ) > 0
     }
 
     #[inline]
     fn clock_rate(self) -> f64 {
-        if self & Self::DT > 0 {
+        // NC is usually sent alongside DT but may also appear on its own
+        if self & (Self::DT | Self::NC) > 0 {
             1.5
         } else if self & Self::HT > 0 {
             0.75
@@ -76,6 +264,58 @@ impl Mods for u32 {
         }
     }
 
+    #[inline]
+    fn contains_all(self, acronyms: &[&str]) -> bool {
+        acronyms
+            .iter()
+            .all(|acronym| matches!(acronym_bits(acronym), Some(bits) if self & bits > 0))
+    }
+
+    #[inline]
+    fn contains_any(self, acronyms: &[&str]) -> bool {
+        acronyms
+            .iter()
+            .any(|acronym| matches!(acronym_bits(acronym), Some(bits) if self & bits > 0))
+    }
+
+    #[inline]
+    fn is_valid_for(self, mode: GameMode) -> bool {
+        let invalid = match mode {
+            GameMode::Osu => MANIA_ONLY,
+            GameMode::Taiko | GameMode::Catch => OSU_ONLY | MANIA_ONLY,
+            GameMode::Mania => OSU_ONLY | Self::RX,
+        };
+
+        self & invalid == 0
+    }
+
+    #[inline]
+    fn has_unranked_mods(self) -> bool {
+        self & !RANKED > 0
+    }
+
+    #[inline]
+    fn is_subset_of(self, other: u32) -> bool {
+        const SD: u32 = 1 << 5;
+        const PF: u32 = 1 << 14;
+
+        let normalize = |mods: u32| {
+            let mut normalized = mods;
+
+            if mods & Self::NC > 0 {
+                normalized |= Self::DT;
+            }
+
+            if mods & PF > 0 {
+                normalized |= SD;
+            }
+
+            normalized
+        };
+
+        normalize(self) & !normalize(other) == 0
+    }
+
     impl_mods!(nf, NF);
     impl_mods!(ez, EZ);
     impl_mods!(td, TD);
@@ -84,7 +324,183 @@ impl Mods for u32 {
     impl_mods!(dt, DT);
     impl_mods!(rx, RX);
     impl_mods!(ht, HT);
+    impl_mods!(nc, NC);
     impl_mods!(fl, FL);
     impl_mods!(so, SO);
     impl_mods!(ap, AP);
+    impl_mods!(v2, V2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{acronym_bits, Mods, UnknownAcronym, ACRONYMS, MANIA_ONLY, OSU_ONLY, RANKED};
+    use crate::GameMode;
+
+    #[test]
+    fn exact_clock_rates() {
+        assert_eq!(0_u32.clock_rate().to_bits(), 1.0_f64.to_bits());
+        assert_eq!(u32::DT.clock_rate().to_bits(), 1.5_f64.to_bits());
+        assert_eq!(u32::NC.clock_rate().to_bits(), 1.5_f64.to_bits());
+        assert_eq!(
+            (u32::DT | u32::NC).clock_rate().to_bits(),
+            1.5_f64.to_bits()
+        );
+        assert_eq!(u32::HT.clock_rate().to_bits(), 0.75_f64.to_bits());
+    }
+
+    #[test]
+    fn contains_acronyms() {
+        let hdhrdt = u32::HD | u32::HR | u32::DT;
+
+        assert!(hdhrdt.contains_all(&["HD", "HR"]));
+        assert!(hdhrdt.contains_all(&["hd", "Dt"]));
+        assert!(!hdhrdt.contains_all(&["HD", "FL"]));
+        assert!(!hdhrdt.contains_all(&["HD", "XY"]));
+
+        assert!(hdhrdt.contains_any(&["HD", "HR"]));
+        assert!(hdhrdt.contains_any(&["FL", "HR"]));
+        assert!(hdhrdt.contains_any(&["XY", "DT"]));
+        assert!(!hdhrdt.contains_any(&["FL", "EZ"]));
+        assert!(!hdhrdt.contains_any(&[]));
+    }
+
+    #[test]
+    fn from_acronyms() {
+        assert_eq!(u32::from_acronyms(&["HD", "hr"]), Ok(u32::HD | u32::HR));
+        assert_eq!(u32::from_acronyms(&[]), Ok(0));
+        assert_eq!(
+            u32::from_acronyms(&["HD", "XY", "DT"]),
+            Err(UnknownAcronym("XY".to_owned()))
+        );
+    }
+
+    #[test]
+    fn acronyms() {
+        assert_eq!((u32::HD | u32::HR).acronyms(), ["HD", "HR"]);
+        assert_eq!((u32::HR | u32::HD).acronyms(), ["HD", "HR"]);
+        assert!(0.acronyms().is_empty());
+
+        // Unknown bits are skipped
+        assert_eq!((u32::DT | u32::NC | (1 << 31)).acronyms(), ["DT", "NC"]);
+
+        for (name, bits) in ACRONYMS {
+            assert_eq!(bits.acronyms(), [name]);
+            assert_eq!(u32::from_acronyms(&bits.acronyms()), Ok(bits));
+        }
+    }
+
+    #[test]
+    fn nightcore_changes_speed() {
+        assert!(u32::NC.change_speed());
+        assert!(u32::NC.change_map());
+        assert!(!u32::HD.change_speed());
+    }
+
+    #[test]
+    fn valid_for_mode() {
+        let modes = [
+            GameMode::Osu,
+            GameMode::Taiko,
+            GameMode::Catch,
+            GameMode::Mania,
+        ];
+
+        let hddt = u32::HD | u32::DT;
+
+        for &mode in modes.iter() {
+            assert!(hddt.is_valid_for(mode));
+        }
+
+        let seven_k = acronym_bits("7K").unwrap();
+
+        assert!(!seven_k.is_valid_for(GameMode::Osu));
+        assert!(!(hddt | seven_k).is_valid_for(GameMode::Catch));
+        assert!(seven_k.is_valid_for(GameMode::Mania));
+
+        assert!(u32::AP.is_valid_for(GameMode::Osu));
+        assert!(!u32::AP.is_valid_for(GameMode::Taiko));
+        assert!(!u32::RX.is_valid_for(GameMode::Mania));
+    }
+
+    #[test]
+    fn mode_exclusive_masks() {
+        let osu_only = ["TD", "SO", "AP", "TP"];
+        let mania_only = [
+            "4K", "5K", "6K", "7K", "8K", "FI", "RD", "9K", "CO", "1K", "3K", "2K", "MR",
+        ];
+
+        let fold = |acronyms: &[&str]| {
+            acronyms
+                .iter()
+                .fold(0, |bits, acronym| bits | acronym_bits(acronym).unwrap())
+        };
+
+        assert_eq!(OSU_ONLY, fold(&osu_only));
+        assert_eq!(MANIA_ONLY, fold(&mania_only));
+    }
+
+    #[test]
+    fn unranked_mods() {
+        let hddt = u32::HD | u32::DT;
+        assert!(!hddt.has_unranked_mods());
+        assert!(!0_u32.has_unranked_mods());
+
+        let ranked = [
+            "NF", "EZ", "TD", "HD", "HR", "SD", "DT", "HT", "NC", "FL", "SO", "PF",
+        ]
+        .iter()
+        .chain(&["4K", "5K", "6K", "7K", "8K", "9K", "FI", "CO", "MR"])
+        .fold(0, |mods, acronym| mods | acronym_bits(acronym).unwrap());
+        assert_eq!(ranked, RANKED);
+        assert!(!ranked.has_unranked_mods());
+
+        for acronym in ["RX", "AT", "AP", "RD", "CN", "TP", "1K", "2K", "3K", "V2"] {
+            let mods = hddt | acronym_bits(acronym).unwrap();
+            assert!(mods.has_unranked_mods(), "{}", acronym);
+        }
+    }
+
+    #[test]
+    fn subsets() {
+        let hd = u32::from_acronyms(&["HD"]).unwrap();
+        let hdhr = u32::from_acronyms(&["HD", "HR"]).unwrap();
+        let fl = u32::from_acronyms(&["FL"]).unwrap();
+
+        assert!(hd.is_subset_of(hdhr));
+        assert!(!fl.is_subset_of(hdhr));
+        assert!(!hdhr.is_subset_of(hd));
+        assert!(0.is_subset_of(hd));
+        assert!(hdhr.is_subset_of(hdhr));
+
+        let dt = u32::from_acronyms(&["DT"]).unwrap();
+        let nc = u32::from_acronyms(&["NC"]).unwrap();
+        assert!(dt.is_subset_of(nc));
+        assert!(!nc.is_subset_of(dt));
+
+        let sd = u32::from_acronyms(&["SD"]).unwrap();
+        let pf = u32::from_acronyms(&["PF"]).unwrap();
+        assert!(sd.is_subset_of(pf));
+        assert!(!pf.is_subset_of(sd));
+    }
+
+    #[test]
+    fn categories() {
+        use super::ModCategory;
+
+        let mods = u32::from_acronyms(&["EZ", "HR", "RX", "MR", "TD", "V2"]).unwrap();
+
+        assert_eq!(
+            mods.categories(),
+            [
+                ("EZ", ModCategory::DifficultyReduction),
+                ("TD", ModCategory::System),
+                ("HR", ModCategory::DifficultyIncrease),
+                ("RX", ModCategory::Automation),
+                ("V2", ModCategory::System),
+                ("MR", ModCategory::Conversion),
+            ]
+        );
+
+        assert!(0.categories().is_empty());
+    }
 }
@@ -17,6 +17,8 @@ pub(crate) struct OsuDifficultyObject<'h> {
 
 impl<'h> OsuDifficultyObject<'h> {
     pub(crate) const MIN_DELTA_TIME: u32 = 25;
+    // Objects closer than this many ms to their previous object are considered simultaneous.
+    const SIMULTANEOUS_DELTA_TIME: f64 = 1.0;
 
     pub(crate) fn new(
         base: &'h OsuObject,
@@ -41,6 +43,12 @@ impl<'h> OsuDifficultyObject<'h> {
         }
     }
 
+    /// Whether the object starts at the same time as the previous object,
+    /// e.g. in "2B" patterns.
+    pub(crate) fn is_simultaneous(&self) -> bool {
+        self.delta_time < Self::SIMULTANEOUS_DELTA_TIME
+    }
+
     pub(crate) fn opacity_at(
         &self,
         time: f64,
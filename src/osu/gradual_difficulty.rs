@@ -55,6 +55,8 @@ pub struct OsuGradualDifficultyAttributes {
     hit_objects: Vec<OsuObject>,
     diff_objects: Vec<OsuDifficultyObject<'static>>,
     skills: Skills,
+    initial_attrs: OsuDifficultyAttributes,
+    initial_skills: Skills,
 }
 
 impl Debug for OsuGradualDifficultyAttributes {
@@ -96,6 +98,7 @@ impl OsuGradualDifficultyAttributes {
             ar: map_attrs.ar,
             hp: map_attrs.hp,
             od: map_attrs.od,
+            mods,
             ..Default::default()
         };
 
@@ -104,6 +107,7 @@ impl OsuGradualDifficultyAttributes {
             attrs: &mut attrs,
             ticks: Vec::new(),
             curve_bufs: CurveBuffers::default(),
+            fast_sliders: false,
         };
 
         let mut hit_objects: Vec<_> = map
@@ -137,6 +141,7 @@ impl OsuGradualDifficultyAttributes {
             time_preempt,
             time_fade_in,
             hit_window,
+            false,
         );
 
         let last = match hit_objects_iter.next() {
@@ -145,9 +150,11 @@ impl OsuGradualDifficultyAttributes {
                 return Self {
                     idx: 0,
                     mods,
+                    initial_attrs: attrs.clone(),
                     attrs,
                     hit_objects: Vec::new(),
                     diff_objects: Vec::new(),
+                    initial_skills: skills.clone(),
                     skills,
                 }
             }
@@ -188,58 +195,109 @@ impl OsuGradualDifficultyAttributes {
         Self {
             idx: 0,
             mods,
+            initial_attrs: attrs.clone(),
             attrs,
             diff_objects: extend_lifetime(diff_objects),
             hit_objects,
+            initial_skills: skills.clone(),
             skills,
         }
     }
 
-    fn increment_combo(h: &OsuObject, attrs: &mut OsuDifficultyAttributes) {
-        attrs.max_combo += 1;
+    /// Specify whether fast streams are assumed to be alternated rather than single-tapped,
+    /// see [`OsuStars::alternating`](crate::osu::OsuStars::alternating).
+    ///
+    /// Defaults to `false`. Resets the iterator back to the first hit object.
+    #[inline]
+    pub fn alternating(mut self, alternating: bool) -> Self {
+        self.initial_skills.speed.alternating = alternating;
+        self.reset();
 
-        match &h.kind {
-            OsuObjectKind::Circle => attrs.n_circles += 1,
-            OsuObjectKind::Slider(slider) => {
-                attrs.n_sliders += 1;
-                attrs.max_combo += slider.nested_len();
-            }
-            OsuObjectKind::Spinner { .. } => attrs.n_spinners += 1,
-        }
+        self
     }
-}
 
-fn extend_lifetime(
-    diff_objects: Vec<OsuDifficultyObject<'_>>,
-) -> Vec<OsuDifficultyObject<'static>> {
-    // SAFETY: Owned values of the references will be contained
-    // in the same struct and hence live just as long as this vec.
-    unsafe { mem::transmute(diff_objects) }
-}
+    /// Reset the iterator back to the first hit object.
+    ///
+    /// The converted hit objects are kept so the map does not need to be processed again.
+    pub fn reset(&mut self) {
+        self.idx = 0;
+        self.attrs.clone_from(&self.initial_attrs);
+        self.skills.clone_from(&self.initial_skills);
+    }
 
-impl Iterator for OsuGradualDifficultyAttributes {
-    type Item = OsuDifficultyAttributes;
+    /// The processing state that is needed to continue from the current hit object.
+    pub(crate) fn progress(&self) -> (usize, u32, OsuDifficultyAttributes, Skills) {
+        (self.idx, self.mods, self.attrs.clone(), self.skills.clone())
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Continue from a state that was previously returned by [`progress`](Self::progress).
+    pub(crate) fn set_progress(
+        &mut self,
+        idx: usize,
+        attrs: &OsuDifficultyAttributes,
+        skills: &Skills,
+    ) {
+        self.idx = idx;
+        self.attrs.clone_from(attrs);
+        self.skills.clone_from(skills);
+    }
+
+    /// The amount of hit objects in total, processed or not.
+    pub(crate) fn n_objects(&self) -> usize {
+        self.diff_objects.len()
+    }
+
+    /// The strain values of the most recently processed hit object.
+    ///
+    /// All values are `0.0` until at least two hit objects have been processed.
+    pub fn current_strains(&self) -> OsuCurrentStrains {
+        OsuCurrentStrains {
+            aim: self.skills.aim.curr_strain(),
+            speed: self.skills.speed.curr_strain(),
+            flashlight: self.skills.flashlight.curr_strain(),
+        }
+    }
+
+    /// The attributes after processing the next hit object, without actually advancing.
+    ///
+    /// Returns `None` if there are no more hit objects, just like [`Iterator::next`] would.
+    pub fn peek(&self) -> Option<OsuDifficultyAttributes> {
         let curr = self.diff_objects.get(self.idx)?;
-        self.idx += 1;
 
-        self.skills.process(curr, &self.diff_objects);
+        let mut skills = self.skills.clone();
+        skills.process(curr, &self.diff_objects);
 
-        Self::increment_combo(curr.base, &mut self.attrs);
+        let mut attrs = self.attrs.clone();
+        Self::increment_combo(curr.base, &mut attrs);
+        attrs.has_simultaneous_objects |= curr.is_simultaneous();
+
+        Some(Self::attributes(self.mods, skills, attrs))
+    }
 
+    /// Calculate the difficulty attributes based on the given skills.
+    fn attributes(
+        mods: u32,
+        skills: Skills,
+        mut attrs: OsuDifficultyAttributes,
+    ) -> OsuDifficultyAttributes {
         let Skills {
             mut aim,
             mut aim_no_sliders,
             mut speed,
             mut flashlight,
-        } = self.skills.clone();
+            flashlight_no_sliders,
+            hidden_flashlight: _,
+        } = skills;
 
+        let aim_angle_bonus = aim.angle_bonus;
+        let aim_peak_variation = aim.peak_variation();
+        let flow_aim_ratio = aim.flow_aim_ratio();
         let mut aim_rating = aim.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
         let aim_rating_no_sliders =
             aim_no_sliders.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
 
         let speed_notes = speed.relevant_note_count();
+        let speed_spacing_factor = speed.spacing_factor();
         let mut speed_rating = speed.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
 
         let mut flashlight_rating = flashlight.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
@@ -250,12 +308,22 @@ impl Iterator for OsuGradualDifficultyAttributes {
             1.0
         };
 
-        if self.mods.td() {
+        let flashlight_slider_factor = match flashlight_no_sliders {
+            Some(mut flashlight_no_sliders) if flashlight_rating > 0.0 => {
+                let flashlight_rating_no_sliders =
+                    flashlight_no_sliders.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+
+                flashlight_rating_no_sliders / flashlight_rating
+            }
+            _ => 1.0,
+        };
+
+        if mods.td() {
             aim_rating = aim_rating.powf(0.8);
             flashlight_rating = flashlight_rating.powf(0.8);
         }
 
-        if self.mods.rx() {
+        if mods.rx() {
             aim_rating *= 0.9;
             speed_rating = 0.0;
             flashlight_rating *= 0.7;
@@ -265,7 +333,7 @@ impl Iterator for OsuGradualDifficultyAttributes {
         let base_speed_performance =
             (5.0 * (speed_rating / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
 
-        let base_flashlight_performance = if self.mods.fl() {
+        let base_flashlight_performance = if mods.fl() {
             flashlight_rating * flashlight_rating * 25.0
         } else {
             0.0
@@ -284,15 +352,72 @@ impl Iterator for OsuGradualDifficultyAttributes {
             0.0
         };
 
-        let mut attrs = self.attrs.clone();
         attrs.aim = aim_rating;
         attrs.speed = speed_rating;
         attrs.flashlight = flashlight_rating;
         attrs.slider_factor = slider_factor;
+        attrs.flashlight_slider_factor = flashlight_slider_factor;
+        attrs.aim_angle_bonus = aim_angle_bonus;
+        attrs.aim_peak_variation = aim_peak_variation;
+        attrs.flow_aim_ratio = flow_aim_ratio;
         attrs.stars = star_rating;
         attrs.speed_note_count = speed_notes;
+        attrs.speed_spacing_factor = speed_spacing_factor;
 
-        Some(attrs)
+        attrs
+    }
+
+    fn increment_combo(h: &OsuObject, attrs: &mut OsuDifficultyAttributes) {
+        attrs.max_combo += 1;
+
+        match &h.kind {
+            OsuObjectKind::Circle => attrs.n_circles += 1,
+            OsuObjectKind::Slider(slider) => {
+                attrs.n_sliders += 1;
+                attrs.max_combo += slider.nested_len();
+            }
+            OsuObjectKind::Spinner { .. } => attrs.n_spinners += 1,
+        }
+    }
+}
+
+/// Strain values of the most recently processed hit object
+/// of an [`OsuGradualDifficultyAttributes`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct OsuCurrentStrains {
+    /// The current strain of the aim skill.
+    pub aim: f64,
+    /// The current strain of the speed skill.
+    pub speed: f64,
+    /// The current strain of the flashlight skill.
+    pub flashlight: f64,
+}
+
+fn extend_lifetime(
+    diff_objects: Vec<OsuDifficultyObject<'_>>,
+) -> Vec<OsuDifficultyObject<'static>> {
+    // SAFETY: Owned values of the references will be contained
+    // in the same struct and hence live just as long as this vec.
+    unsafe { mem::transmute(diff_objects) }
+}
+
+impl Iterator for OsuGradualDifficultyAttributes {
+    type Item = OsuDifficultyAttributes;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let curr = self.diff_objects.get(self.idx)?;
+        self.idx += 1;
+
+        self.skills.process(curr, &self.diff_objects);
+
+        Self::increment_combo(curr.base, &mut self.attrs);
+        self.attrs.has_simultaneous_objects |= curr.is_simultaneous();
+
+        Some(Self::attributes(
+            self.mods,
+            self.skills.clone(),
+            self.attrs.clone(),
+        ))
     }
 
     #[inline]
@@ -312,6 +437,7 @@ impl Iterator for OsuGradualDifficultyAttributes {
             self.skills.process(curr, &self.diff_objects);
 
             Self::increment_combo(curr.base, &mut self.attrs);
+            self.attrs.has_simultaneous_objects |= curr.is_simultaneous();
         }
 
         self.next()
@@ -324,3 +450,29 @@ impl ExactSizeIterator for OsuGradualDifficultyAttributes {
         self.diff_objects.len() - self.idx
     }
 }
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn peek_does_not_advance() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let mut gradual = OsuGradualDifficultyAttributes::new(&map, 8 + 64);
+
+        let len = gradual.len();
+        let peeked = gradual.peek();
+        assert_eq!(gradual.len(), len);
+        assert_eq!(gradual.peek(), peeked);
+        assert_eq!(gradual.next(), peeked);
+
+        let _ = gradual.nth(100);
+        let peeked = gradual.peek();
+        assert!(peeked.is_some());
+        assert_eq!(gradual.next(), peeked);
+
+        let _ = gradual.by_ref().last();
+        assert_eq!(gradual.peek(), None);
+    }
+}
@@ -1,12 +1,18 @@
+use std::{error::Error as StdError, fmt};
+
 use crate::{Beatmap, OsuPP};
 
-use super::{OsuGradualDifficultyAttributes, OsuPerformanceAttributes};
+use super::{
+    skills::Skills, OsuCurrentStrains, OsuDifficultyAttributes, OsuGradualDifficultyAttributes,
+    OsuPerformanceAttributes,
+};
 
 /// Aggregation for a score's current state i.e. what was the
 /// maximum combo so far and what are the current hitresults.
 ///
 /// This struct is used for [`OsuGradualPerformanceAttributes`].
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsuScoreState {
     /// Maximum combo that the score has had so far.
     /// **Not** the maximum possible combo of the map so far.
@@ -140,6 +146,7 @@ impl OsuScoreState {
 pub struct OsuGradualPerformanceAttributes<'map> {
     difficulty: OsuGradualDifficultyAttributes,
     performance: OsuPP<'map>,
+    attributes: Option<OsuDifficultyAttributes>,
 }
 
 impl<'map> OsuGradualPerformanceAttributes<'map> {
@@ -151,9 +158,38 @@ impl<'map> OsuGradualPerformanceAttributes<'map> {
         Self {
             difficulty,
             performance,
+            attributes: None,
         }
     }
 
+    /// Specify whether fast streams are assumed to be alternated rather than single-tapped,
+    /// see [`OsuStars::alternating`](crate::osu::OsuStars::alternating).
+    ///
+    /// Defaults to `false`. Resets the calculator back to the first hit object.
+    #[inline]
+    pub fn alternating(mut self, alternating: bool) -> Self {
+        self.difficulty = self.difficulty.alternating(alternating);
+        self.attributes = None;
+
+        self
+    }
+
+    /// Reset the calculator back to the first hit object so that it
+    /// can be re-used for another score on the same map and mods.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.difficulty.reset();
+        self.attributes = None;
+    }
+
+    /// The strain values of the most recently processed hit object.
+    ///
+    /// Can be called after each processing step to receive the strains alongside the performance.
+    #[inline]
+    pub fn current_strains(&self) -> OsuCurrentStrains {
+        self.difficulty.current_strains()
+    }
+
     /// Process the next hit object and calculate the
     /// performance attributes for the resulting score state.
     pub fn process_next_object(
@@ -176,6 +212,74 @@ impl<'map> OsuGradualPerformanceAttributes<'map> {
     ) -> Option<OsuPerformanceAttributes> {
         let sub = (self.difficulty.idx == 0) as usize;
         let difficulty = self.difficulty.nth(n.saturating_sub(sub))?;
+        self.attributes = Some(difficulty);
+
+        self.recalculate(state)
+    }
+
+    /// Save the current processing state so that it can later be
+    /// restored through [`restore_state`](OsuGradualPerformanceAttributes::restore_state).
+    ///
+    /// Useful to resume a score, e.g. after a live overlay reconnects,
+    /// without processing all prior hit objects again.
+    pub fn save_state(&self) -> OsuGradualCheckpoint {
+        let (idx, mods, attrs, skills) = self.difficulty.progress();
+
+        OsuGradualCheckpoint {
+            idx,
+            mods,
+            md5_hash: self.performance.map.md5_hash,
+            n_objects: self.difficulty.n_objects(),
+            attrs,
+            skills,
+            attributes: self.attributes.clone(),
+        }
+    }
+
+    /// Continue processing from a state that was saved through
+    /// [`save_state`](OsuGradualPerformanceAttributes::save_state).
+    ///
+    /// The checkpoint must have been created by a calculator for the same map and mods.
+    /// Maps are compared through their MD5 hash, if available, and their amount of hit objects.
+    /// On mismatch, an error is returned and the calculator is left unchanged.
+    pub fn restore_state(
+        &mut self,
+        checkpoint: &OsuGradualCheckpoint,
+    ) -> Result<(), CheckpointMismatch> {
+        let (_, mods, ..) = self.difficulty.progress();
+
+        let same_hash = match (checkpoint.md5_hash, self.performance.map.md5_hash) {
+            (Some(checkpoint), Some(calculator)) => checkpoint == calculator,
+            _ => true,
+        };
+
+        if !same_hash || checkpoint.n_objects != self.difficulty.n_objects() {
+            return Err(CheckpointMismatch::Map);
+        }
+
+        if checkpoint.mods != mods {
+            return Err(CheckpointMismatch::Mods {
+                checkpoint: checkpoint.mods,
+                calculator: mods,
+            });
+        }
+
+        self.difficulty
+            .set_progress(checkpoint.idx, &checkpoint.attrs, &checkpoint.skills);
+        self.attributes.clone_from(&checkpoint.attributes);
+
+        Ok(())
+    }
+
+    /// Calculate the performance attributes for an updated score state
+    /// without processing any further hit objects.
+    ///
+    /// Useful if the hitresults or combo of the already processed objects changed,
+    /// e.g. because a slider's judgement was only finalized afterwards.
+    ///
+    /// Returns `None` if no object has been processed yet.
+    pub fn recalculate(&self, state: OsuScoreState) -> Option<OsuPerformanceAttributes> {
+        let difficulty = self.attributes.clone()?;
 
         let performance = self
             .performance
@@ -188,3 +292,233 @@ impl<'map> OsuGradualPerformanceAttributes<'map> {
         Some(performance)
     }
 }
+
+/// The processing state of an [`OsuGradualPerformanceAttributes`],
+/// see [`save_state`](OsuGradualPerformanceAttributes::save_state).
+#[derive(Clone, Debug)]
+pub struct OsuGradualCheckpoint {
+    idx: usize,
+    mods: u32,
+    md5_hash: Option<[u8; 16]>,
+    n_objects: usize,
+    attrs: OsuDifficultyAttributes,
+    skills: Skills,
+    attributes: Option<OsuDifficultyAttributes>,
+}
+
+impl OsuGradualCheckpoint {
+    /// The amount of hit objects that were processed before the checkpoint was created.
+    #[inline]
+    pub fn processed_objects(&self) -> usize {
+        self.idx
+    }
+}
+
+/// Error returned by [`restore_state`](OsuGradualPerformanceAttributes::restore_state)
+/// if the checkpoint was created by a calculator for a different map or mods.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CheckpointMismatch {
+    /// The MD5 hash or the amount of hit objects of the maps differ.
+    Map,
+    /// The mods differ.
+    Mods {
+        /// The mods of the checkpoint.
+        checkpoint: u32,
+        /// The mods of the calculator.
+        calculator: u32,
+    },
+}
+
+impl fmt::Display for CheckpointMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Map => f.write_str("checkpoint was created for a different map"),
+            Self::Mods {
+                checkpoint,
+                calculator,
+            } => write!(
+                f,
+                "checkpoint was created with mods {} but the calculator uses mods {}",
+                checkpoint, calculator
+            ),
+        }
+    }
+}
+
+impl StdError for CheckpointMismatch {}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reset_reproduces_results() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let mut gradual = OsuGradualPerformanceAttributes::new(&map, 8 + 16);
+
+        let run = |gradual: &mut OsuGradualPerformanceAttributes<'_>| {
+            let mut state = OsuScoreState::new();
+            let mut results = Vec::new();
+
+            loop {
+                state.n300 += 1;
+                state.max_combo += 1;
+
+                match gradual.process_next_object(state.clone()) {
+                    Some(attrs) => results.push(attrs),
+                    None => break results,
+                }
+            }
+        };
+
+        let first = run(&mut gradual);
+        gradual.reset();
+        let second = run(&mut gradual);
+
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn current_strains() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let mut gradual = OsuGradualPerformanceAttributes::new(&map, 0);
+        let mut state = OsuScoreState::new();
+
+        let mut max_aim = 0.0_f64;
+        let mut max_speed = 0.0_f64;
+
+        loop {
+            state.n300 += 1;
+            state.max_combo += 1;
+
+            if gradual.process_next_object(state.clone()).is_none() {
+                break;
+            }
+
+            let strains = gradual.current_strains();
+            max_aim = max_aim.max(strains.aim);
+            max_speed = max_speed.max(strains.speed);
+        }
+
+        let peaks = crate::OsuStars::new(&map).strains();
+        let max_peak = |peaks: &[f64]| peaks.iter().copied().fold(0.0, f64::max);
+
+        assert!((max_aim - max_peak(&peaks.aim)).abs() < 1e-9);
+        assert!((max_speed - max_peak(&peaks.speed)).abs() < 1e-9);
+
+        let last = gradual.current_strains();
+        assert!(last.aim > 0.0 && last.aim <= max_aim);
+        assert!(last.speed > 0.0 && last.speed <= max_speed);
+    }
+
+    #[test]
+    fn changing_states() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let mut gradual = OsuGradualPerformanceAttributes::new(&map, 8);
+        let mut state = OsuScoreState::new();
+
+        assert!(gradual.recalculate(state.clone()).is_none());
+
+        let mut combo = 0;
+        let mut last = None;
+
+        for i in 1.. {
+            match i % 7 {
+                0 => {
+                    state.n_misses += 1;
+                    combo = 0;
+                }
+                3 => {
+                    state.n100 += 1;
+                    combo += 1;
+                }
+                _ => {
+                    state.n300 += 1;
+                    combo += 1;
+                }
+            }
+
+            state.max_combo = state.max_combo.max(combo);
+
+            match gradual.process_next_object(state.clone()) {
+                Some(attrs) => last = Some(attrs),
+                None => break,
+            }
+        }
+
+        // The last judgement turned out differently
+        state.n300 -= 1;
+        state.n50 += 1;
+
+        let last = last.unwrap();
+        let recalculated = gradual.recalculate(state.clone()).unwrap();
+        assert!(recalculated.pp < last.pp);
+
+        let batch = OsuPP::new(&map).mods(8).state(state).calculate();
+        assert_eq!(recalculated, batch);
+    }
+
+    #[test]
+    fn checkpoints() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let mut gradual = OsuGradualPerformanceAttributes::new(&map, 8);
+        let mut state = OsuScoreState::new();
+
+        let mut process = |gradual: &mut OsuGradualPerformanceAttributes<'_>| {
+            state.n300 += 1;
+            state.max_combo += 1;
+
+            gradual.process_next_object(state.clone())
+        };
+
+        for _ in 0..100 {
+            assert!(process(&mut gradual).is_some());
+        }
+
+        let checkpoint = gradual.save_state();
+        assert_eq!(checkpoint.processed_objects(), 100);
+
+        assert_eq!(
+            OsuGradualPerformanceAttributes::new(&map, 16).restore_state(&checkpoint),
+            Err(CheckpointMismatch::Mods {
+                checkpoint: 8,
+                calculator: 16
+            })
+        );
+
+        // Another map with the same amount of hit objects
+        let mut other_map = map.clone();
+        other_map.md5_hash = Some([0; 16]);
+
+        assert_eq!(
+            OsuGradualPerformanceAttributes::new(&other_map, 8).restore_state(&checkpoint),
+            Err(CheckpointMismatch::Map)
+        );
+
+        let mut resumed = OsuGradualPerformanceAttributes::new(&map, 8);
+        assert_eq!(resumed.restore_state(&checkpoint), Ok(()));
+
+        let mut state = OsuScoreState::new();
+        state.n300 = 100;
+        state.max_combo = 100;
+
+        assert_eq!(
+            resumed.recalculate(state.clone()),
+            gradual.recalculate(state.clone())
+        );
+
+        loop {
+            state.n300 += 1;
+            state.max_combo += 1;
+
+            let expected = gradual.process_next_object(state.clone());
+            assert_eq!(resumed.process_next_object(state.clone()), expected);
+
+            if expected.is_none() {
+                break;
+            }
+        }
+    }
+}
@@ -3,21 +3,35 @@ mod gradual_difficulty;
 mod gradual_performance;
 mod osu_object;
 mod pp;
+mod relax;
 mod scaling_factor;
 mod skills;
 
-use crate::{curve::CurveBuffers, parse::Pos2, AnyStars, Beatmap, GameMode, Mods};
+use std::{
+    error::Error as StdError,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    beatmap::difficulty_range,
+    curve::CurveBuffers,
+    parse::{HitObjectKind, Pos2},
+    AnyStars, Beatmap, GameMode, Mods, PerformanceBreakdown, PerformanceComponent,
+};
 
 use self::{
     difficulty_object::{Distances, OsuDifficultyObject},
     osu_object::{ObjectParameters, OsuObject},
     scaling_factor::ScalingFactor,
-    skills::{Skill, Skills},
+    skills::{Flashlight, Skill, Skills},
 };
 
-pub use self::{gradual_difficulty::*, gradual_performance::*, pp::*};
+pub use self::{gradual_difficulty::*, gradual_performance::*, pp::*, relax::*};
 
 const SECTION_LEN: f64 = 400.0;
+// Lower bound for custom section lengths so that sections always advance.
+const MIN_SECTION_LEN: f64 = 1.0;
 const DIFFICULTY_MULTIPLIER: f64 = 0.0675;
 // * Change radius to 50 to make 100 the diameter. Easier for mental maths.
 const NORMALIZED_RADIUS: f32 = 50.0;
@@ -27,6 +41,12 @@ const PERFORMANCE_BASE_MULTIPLIER: f64 = 1.14;
 const PREEMPT_MIN: f64 = 450.0;
 const FADE_IN_DURATION_MULTIPLIER: f64 = 0.4;
 const PLAYFIELD_BASE_SIZE: Pos2 = Pos2 { x: 512.0, y: 384.0 };
+// Amount of processed objects inbetween two checks of the cancellation flag; must be a power of two.
+const CANCEL_CHECK_INTERVAL: usize = 256;
+// Maximum clock-adjusted ms between two notes of a stream, i.e. 1/4 at 120 BPM.
+const STREAM_MAX_DELTA_TIME: f64 = 125.0;
+// Minimum amount of notes to be considered a stream.
+const STREAM_MIN_LEN: usize = 3;
 
 /// Difficulty calculator on osu!standard maps.
 ///
@@ -52,6 +72,12 @@ pub struct OsuStars<'map> {
     pub(crate) mods: u32,
     pub(crate) passed_objects: Option<usize>,
     pub(crate) clock_rate: Option<f64>,
+    pub(crate) stacking: bool,
+    pub(crate) alternating: bool,
+    pub(crate) spinner_strain: Option<f64>,
+    pub(crate) fast_sliders: bool,
+    pub(crate) cancel: Option<&'map AtomicBool>,
+    pub(crate) objects: Option<&'map OsuObjects>,
 }
 
 impl<'map> OsuStars<'map> {
@@ -63,6 +89,12 @@ impl<'map> OsuStars<'map> {
             mods: 0,
             passed_objects: None,
             clock_rate: None,
+            stacking: true,
+            alternating: false,
+            spinner_strain: None,
+            fast_sliders: false,
+            cancel: None,
+            objects: None,
         }
     }
 
@@ -109,25 +141,111 @@ impl<'map> OsuStars<'map> {
         self
     }
 
+    /// Specify whether stacked objects should be offset based on the map's
+    /// [`stack_leniency`](Beatmap::stack_leniency).
+    ///
+    /// Defaults to `true`. Disabling stacking leaves all objects at their
+    /// original position which affects distances and thus mostly aim.
+    #[inline]
+    pub fn stacking(mut self, stacking: bool) -> Self {
+        self.stacking = stacking;
+
+        self
+    }
+
+    /// Specify whether fast streams are assumed to be alternated rather than single-tapped.
+    ///
+    /// Defaults to `false`. If enabled, the speed skill no longer caps the
+    /// strain of objects whose delta time is close to the OD hit window.
+    #[inline]
+    pub fn alternating(mut self, alternating: bool) -> Self {
+        self.alternating = alternating;
+
+        self
+    }
+
+    /// Specify a strain that is used as minimum peak for all sections that overlap with a spinner.
+    ///
+    /// Only affects [`strains`](OsuStars::strains), not the difficulty attributes.
+    /// Defaults to `None` in which case spinners do not add any strain so that sections
+    /// during spinners are often zero. A placeholder strain keeps such sections visible,
+    /// e.g. when aligning a strain graph with the audio.
+    #[inline]
+    pub fn spinner_strain(mut self, spinner_strain: f64) -> Self {
+        self.spinner_strain = Some(spinner_strain);
+
+        self
+    }
+
+    /// Specify whether slider paths are approximated by straight lines between
+    /// their control points instead of sampling bezier, perfect, and catmull curves.
+    ///
+    /// Defaults to `false`. Slider durations and tick counts remain exact but
+    /// slider ends and ticks may be positioned off the actual path which mostly
+    /// affects aim. On regular maps stars deviate by less than 2%.
+    #[inline]
+    pub fn fast_sliders(mut self, fast_sliders: bool) -> Self {
+        self.fast_sliders = fast_sliders;
+
+        self
+    }
+
+    /// Provide a flag that allows aborting the calculation from another thread.
+    ///
+    /// The flag is only checked by [`try_calculate`](OsuStars::try_calculate)
+    /// which returns [`Cancelled`] shortly after the flag was set to `true`.
+    #[inline]
+    pub fn cancel_flag(mut self, cancel: &'map AtomicBool) -> Self {
+        self.cancel = Some(cancel);
+
+        self
+    }
+
     /// Calculate all difficulty related values, including stars.
+    ///
+    /// A flag specified through [`cancel_flag`](OsuStars::cancel_flag) is ignored,
+    /// use [`try_calculate`](OsuStars::try_calculate) for a cancellable calculation.
     #[inline]
-    pub fn calculate(self) -> OsuDifficultyAttributes {
+    pub fn calculate(mut self) -> OsuDifficultyAttributes {
+        self.cancel = None;
+
+        match self.try_calculate() {
+            Ok(attrs) => attrs,
+            Err(Cancelled) => unreachable!("calculation without flag cannot be cancelled"),
+        }
+    }
+
+    /// Same as [`calculate`](OsuStars::calculate) but checks the flag specified
+    /// through [`cancel_flag`](OsuStars::cancel_flag) periodically and returns
+    /// [`Cancelled`] if it has been set.
+    pub fn try_calculate(self) -> Result<OsuDifficultyAttributes, Cancelled> {
         let mods = self.mods;
 
-        let (skills, mut attrs) = calculate_skills(self);
+        let (skills, mut attrs) = calculate_skills(self, SECTION_LEN, false)?;
+
+        // Without any objects there is no difficulty
+        if attrs.max_combo == 0 {
+            return Ok(attrs);
+        }
 
         let Skills {
             mut aim,
             mut aim_no_sliders,
             mut speed,
             mut flashlight,
+            flashlight_no_sliders,
+            hidden_flashlight: _,
         } = skills;
 
+        let aim_angle_bonus = aim.angle_bonus;
+        let aim_peak_variation = aim.peak_variation();
+        let flow_aim_ratio = aim.flow_aim_ratio();
         let mut aim_rating = aim.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
         let aim_rating_no_sliders =
             aim_no_sliders.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
 
         let speed_notes = speed.relevant_note_count();
+        let speed_spacing_factor = speed.spacing_factor();
         let mut speed_rating = speed.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
 
         let mut flashlight_rating = flashlight.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
@@ -138,6 +256,16 @@ impl<'map> OsuStars<'map> {
             1.0
         };
 
+        let flashlight_slider_factor = match flashlight_no_sliders {
+            Some(mut flashlight_no_sliders) if flashlight_rating > 0.0 => {
+                let flashlight_rating_no_sliders =
+                    flashlight_no_sliders.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+
+                flashlight_rating_no_sliders / flashlight_rating
+            }
+            _ => 1.0,
+        };
+
         if mods.td() {
             aim_rating = aim_rating.powf(0.8);
             flashlight_rating = flashlight_rating.powf(0.8);
@@ -181,10 +309,186 @@ impl<'map> OsuStars<'map> {
         attrs.speed = speed_rating;
         attrs.flashlight = flashlight_rating;
         attrs.slider_factor = slider_factor;
+        attrs.flashlight_slider_factor = flashlight_slider_factor;
+        attrs.aim_angle_bonus = aim_angle_bonus;
+        attrs.aim_peak_variation = aim_peak_variation;
+        attrs.flow_aim_ratio = flow_aim_ratio;
         attrs.stars = star_rating;
         attrs.speed_note_count = speed_notes;
+        attrs.speed_spacing_factor = speed_spacing_factor;
 
-        attrs
+        Ok(attrs)
+    }
+
+    /// Count how many objects are visible at the time each object appears.
+    ///
+    /// An object counts as visible from the start of its approach until its start time.
+    /// The returned pairs consist of the object's appear time in ms, adjusted by the clock rate,
+    /// and the amount of visible objects at that time, including the object itself.
+    ///
+    /// Since the approach window depends on the AR, mods like HR or EZ affect the density.
+    /// Suitable to plot a density heatmap that is independent of the strains.
+    pub fn note_density(self) -> Vec<(f64, usize)> {
+        let take = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+
+        let preempt = self
+            .map
+            .attributes()
+            .mods(self.mods)
+            .clock_rate(clock_rate)
+            .hit_windows()
+            .ar;
+
+        let start_times: Vec<_> = self
+            .map
+            .hit_objects
+            .iter()
+            .take(take)
+            .map(|h| h.start_time / clock_rate)
+            .collect();
+
+        let mut first_visible = 0;
+
+        start_times
+            .iter()
+            .enumerate()
+            .map(|(i, &start_time)| {
+                let appear_time = start_time - preempt;
+
+                while start_times[first_visible] < appear_time {
+                    first_visible += 1;
+                }
+
+                (appear_time, i + 1 - first_visible)
+            })
+            .collect()
+    }
+
+    /// The amount of full spins required to clear each spinner of the map.
+    ///
+    /// The requirement depends on the spinner's duration and the OD, including HR and EZ.
+    /// Rate-changing mods do not change the amount of required spins, only the time to do them.
+    pub fn spinner_spins(self) -> Vec<usize> {
+        let take = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+        let od = (self.map.od as f64 * self.mods.od_ar_hp_multiplier()).min(10.0);
+        let spins_per_second = difficulty_range(od, 3.0, 5.0, 7.5);
+
+        self.map
+            .hit_objects
+            .iter()
+            .take(take)
+            .filter_map(|h| match h.kind {
+                HitObjectKind::Spinner { end_time } => {
+                    let seconds = (end_time - h.start_time) / 1000.0;
+
+                    Some((seconds * spins_per_second).max(0.0) as usize)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Histogram of stream lengths where the value at index `n` is the amount of streams with `n` notes.
+    ///
+    /// Two consecutive notes belong to the same stream if their clock-adjusted start times
+    /// are at most 125ms apart, i.e. 1/4 at 120 BPM. Spinners interrupt streams and only
+    /// streams of at least three notes are counted.
+    pub fn stream_lengths(self) -> Vec<usize> {
+        fn add_stream(histogram: &mut Vec<usize>, len: usize) {
+            if len < STREAM_MIN_LEN {
+                return;
+            }
+
+            if histogram.len() <= len {
+                histogram.resize(len + 1, 0);
+            }
+
+            histogram[len] += 1;
+        }
+
+        let take = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+
+        let mut histogram = Vec::new();
+        let mut curr_len = 0;
+        let mut last_start_time = None;
+
+        for h in self.map.hit_objects.iter().take(take) {
+            if let HitObjectKind::Spinner { .. } = h.kind {
+                add_stream(&mut histogram, curr_len);
+                curr_len = 0;
+                last_start_time = None;
+
+                continue;
+            }
+
+            let start_time = h.start_time / clock_rate;
+
+            match last_start_time {
+                Some(last) if start_time - last <= STREAM_MAX_DELTA_TIME => curr_len += 1,
+                _ => {
+                    add_stream(&mut histogram, curr_len);
+                    curr_len = 1;
+                }
+            }
+
+            last_start_time = Some(start_time);
+        }
+
+        add_stream(&mut histogram, curr_len);
+
+        histogram
+    }
+
+    /// The cursor velocity in osu!pixels per ms that is required to hit the objects of each
+    /// 400ms section, e.g. to analyze the cursor movement of Relax scores.
+    ///
+    /// The returned pairs consist of the section's start time in ms, adjusted by the clock rate,
+    /// and the highest velocity within the section. The velocity towards an object is the
+    /// distance from the previous object's end, i.e. the lazy slider end, divided by the time
+    /// inbetween. Objects after spinners, spinners themselves, and simultaneous objects
+    /// don't require any cursor movement.
+    pub fn cursor_velocity(mut self) -> Vec<(f64, f64)> {
+        self.cancel = None;
+
+        let calculate = with_difficulty_objects(self, |skill_params, diff_objects| {
+            let scaling_factor = skill_params.scaling_factor.factor as f64;
+
+            let mut curr_section_end = match diff_objects.first() {
+                Some(h) => (h.start_time / SECTION_LEN).ceil() * SECTION_LEN,
+                None => return Ok(Vec::new()),
+            };
+
+            let mut curr_section_peak = 0.0_f64;
+            let mut velocities = Vec::new();
+
+            for curr in diff_objects {
+                while curr.start_time > curr_section_end {
+                    velocities.push((curr_section_end - SECTION_LEN, curr_section_peak));
+                    curr_section_peak = 0.0;
+                    curr_section_end += SECTION_LEN;
+                }
+
+                let after_spinner = curr.idx > 0 && diff_objects[curr.idx - 1].base.is_spinner();
+
+                if curr.is_simultaneous() || curr.base.is_spinner() || after_spinner {
+                    continue;
+                }
+
+                let velocity = curr.dists.lazy_jump_dist / scaling_factor / curr.strain_time;
+                curr_section_peak = curr_section_peak.max(velocity);
+            }
+
+            velocities.push((curr_section_end - SECTION_LEN, curr_section_peak));
+
+            Ok(velocities)
+        });
+
+        match calculate {
+            Ok((velocities, _)) => velocities,
+            Err(Cancelled) => unreachable!("calculation without flag cannot be cancelled"),
+        }
     }
 
     /// Calculate the skill strains.
@@ -192,28 +496,190 @@ impl<'map> OsuStars<'map> {
     /// Suitable to plot the difficulty of a map over time.
     #[inline]
     pub fn strains(self) -> OsuStrains {
-        let (skills, _) = calculate_skills(self);
+        self.strains_with_section_len(SECTION_LEN)
+    }
+
+    /// Same as [`strains`](OsuStars::strains) but each strain peak covers
+    /// `section_len` ms instead of the default 400ms.
+    ///
+    /// The section length is clamped to be at least 1ms.
+    pub fn strains_with_section_len(mut self, section_len: f64) -> OsuStrains {
+        let section_len = section_len.max(MIN_SECTION_LEN);
+        self.cancel = None;
+
+        // Hidden only affects the flashlight skill so without HD
+        // an additional flashlight skill provides the hidden reading strains
+        let hidden_reading = !self.mods.hd();
+
+        let spinner_sections = self
+            .spinner_strain
+            .map(|strain| (strain, self.spinner_sections(section_len)));
+
+        let (skills, _) = match calculate_skills(self, section_len, hidden_reading) {
+            Ok(tuple) => tuple,
+            Err(Cancelled) => unreachable!("calculation without flag cannot be cancelled"),
+        };
 
         let Skills {
             aim,
             aim_no_sliders,
             speed,
             flashlight,
+            flashlight_no_sliders: _,
+            hidden_flashlight,
         } = skills;
 
-        OsuStrains {
-            section_len: SECTION_LEN,
+        let hidden_reading = match hidden_flashlight {
+            Some(hidden_flashlight) => hidden_flashlight.strain_peaks,
+            None => flashlight.strain_peaks.clone(),
+        };
+
+        let mut strains = OsuStrains {
+            section_len,
             aim: aim.strain_peaks,
             aim_no_sliders: aim_no_sliders.strain_peaks,
             speed: speed.strain_peaks,
             flashlight: flashlight.strain_peaks,
+            hidden_reading,
+        };
+
+        if let Some((strain, sections)) = spinner_sections {
+            let peaks = [
+                &mut strains.aim,
+                &mut strains.aim_no_sliders,
+                &mut strains.speed,
+                &mut strains.flashlight,
+                &mut strains.hidden_reading,
+            ];
+
+            for peaks in peaks {
+                for &i in sections.iter() {
+                    if let Some(peak) = peaks.get_mut(i) {
+                        *peak = peak.max(strain);
+                    }
+                }
+            }
+        }
+
+        strains
+    }
+
+    /// Calculate the strain of each skill for every hit object.
+    ///
+    /// Contrary to [`strains`](OsuStars::strains), which only keeps the peak of each
+    /// section, this is suitable to find the hardest objects of a map or to draw
+    /// a heatmap with per-object precision.
+    pub fn object_strains(mut self) -> OsuObjectStrains {
+        self.cancel = None;
+
+        let take = self
+            .passed_objects
+            .unwrap_or(self.map.hit_objects.len())
+            .min(self.map.hit_objects.len());
+
+        let times: Vec<_> = self.map.hit_objects[..take]
+            .iter()
+            .map(|h| h.start_time)
+            .collect();
+
+        let mods = self.mods;
+        let alternating = self.alternating;
+
+        let calculate = with_difficulty_objects(self, |skill_params, diff_objects| {
+            let mut skills = Skills::new(
+                mods,
+                skill_params.scaling_factor.radius,
+                skill_params.time_preempt,
+                skill_params.time_fade_in,
+                skill_params.hit_window,
+                alternating,
+            );
+
+            // The first object doesn't generate a strain
+            let init = || {
+                let mut strains = Vec::with_capacity(take);
+                strains.extend(times.first().map(|_| 0.0));
+
+                strains
+            };
+
+            let mut aim = init();
+            let mut aim_no_sliders = init();
+            let mut speed = init();
+            let mut flashlight = init();
+
+            for curr in diff_objects {
+                skills.process(curr, diff_objects);
+
+                aim.push(skills.aim.curr_strain());
+                aim_no_sliders.push(skills.aim_no_sliders.curr_strain());
+                speed.push(skills.speed.curr_strain());
+                flashlight.push(skills.flashlight.curr_strain());
+            }
+
+            Ok(OsuObjectStrains {
+                times: Vec::new(),
+                aim,
+                aim_no_sliders,
+                speed,
+                flashlight,
+            })
+        });
+
+        match calculate {
+            Ok((strains, _)) => OsuObjectStrains { times, ..strains },
+            Err(Cancelled) => unreachable!("calculation without flag cannot be cancelled"),
+        }
+    }
+
+    /// End time of the first strain section, adjusted by the clock rate.
+    pub(crate) fn first_section_end(&self, section_len: f64) -> Option<f64> {
+        let take = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+
+        // Sections start with the second object since the first one has no strain
+        self.map
+            .hit_objects
+            .iter()
+            .take(take)
+            .nth(1)
+            .map(|h| (h.start_time / clock_rate / section_len).ceil() * section_len)
+    }
+
+    /// Indices of all strain sections that overlap with a spinner.
+    fn spinner_sections(&self, section_len: f64) -> Vec<usize> {
+        let take = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+        let hit_objects = &self.map.hit_objects[..take.min(self.map.hit_objects.len())];
+
+        let first_section_end = match self.first_section_end(section_len) {
+            Some(first_section_end) => first_section_end,
+            None => return Vec::new(),
+        };
+
+        let mut sections = Vec::new();
+
+        for h in hit_objects {
+            if let HitObjectKind::Spinner { end_time } = h.kind {
+                let start = (h.start_time / clock_rate - first_section_end) / section_len;
+                let end = (end_time / clock_rate - first_section_end) / section_len;
+
+                // Section `i` covers the time range `(end - len, end]`
+                let first = start.ceil().max(0.0) as usize;
+                let last = (end + 1.0).ceil().max(0.0) as usize;
+
+                sections.extend(first..last);
+            }
         }
+
+        sections
     }
 }
 
 /// The result of calculating the strains on a osu! map.
 /// Suitable to plot the difficulty of a map over time.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsuStrains {
     /// Time in ms inbetween two strains.
     pub section_len: f64, // TODO: remove field, make it a method
@@ -225,8 +691,54 @@ pub struct OsuStrains {
     pub speed: Vec<f64>,
     /// Strain peaks of the flashlight skill.
     pub flashlight: Vec<f64>,
+    /// Strain peaks of the flashlight skill as if HD was enabled,
+    /// i.e. where the map is hard to read without approach circles.
+    pub hidden_reading: Vec<f64>,
 }
 
+/// The strain of each skill for every hit object of an osu! map,
+/// see [`OsuStars::object_strains`].
+///
+/// All vectors contain one value per hit object.
+/// The first object has no strain so its values are always `0.0`.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OsuObjectStrains {
+    /// Start time in ms of each hit object as specified in the map,
+    /// i.e. the clock rate is not applied.
+    pub times: Vec<f64>,
+    /// Strains of the aim skill.
+    pub aim: Vec<f64>,
+    /// Strains of the aim skill without sliders.
+    pub aim_no_sliders: Vec<f64>,
+    /// Strains of the speed skill.
+    pub speed: Vec<f64>,
+    /// Strains of the flashlight skill.
+    pub flashlight: Vec<f64>,
+}
+
+impl OsuObjectStrains {
+    /// Returns the number of hit objects.
+    #[inline]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+}
+
+/// Error returned by [`OsuStars::try_calculate`] if the
+/// calculation was aborted through the cancellation flag.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("difficulty calculation was cancelled")
+    }
+}
+
+impl StdError for Cancelled {}
+
 impl OsuStrains {
     /// Returns the number of strain peaks per skill.
     #[inline]
@@ -236,12 +748,127 @@ impl OsuStrains {
     }
 }
 
-fn calculate_skills(params: OsuStars<'_>) -> (Skills, OsuDifficultyAttributes) {
+fn calculate_skills(
+    params: OsuStars<'_>,
+    section_len: f64,
+    hidden_reading: bool,
+) -> Result<(Skills, OsuDifficultyAttributes), Cancelled> {
+    let mods = params.mods;
+    let alternating = params.alternating;
+    let cancel = params.cancel;
+
+    with_difficulty_objects(params, |skill_params, diff_objects| {
+        let mut skills = Skills::new(
+            mods,
+            skill_params.scaling_factor.radius,
+            skill_params.time_preempt,
+            skill_params.time_fade_in,
+            skill_params.hit_window,
+            alternating,
+        );
+
+        if hidden_reading {
+            skills.hidden_flashlight = Some(Flashlight::new(
+                mods | u32::HD,
+                skill_params.scaling_factor.radius,
+                skill_params.time_preempt,
+                skill_params.time_preempt * FADE_IN_DURATION_MULTIPLIER,
+                true,
+            ));
+        }
+
+        skills.set_section_len(section_len);
+
+        for (i, curr) in diff_objects.iter().enumerate() {
+            check_cancel(cancel, i)?;
+            skills.process(curr, diff_objects);
+        }
+
+        Ok(skills)
+    })
+}
+
+fn check_cancel(cancel: Option<&AtomicBool>, i: usize) -> Result<(), Cancelled> {
+    match cancel {
+        Some(cancel) if i & (CANCEL_CHECK_INTERVAL - 1) == 0 && cancel.load(Ordering::Relaxed) => {
+            Err(Cancelled)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Hit objects of a map before stacking is applied.
+///
+/// They don't depend on the mods or the clock rate so
+/// they can be shared between calculations on the same map.
+#[derive(Clone, Debug)]
+pub(crate) struct OsuObjects {
+    hit_objects: Vec<OsuObject>,
+    // Only the object counts and the max combo are set
+    counts: OsuDifficultyAttributes,
+    fast_sliders: bool,
+}
+
+impl OsuObjects {
+    pub(crate) fn new(map: &Beatmap, take: usize, fast_sliders: bool) -> Self {
+        let mut counts = OsuDifficultyAttributes::default();
+
+        let mut params = ObjectParameters {
+            map,
+            attrs: &mut counts,
+            ticks: Vec::new(),
+            curve_bufs: CurveBuffers::default(),
+            fast_sliders,
+        };
+
+        let hit_objects = map
+            .hit_objects
+            .iter()
+            .take(take)
+            .map(|h| OsuObject::new(h, &mut params))
+            .collect();
+
+        Self {
+            hit_objects,
+            counts,
+            fast_sliders,
+        }
+    }
+
+    /// Whether the objects match a calculation with the given parameters.
+    fn fits(&self, take: usize, fast_sliders: bool) -> bool {
+        self.fast_sliders == fast_sliders && self.hit_objects.len() == take
+    }
+
+    fn into_parts(self) -> (Vec<OsuObject>, OsuDifficultyAttributes) {
+        (self.hit_objects, self.counts)
+    }
+}
+
+/// Values that are required to set up the skills.
+struct SkillParameters {
+    scaling_factor: ScalingFactor,
+    time_preempt: f64,
+    time_fade_in: f64,
+    hit_window: f64,
+}
+
+/// Create the difficulty objects of the map and pass them to `f`.
+fn with_difficulty_objects<T>(
+    params: OsuStars<'_>,
+    f: impl FnOnce(SkillParameters, &[OsuDifficultyObject<'_>]) -> Result<T, Cancelled>,
+) -> Result<(T, OsuDifficultyAttributes), Cancelled> {
     let OsuStars {
         map,
         mods,
         passed_objects,
         clock_rate,
+        stacking: apply_stacking,
+        alternating: _,
+        spinner_strain: _,
+        fast_sliders,
+        cancel,
+        objects,
     } = params;
 
     let take = passed_objects.unwrap_or(map.hit_objects.len());
@@ -267,33 +894,29 @@ fn calculate_skills(params: OsuStars<'_>) -> (Skills, OsuDifficultyAttributes) {
         400.0 * (time_preempt / PREEMPT_MIN).min(1.0)
     };
 
+    let (mut hit_objects, counts) = match objects {
+        Some(objects) if objects.fits(take.min(map.hit_objects.len()), fast_sliders) => {
+            (objects.hit_objects.clone(), objects.counts.clone())
+        }
+        _ => OsuObjects::new(map, take, fast_sliders).into_parts(),
+    };
+
     let mut attrs = OsuDifficultyAttributes {
         ar: map_attrs.ar,
         hp: map_attrs.hp,
         od: map_attrs.od,
-        ..Default::default()
-    };
-
-    let mut params = ObjectParameters {
-        map,
-        attrs: &mut attrs,
-        ticks: Vec::new(),
-        curve_bufs: CurveBuffers::default(),
+        mods,
+        ..counts
     };
 
-    let mut hit_objects: Vec<_> = map
-        .hit_objects
-        .iter()
-        .take(take)
-        .map(|h| OsuObject::new(h, &mut params))
-        .collect();
-
     let stack_threshold = time_preempt * map.stack_leniency as f64;
 
-    if map.version >= 6 {
-        stacking(&mut hit_objects, stack_threshold);
-    } else {
-        old_stacking(&mut hit_objects, stack_threshold);
+    if apply_stacking {
+        if map.version >= 6 {
+            stacking(&mut hit_objects, stack_threshold);
+        } else {
+            old_stacking(&mut hit_objects, stack_threshold);
+        }
     }
 
     let mut hit_objects = hit_objects.iter_mut().map(|h| {
@@ -302,17 +925,16 @@ fn calculate_skills(params: OsuStars<'_>) -> (Skills, OsuDifficultyAttributes) {
         h
     });
 
-    let mut skills = Skills::new(
-        mods,
-        scaling_factor.radius,
+    let skill_params = SkillParameters {
+        scaling_factor,
         time_preempt,
         time_fade_in,
         hit_window,
-    );
+    };
 
     let last = match hit_objects.next() {
         Some(prev) => prev,
-        None => return (skills, attrs),
+        None => return f(skill_params, &[]).map(|value| (value, attrs)),
     };
 
     let mut last_last = None;
@@ -324,6 +946,8 @@ fn calculate_skills(params: OsuStars<'_>) -> (Skills, OsuDifficultyAttributes) {
     let mut diff_objects = Vec::with_capacity(hit_objects.len());
 
     for (i, curr) in hit_objects.enumerate() {
+        check_cancel(cancel, i)?;
+
         let delta_time = (curr.start_time - last.start_time) / clock_rate;
 
         // * Capped to 25ms to prevent difficulty calculation breaking from simultaneous objects.
@@ -339,17 +963,14 @@ fn calculate_skills(params: OsuStars<'_>) -> (Skills, OsuDifficultyAttributes) {
         );
 
         let diff_obj = OsuDifficultyObject::new(curr, last, clock_rate, i, dists);
+        attrs.has_simultaneous_objects |= diff_obj.is_simultaneous();
         diff_objects.push(diff_obj);
 
         last_last = Some(last);
         last = &*curr;
     }
 
-    for curr in diff_objects.iter() {
-        skills.process(curr, &diff_objects);
-    }
-
-    (skills, attrs)
+    f(skill_params, &diff_objects).map(|value| (value, attrs))
 }
 
 fn stacking(hit_objects: &mut [OsuObject], stack_threshold: f64) {
@@ -504,6 +1125,7 @@ fn old_stacking(hit_objects: &mut [OsuObject], stack_threshold: f64) {
 
 /// The result of a difficulty calculation on an osu!standard map.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsuDifficultyAttributes {
     /// The aim portion of the total strain.
     pub aim: f64,
@@ -513,8 +1135,33 @@ pub struct OsuDifficultyAttributes {
     pub flashlight: f64,
     /// The ratio of the aim strain with and without considering sliders
     pub slider_factor: f64,
+    /// The ratio of the flashlight strain with and without considering sliders.
+    ///
+    /// Only calculated with FL, otherwise `1.0`.
+    pub flashlight_slider_factor: f64,
+    /// The summed strain that the aim skill awarded for angle changes
+    /// across all objects i.e. the acute and wide angle bonuses.
+    pub aim_angle_bonus: f64,
+    /// The coefficient of variation of the aim strain peaks, ignoring sections without strain.
+    ///
+    /// Low values indicate consistently hard aim while high values indicate spikes.
+    pub aim_peak_variation: f64,
+    /// The share of the aim strain in `[0, 1]` that stems from flow aim rather than snap aim.
+    ///
+    /// Movement through wide angles counts as flow aim while
+    /// acute angles, e.g. back-and-forth jumps, count as snap aim.
+    pub flow_aim_ratio: f64,
     /// The number of clickable objects weighted by difficulty.
     pub speed_note_count: f64,
+    /// The average multiplier in `[1, 2]` that the speed skill applied for the spacing
+    /// between objects, weighted by each object's speed difficulty without it.
+    ///
+    /// Tightly packed streams are close to `1.0` while spaced streams are higher.
+    pub speed_spacing_factor: f64,
+    /// Whether the map contains objects that start at the same time, e.g. "2B" patterns.
+    ///
+    /// Their strain time is capped to 25ms so they're still evaluated like regular objects.
+    pub has_simultaneous_objects: bool,
     /// The approach rate.
     pub ar: f64,
     /// The overall difficulty
@@ -531,6 +1178,8 @@ pub struct OsuDifficultyAttributes {
     pub stars: f64,
     /// The maximum combo.
     pub max_combo: usize,
+    /// The mods that the attributes were calculated with.
+    pub mods: u32,
 }
 
 impl OsuDifficultyAttributes {
@@ -543,6 +1192,7 @@ impl OsuDifficultyAttributes {
 
 /// The result of a performance calculation on an osu!standard map.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsuPerformanceAttributes {
     /// The difficulty attributes that were used for the performance calculation
     pub difficulty: OsuDifficultyAttributes,
@@ -556,8 +1206,28 @@ pub struct OsuPerformanceAttributes {
     pub pp_flashlight: f64,
     /// The speed portion of the final pp.
     pub pp_speed: f64,
+    /// The portion of the final pp that is attributable to HD and FL.
+    ///
+    /// Calculated as the difference to the pp without HD and FL so it also includes
+    /// the additional value of both mods combined. Zero if neither mod is enabled.
+    pub pp_reading: f64,
+    /// The multiplier that [`pp_aim`](OsuPerformanceAttributes::pp_aim) and
+    /// [`pp_speed`](OsuPerformanceAttributes::pp_speed) include for the map's length.
+    ///
+    /// It ranges from `0.95` for very short maps upwards and exceeds `1.0` after 250 hits.
+    pub length_bonus: f64,
     /// Misses including an approximated amount of slider breaks
     pub effective_miss_count: f64,
+    /// The accuracy between `0.0` and `1.0` of the hitresults that were used.
+    pub accuracy: f64,
+    /// The combo of the score that was used.
+    pub combo: usize,
+    /// Whether the score was treated as autopilot.
+    ///
+    /// If so, [`pp_aim`](OsuPerformanceAttributes::pp_aim) and
+    /// [`pp_flashlight`](OsuPerformanceAttributes::pp_flashlight) are zero
+    /// so [`pp`](OsuPerformanceAttributes::pp) only consists of speed and accuracy.
+    pub autopilot: bool,
 }
 
 impl OsuPerformanceAttributes {
@@ -573,6 +1243,20 @@ impl OsuPerformanceAttributes {
         self.pp
     }
 
+    /// Return the portions of the final pp per skill.
+    #[inline]
+    pub fn breakdown(&self) -> PerformanceBreakdown {
+        PerformanceBreakdown {
+            pp: self.pp,
+            components: vec![
+                (PerformanceComponent::Aim, self.pp_aim),
+                (PerformanceComponent::Speed, self.pp_speed),
+                (PerformanceComponent::Accuracy, self.pp_acc),
+                (PerformanceComponent::Flashlight, self.pp_flashlight),
+            ],
+        }
+    }
+
     /// Return the maximum combo of the map.
     #[inline]
     pub fn max_combo(&self) -> usize {
@@ -586,3 +1270,714 @@ impl From<OsuPerformanceAttributes> for OsuDifficultyAttributes {
         attributes.difficulty
     }
 }
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::HitObject;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn disabled_stacking() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let stacked = OsuStars::new(&map).calculate();
+        let unstacked = OsuStars::new(&map).stacking(false).calculate();
+
+        assert_ne!(stacked.aim, unstacked.aim);
+        assert_eq!(stacked.max_combo, unstacked.max_combo);
+    }
+
+    #[test]
+    fn angle_bonus_rewards_angle_changes() {
+        fn circles_map(positions: impl Iterator<Item = (f32, f32)>) -> Beatmap {
+            let hit_objects = positions
+                .enumerate()
+                .map(|(i, (x, y))| HitObject {
+                    pos: Pos2 { x, y },
+                    start_time: 1000.0 + i as f64 * 150.0,
+                    kind: HitObjectKind::Circle,
+                })
+                .collect();
+
+            Beatmap {
+                version: 14,
+                ar: 9.0,
+                od: 8.0,
+                cs: 4.0,
+                hp: 5.0,
+                slider_mult: 1.4,
+                tick_rate: 1.0,
+                stack_leniency: 0.7,
+                hit_objects,
+                ..Default::default()
+            }
+        }
+
+        let corners = [
+            (100.0, 100.0),
+            (300.0, 100.0),
+            (300.0, 300.0),
+            (100.0, 300.0),
+        ];
+        let square = circles_map(corners.iter().copied().cycle().take(20));
+        let line = circles_map((0..20).map(|i| (50.0 + i as f32 * 20.0, 200.0)));
+
+        let square_attrs = OsuStars::new(&square).calculate();
+        let line_attrs = OsuStars::new(&line).calculate();
+
+        assert!(square_attrs.aim_angle_bonus > line_attrs.aim_angle_bonus);
+        assert!(line_attrs.aim_angle_bonus.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn flow_aim_ratio() {
+        fn circles_map(positions: impl Iterator<Item = (f32, f32)>) -> Beatmap {
+            let hit_objects = positions
+                .enumerate()
+                .map(|(i, (x, y))| HitObject {
+                    pos: Pos2 { x, y },
+                    start_time: 1000.0 + i as f64 * 150.0,
+                    kind: HitObjectKind::Circle,
+                })
+                .collect();
+
+            Beatmap {
+                version: 14,
+                ar: 9.0,
+                od: 8.0,
+                cs: 4.0,
+                hp: 5.0,
+                slider_mult: 1.4,
+                tick_rate: 1.0,
+                stack_leniency: 0.7,
+                hit_objects,
+                ..Default::default()
+            }
+        }
+
+        // Circular motion with wide angles between consecutive jumps
+        let flowy = circles_map((0..40).map(|i| {
+            let angle = i as f32 * std::f32::consts::PI / 8.0;
+
+            (256.0 + 150.0 * angle.cos(), 192.0 + 150.0 * angle.sin())
+        }));
+
+        // Back-and-forth jumps across the playfield
+        let snappy = circles_map((0..40).map(|i| {
+            if i % 2 == 0 {
+                (50.0, 192.0)
+            } else {
+                (450.0, 192.0)
+            }
+        }));
+
+        let flowy_attrs = OsuStars::new(&flowy).calculate();
+        let snappy_attrs = OsuStars::new(&snappy).calculate();
+
+        assert!(flowy_attrs.flow_aim_ratio > snappy_attrs.flow_aim_ratio);
+        assert!(flowy_attrs.flow_aim_ratio > 0.5);
+        assert!(snappy_attrs.flow_aim_ratio < 0.1);
+
+        for attrs in [flowy_attrs, snappy_attrs] {
+            assert!((0.0..=1.0).contains(&attrs.flow_aim_ratio));
+        }
+    }
+
+    #[test]
+    fn speed_spacing_factor() {
+        fn stream_map(spacing: f32) -> Beatmap {
+            let hit_objects = (0..120)
+                .map(|i| HitObject {
+                    pos: Pos2 {
+                        x: 100.0 + (i % 3) as f32 * spacing,
+                        y: 192.0,
+                    },
+                    start_time: 1000.0 + i as f64 * 75.0,
+                    kind: HitObjectKind::Circle,
+                })
+                .collect();
+
+            Beatmap {
+                version: 14,
+                ar: 9.0,
+                od: 8.0,
+                cs: 4.0,
+                hp: 5.0,
+                slider_mult: 1.4,
+                tick_rate: 1.0,
+                stack_leniency: 0.7,
+                hit_objects,
+                ..Default::default()
+            }
+        }
+
+        let tight = OsuStars::new(&stream_map(10.0)).calculate();
+        let spaced = OsuStars::new(&stream_map(110.0)).calculate();
+
+        assert!(spaced.speed_spacing_factor > tight.speed_spacing_factor);
+        assert!(spaced.speed > tight.speed);
+        assert!(tight.speed_spacing_factor >= 1.0 && tight.speed_spacing_factor < 1.01);
+        assert!(spaced.speed_spacing_factor <= 2.0);
+    }
+
+    #[test]
+    fn simultaneous_objects() {
+        // Pairs of objects on opposite sides of the playfield at the same time
+        let hit_objects = (0..40)
+            .map(|i| HitObject {
+                pos: Pos2 {
+                    x: if i % 2 == 0 { 50.0 } else { 450.0 },
+                    y: 100.0 + (i % 3) as f32 * 80.0,
+                },
+                start_time: 1000.0 + (i / 2) as f64 * 200.0,
+                kind: HitObjectKind::Circle,
+            })
+            .collect();
+
+        let map = Beatmap {
+            version: 14,
+            ar: 9.0,
+            od: 8.0,
+            cs: 4.0,
+            hp: 5.0,
+            slider_mult: 1.4,
+            tick_rate: 1.0,
+            stack_leniency: 0.7,
+            hit_objects,
+            ..Default::default()
+        };
+
+        // The same pattern with the pairs spaced by the minimum strain time
+        let mut spaced = map.clone();
+
+        for (i, h) in spaced.hit_objects.iter_mut().enumerate() {
+            h.start_time += (i % 2) as f64 * OsuDifficultyObject::MIN_DELTA_TIME as f64;
+        }
+
+        for &mods in [0, 64].iter() {
+            let attrs = OsuStars::new(&map).mods(mods).calculate();
+
+            assert!(attrs.has_simultaneous_objects);
+            assert!(attrs.stars.is_finite());
+            assert!(attrs.aim.is_finite());
+
+            let spaced_attrs = OsuStars::new(&spaced).mods(mods).calculate();
+            assert!(!spaced_attrs.has_simultaneous_objects);
+
+            // Strain times are floored at the minimum delta time so simultaneous
+            // objects are bounded by the difficulty of the spaced pattern
+            assert!(
+                attrs.stars <= spaced_attrs.stars,
+                "{} > {}",
+                attrs.stars,
+                spaced_attrs.stars
+            );
+        }
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        assert!(!OsuStars::new(&map).calculate().has_simultaneous_objects);
+    }
+
+    #[test]
+    fn flashlight_slider_factor() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let attrs = OsuStars::new(&map).mods(1024).calculate();
+
+        assert!(attrs.n_sliders > 0);
+        assert!(attrs.flashlight_slider_factor < 1.0);
+        assert!(attrs.flashlight_slider_factor > 0.0);
+
+        // Without FL the factor is not calculated
+        let attrs = OsuStars::new(&map).calculate();
+        assert_eq!(attrs.flashlight_slider_factor, 1.0);
+    }
+
+    #[test]
+    fn alternating_streams() {
+        // A burst of 40 circles with 50ms inbetween
+        let hit_objects = (0..40)
+            .map(|i| HitObject {
+                pos: Pos2 {
+                    x: 100.0 + i as f32 * 8.0,
+                    y: 192.0,
+                },
+                start_time: 1000.0 + i as f64 * 50.0,
+                kind: HitObjectKind::Circle,
+            })
+            .collect();
+
+        let map = Beatmap {
+            version: 14,
+            ar: 9.0,
+            od: 8.0,
+            cs: 4.0,
+            hp: 5.0,
+            slider_mult: 1.4,
+            tick_rate: 1.0,
+            stack_leniency: 0.7,
+            hit_objects,
+            ..Default::default()
+        };
+
+        let single_tap = OsuStars::new(&map).calculate();
+        let alternating = OsuStars::new(&map).alternating(true).calculate();
+
+        assert_eq!(
+            single_tap,
+            OsuStars::new(&map).alternating(false).calculate()
+        );
+        assert!(alternating.speed > single_tap.speed);
+        assert_eq!(alternating.aim, single_tap.aim);
+
+        let gradual = OsuGradualDifficultyAttributes::new(&map, 0)
+            .alternating(true)
+            .last();
+
+        assert_eq!(gradual, Some(alternating));
+    }
+
+    #[test]
+    fn spinner_spins() {
+        let spinner = |start_time: f64, end_time: f64| HitObject {
+            pos: Pos2 { x: 256.0, y: 192.0 },
+            start_time,
+            kind: HitObjectKind::Spinner { end_time },
+        };
+
+        let map = Beatmap {
+            od: 8.0,
+            hit_objects: vec![spinner(1000.0, 2000.0), spinner(3000.0, 8000.0)],
+            ..Default::default()
+        };
+
+        let spins = OsuStars::new(&map).spinner_spins();
+        assert_eq!(spins, vec![6, 32]);
+        assert!(spins[1] > spins[0]);
+
+        let hr_spins = OsuStars::new(&map).mods(16).spinner_spins();
+        assert!(hr_spins[1] > spins[1]);
+
+        assert_eq!(OsuStars::new(&map).mods(64).spinner_spins(), spins);
+        assert_eq!(
+            OsuStars::new(&map).passed_objects(1).spinner_spins(),
+            vec![6]
+        );
+    }
+
+    #[test]
+    fn cancelled_calculation() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let cancel = AtomicBool::new(false);
+
+        let attrs = OsuStars::new(&map).cancel_flag(&cancel).try_calculate();
+        assert_eq!(attrs, Ok(OsuStars::new(&map).calculate()));
+
+        // Repeat the map so that the calculation runs long enough for the
+        // flag to be set while objects are still being processed
+        let mut long_map = map.clone();
+        let offset = map.hit_objects.last().map_or(0.0, HitObject::end_time) + 1000.0;
+
+        for i in 1..100 {
+            long_map.hit_objects.extend(map.hit_objects.iter().map(|h| {
+                let mut h = h.to_owned();
+                h.start_time += i as f64 * offset;
+
+                if let HitObjectKind::Spinner { ref mut end_time } = h.kind {
+                    *end_time += i as f64 * offset;
+                }
+
+                h
+            }));
+        }
+
+        let attrs = thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(10));
+                cancel.store(true, Ordering::Relaxed);
+            });
+
+            OsuStars::new(&long_map)
+                .cancel_flag(&cancel)
+                .try_calculate()
+        });
+
+        assert_eq!(attrs, Err(Cancelled));
+
+        // The flag is ignored outside of `try_calculate`
+        let attrs = OsuStars::new(&map).cancel_flag(&cancel).calculate();
+        assert_eq!(attrs, OsuStars::new(&map).calculate());
+    }
+
+    #[test]
+    fn note_density_depends_on_approach_rate() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let total = |density: Vec<(f64, usize)>| -> usize { density.iter().map(|(_, n)| n).sum() };
+
+        let nomod = OsuStars::new(&map).note_density();
+        let ez = OsuStars::new(&map).mods(2).note_density();
+        let hr = OsuStars::new(&map).mods(16).note_density();
+
+        assert_eq!(nomod.len(), map.hit_objects.len());
+        assert!(nomod.iter().all(|(_, n)| *n >= 1));
+        assert!(total(ez.clone()) > total(nomod.clone()));
+        assert!(total(nomod) > total(hr));
+
+        let passed = OsuStars::new(&map)
+            .mods(2)
+            .passed_objects(100)
+            .note_density();
+        assert_eq!(passed[..], ez[..100]);
+    }
+
+    #[test]
+    fn hidden_reading_strains() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let nomod = OsuStars::new(&map).strains();
+        let hd = OsuStars::new(&map).mods(8).strains();
+
+        assert_eq!(nomod.hidden_reading.len(), nomod.len());
+        assert_eq!(nomod.hidden_reading, hd.flashlight);
+        assert_eq!(hd.hidden_reading, hd.flashlight);
+
+        // Sections with objects are harder to read under HD
+        let harder = nomod
+            .hidden_reading
+            .iter()
+            .zip(nomod.flashlight.iter())
+            .filter(|(hidden, flashlight)| hidden > flashlight)
+            .count();
+
+        assert!(harder > nomod.len() / 2);
+        assert_ne!(nomod.hidden_reading, nomod.aim);
+    }
+
+    #[test]
+    fn explicit_clock_rate_overrides_mods() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let map_attrs = map.attributes().mods(64).clock_rate(1.3).build();
+        assert_eq!(map_attrs.clock_rate, 1.3);
+
+        let dt_override = OsuStars::new(&map).mods(64).clock_rate(1.3).calculate();
+        let mut nomod_override = OsuStars::new(&map).clock_rate(1.3).calculate();
+        let dt = OsuStars::new(&map).mods(64).calculate();
+
+        // The recorded mods are checked in `attributes_record_mods`
+        nomod_override.mods = dt_override.mods;
+
+        assert_eq!(dt_override, nomod_override);
+        assert_ne!(dt_override, dt);
+
+        let pp_override = OsuPP::new(&map).mods(64).clock_rate(1.3).calculate();
+        assert_eq!(pp_override.difficulty, dt_override);
+    }
+
+    #[test]
+    fn attributes_record_mods() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        assert_eq!(OsuStars::new(&map).calculate().mods, 0);
+        assert_eq!(OsuStars::new(&map).mods(8 + 64).calculate().mods, 8 + 64);
+
+        // Mods are recorded as given, even if the clock rate is overridden
+        let attrs = OsuStars::new(&map).mods(512).clock_rate(1.3).calculate();
+        assert_eq!(attrs.mods, 512);
+    }
+
+    #[test]
+    fn stream_lengths() {
+        let mut start_time = 1000.0;
+        let mut hit_objects = Vec::new();
+
+        // Ten 4-note bursts, two 5-note bursts, and one triplet with 80ms inbetween notes
+        for len in [4, 4, 4, 4, 4, 5, 4, 4, 3, 4, 5, 4, 4] {
+            for _ in 0..len {
+                hit_objects.push(HitObject {
+                    pos: Pos2 { x: 256.0, y: 192.0 },
+                    start_time,
+                    kind: HitObjectKind::Circle,
+                });
+
+                start_time += 80.0;
+            }
+
+            start_time += 400.0;
+        }
+
+        let map = Beatmap {
+            hit_objects,
+            ..Default::default()
+        };
+
+        let histogram = OsuStars::new(&map).stream_lengths();
+        assert_eq!(histogram, vec![0, 0, 0, 1, 10, 2]);
+
+        let peak = (0..histogram.len()).max_by_key(|&len| histogram[len]);
+        assert_eq!(peak, Some(4));
+
+        // At half speed the notes are too far apart to be considered a stream
+        assert!(OsuStars::new(&map)
+            .clock_rate(0.5)
+            .stream_lengths()
+            .is_empty());
+    }
+
+    #[test]
+    fn aim_peak_variation() {
+        let jumps = |times: &[(f64, f32)]| Beatmap {
+            version: 14,
+            ar: 9.0,
+            od: 8.0,
+            cs: 4.0,
+            hp: 5.0,
+            slider_mult: 1.4,
+            tick_rate: 1.0,
+            stack_leniency: 0.7,
+            hit_objects: times
+                .iter()
+                .enumerate()
+                .map(|(i, &(start_time, spacing))| HitObject {
+                    pos: Pos2 {
+                        x: 256.0 + if i & 1 == 0 { spacing } else { -spacing },
+                        y: 192.0,
+                    },
+                    start_time,
+                    kind: HitObjectKind::Circle,
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        // Big jumps every 200ms for 20 seconds
+        let uniform: Vec<_> = (0..100).map(|i| (i as f64 * 200.0, 150.0)).collect();
+
+        // Small jumps every 500ms with a short burst of big jumps in the middle
+        let spiky: Vec<_> = (0..40)
+            .map(|i| (i as f64 * 500.0, 20.0))
+            .chain((0..10).map(|i| (20_100.0 + i as f64 * 200.0, 150.0)))
+            .chain((0..40).map(|i| (22_500.0 + i as f64 * 500.0, 20.0)))
+            .collect();
+
+        let uniform = OsuStars::new(&jumps(&uniform)).calculate();
+        let spiky = OsuStars::new(&jumps(&spiky)).calculate();
+
+        assert!(uniform.aim_peak_variation >= 0.0);
+        assert!(uniform.aim_peak_variation < spiky.aim_peak_variation);
+    }
+
+    #[test]
+    fn spinner_placeholder_strain() {
+        let circle = |start_time: f64| HitObject {
+            pos: Pos2 { x: 256.0, y: 192.0 },
+            start_time,
+            kind: HitObjectKind::Circle,
+        };
+
+        let hit_objects = (0..10)
+            .map(|i| circle(1000.0 + i as f64 * 200.0))
+            .chain(Some(HitObject {
+                pos: Pos2 { x: 256.0, y: 192.0 },
+                start_time: 3000.0,
+                kind: HitObjectKind::Spinner { end_time: 6000.0 },
+            }))
+            .chain((0..10).map(|i| circle(7000.0 + i as f64 * 200.0)))
+            .collect();
+
+        let map = Beatmap {
+            hit_objects,
+            ..Default::default()
+        };
+
+        // First section ends at 1200ms so 3000ms-6000ms are sections 5 to 12
+        let spinner_sections = 5..=12;
+
+        let default = OsuStars::new(&map).strains();
+        let placeholder = OsuStars::new(&map).spinner_strain(1000.0).strains();
+
+        assert!(default.speed[spinner_sections.clone()]
+            .iter()
+            .any(|&peak| peak < 1000.0));
+
+        for i in 0..placeholder.len() {
+            if spinner_sections.contains(&i) {
+                assert_eq!(placeholder.aim[i], 1000.0, "{}", i);
+                assert_eq!(placeholder.speed[i], 1000.0, "{}", i);
+            } else {
+                assert_eq!(placeholder.aim[i], default.aim[i], "{}", i);
+                assert_eq!(placeholder.speed[i], default.speed[i], "{}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn cursor_velocity() {
+        let circle = |x: f32, start_time: f64| HitObject {
+            pos: Pos2 { x, y: 192.0 },
+            start_time,
+            kind: HitObjectKind::Circle,
+        };
+
+        // 100px every 500ms, then 300px every 150ms, then stacked every 200ms
+        let slow =
+            (0..8).map(|i| circle(200.0 + (i % 2) as f32 * 100.0, 1000.0 + i as f64 * 500.0));
+        let fast =
+            (0..8).map(|i| circle(100.0 + (i % 2) as f32 * 300.0, 5000.0 + i as f64 * 150.0));
+        let stacked = (0..8).map(|i| circle(256.0, 7000.0 + i as f64 * 200.0));
+
+        let map = Beatmap {
+            version: 14,
+            ar: 9.0,
+            od: 8.0,
+            cs: 4.0,
+            hp: 5.0,
+            slider_mult: 1.4,
+            tick_rate: 1.0,
+            stack_leniency: 0.7,
+            hit_objects: slow.chain(fast).chain(stacked).collect(),
+            ..Default::default()
+        };
+
+        let velocities = OsuStars::new(&map).cursor_velocity();
+
+        let max_velocity = |from: f64, to: f64| {
+            velocities
+                .iter()
+                .filter(|(start, _)| *start >= from && start + SECTION_LEN <= to)
+                .map(|(_, velocity)| *velocity)
+                .fold(0.0, f64::max)
+        };
+
+        let slow = max_velocity(1000.0, 4500.0);
+        let fast = max_velocity(5000.0, 6050.0);
+        let stacked = max_velocity(7200.0, 8400.0);
+
+        assert!((slow - 0.2).abs() < 1e-6, "{}", slow);
+        assert!((fast - 2.0).abs() < 1e-6, "{}", fast);
+        assert!(stacked < 0.05, "{}", stacked);
+
+        assert_eq!(velocities[0].0, 1200.0);
+        assert!(velocities
+            .windows(2)
+            .all(|w| w[1].0 - w[0].0 == SECTION_LEN));
+
+        // Rate changes are accounted for
+        let dt = OsuStars::new(&map).mods(u32::DT).cursor_velocity();
+        let dt_fast = dt.iter().map(|(_, velocity)| *velocity).fold(0.0, f64::max);
+        assert!((dt_fast - 3.0).abs() < 1e-6, "{}", dt_fast);
+    }
+
+    #[test]
+    fn fast_sliders() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        for &mods in [0, 2, 16, 64, 8 + 16 + 64 + 1024].iter() {
+            let exact = OsuStars::new(&map).mods(mods).calculate();
+            let fast = OsuStars::new(&map)
+                .mods(mods)
+                .fast_sliders(true)
+                .calculate();
+
+            let deviation = (fast.stars - exact.stars).abs() / exact.stars;
+            assert!(deviation < 0.02, "{}: {}", mods, deviation);
+            assert_ne!(fast.stars, exact.stars, "{}", mods);
+
+            assert_eq!(fast.max_combo, exact.max_combo, "{}", mods);
+            assert_eq!(fast.n_sliders, exact.n_sliders, "{}", mods);
+        }
+    }
+
+    #[test]
+    fn absurd_coordinates() {
+        let hit_objects = (0..40)
+            .map(|i| HitObject {
+                pos: Pos2 {
+                    x: 100.0 + (i % 4) as f32 * 100.0,
+                    y: 200.0,
+                },
+                start_time: 1000.0 + i as f64 * 250.0,
+                kind: HitObjectKind::Circle,
+            })
+            .collect();
+
+        let map = Beatmap {
+            version: 14,
+            ar: 9.0,
+            od: 8.0,
+            cs: 4.0,
+            hp: 5.0,
+            slider_mult: 1.4,
+            tick_rate: 1.0,
+            stack_leniency: 0.7,
+            hit_objects,
+            ..Default::default()
+        };
+
+        let with_pos = |pos: Pos2| {
+            let mut map = map.clone();
+            map.hit_objects[20].pos = pos;
+
+            OsuStars::new(&map).calculate()
+        };
+
+        let absurd = with_pos(Pos2 { x: 1e30, y: -1e30 });
+        let clamped = with_pos(Pos2 {
+            x: 2.0 * PLAYFIELD_BASE_SIZE.x,
+            y: -PLAYFIELD_BASE_SIZE.y,
+        });
+
+        assert!(absurd.stars.is_finite());
+        assert!(absurd.aim.is_finite());
+        assert_eq!(absurd.stars, clamped.stars);
+        assert!(absurd.stars > OsuStars::new(&map).calculate().stars);
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let mut far_slider = map.clone();
+        let idx = far_slider
+            .hit_objects
+            .iter()
+            .position(HitObject::is_slider)
+            .unwrap();
+        far_slider.hit_objects[idx].pos = Pos2 {
+            x: f32::MAX,
+            y: f32::MAX,
+        };
+
+        let attrs = OsuStars::new(&far_slider).calculate();
+        assert!(attrs.stars.is_finite());
+        assert!(attrs.stars < 2.0 * OsuStars::new(&map).calculate().stars);
+    }
+
+    #[test]
+    fn object_strains() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let objects = OsuStars::new(&map).mods(64).object_strains();
+        let peaks = OsuStars::new(&map).mods(64).strains();
+
+        assert_eq!(objects.len(), map.hit_objects.len());
+        assert_eq!(objects.aim.len(), objects.len());
+        assert_eq!(objects.flashlight.len(), objects.len());
+        assert_eq!(objects.speed[0], 0.0);
+        assert_eq!(objects.times[1], map.hit_objects[1].start_time);
+
+        // Section peaks are the highest object strain or a decayed strain of a previous object
+        let max = |strains: &[f64]| strains.iter().copied().fold(0.0, f64::max);
+
+        assert!((max(&objects.aim) - max(&peaks.aim)).abs() < 1e-10);
+        assert!((max(&objects.speed) - max(&peaks.speed)).abs() < 1e-10);
+
+        let passed = OsuStars::new(&map)
+            .mods(64)
+            .passed_objects(10)
+            .object_strains();
+        assert_eq!(passed.len(), 10);
+
+        // The last passed object lacks its successor which some evaluators take into account
+        assert_eq!(passed.aim[..9], objects.aim[..9]);
+    }
+}
@@ -11,6 +11,38 @@ use crate::{
 const LEGACY_LAST_TICK_OFFSET: f64 = 36.0;
 const BASE_SCORING_DISTANCE: f64 = 100.0;
 
+/// Positions are clamped to at most one playfield size beyond
+/// each edge so that absurd coordinates can't produce infinite distances.
+const MIN_POS: Pos2 = Pos2 {
+    x: -PLAYFIELD_BASE_SIZE.x,
+    y: -PLAYFIELD_BASE_SIZE.y,
+};
+
+const MAX_POS: Pos2 = Pos2 {
+    x: 2.0 * PLAYFIELD_BASE_SIZE.x,
+    y: 2.0 * PLAYFIELD_BASE_SIZE.y,
+};
+
+fn clamp_pos(pos: Pos2) -> Pos2 {
+    Pos2 {
+        x: pos.x.clamp(MIN_POS.x, MAX_POS.x),
+        y: pos.y.clamp(MIN_POS.y, MAX_POS.y),
+    }
+}
+
+/// Clamps the absolute position of `offset` relative to `pos`
+/// while keeping in-bounds offsets unaltered.
+fn clamp_offset(pos: Pos2, offset: Pos2) -> Pos2 {
+    let absolute = pos + offset;
+    let clamped = clamp_pos(absolute);
+
+    if clamped == absolute {
+        offset
+    } else {
+        clamped - pos
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct OsuObject {
     pos: Pos2,
@@ -89,6 +121,7 @@ pub(crate) struct ObjectParameters<'a> {
     pub(crate) attrs: &'a mut OsuDifficultyAttributes,
     pub(crate) ticks: Vec<(Pos2, f64)>,
     pub(crate) curve_bufs: CurveBuffers,
+    pub(crate) fast_sliders: bool,
 }
 
 impl OsuObject {
@@ -98,10 +131,11 @@ impl OsuObject {
             attrs,
             ticks,
             curve_bufs,
+            fast_sliders,
         } = params;
 
         attrs.max_combo += 1; // hitcircle, slider head, or spinner
-        let pos = h.pos;
+        let pos = clamp_pos(h.pos);
 
         match &h.kind {
             HitObjectKind::Circle => {
@@ -148,7 +182,11 @@ impl OsuObject {
                 let span_count = (*repeats + 1) as f64;
 
                 // Build the curve w.r.t. the control points
-                let curve = Curve::new(control_points, *pixel_len, curve_bufs);
+                let curve = if *fast_sliders {
+                    Curve::linear(control_points, *pixel_len, curve_bufs)
+                } else {
+                    Curve::new(control_points, *pixel_len, curve_bufs)
+                };
 
                 let end_time = h.start_time + span_count * curve.dist() / vel;
                 let total_duration = end_time - h.start_time;
@@ -177,7 +215,7 @@ impl OsuObject {
                         let progress = curr_dist / len;
 
                         let curr_time = h.start_time + progress * span_duration;
-                        let curr_pos = h.pos + curve.position_at(progress);
+                        let curr_pos = clamp_pos(pos + curve.position_at(progress));
 
                         let tick = NestedObject {
                             pos: curr_pos,
@@ -198,7 +236,7 @@ impl OsuObject {
 
                         // Repeat point
                         let curr_time = h.start_time + span_duration * span_idx_f64;
-                        let curr_pos = h.pos + curve.position_at(progress);
+                        let curr_pos = clamp_pos(pos + curve.position_at(progress));
 
                         let repeat = NestedObject {
                             pos: curr_pos,
@@ -257,7 +295,7 @@ impl OsuObject {
                     .max(final_span_start_time + span_duration - LEGACY_LAST_TICK_OFFSET);
 
                 let progress = (*repeats % 2 == 0) as u8 as f64;
-                let end_pos = curve.position_at(progress);
+                let end_pos = clamp_offset(pos, curve.position_at(progress));
 
                 // * we need to use the LegacyLastTick here for compatibility reasons (difficulty).
                 // * it is *okay* to use this because the TailCircle is not used for any meaningful purpose in gameplay.
@@ -301,7 +339,7 @@ impl OsuObject {
                 // * temporary lazy end position until a real result can be derived.
                 // The position is added after the stacking for the correct order of
                 // floating point operations.
-                let lazy_end_pos = curve.position_at(end_time_min);
+                let lazy_end_pos = clamp_offset(pos, curve.position_at(end_time_min));
 
                 let slider = OsuSlider {
                     end_time,
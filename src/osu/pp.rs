@@ -1,9 +1,10 @@
 use super::{
-    OsuDifficultyAttributes, OsuPerformanceAttributes, OsuScoreState, PERFORMANCE_BASE_MULTIPLIER,
+    OsuDifficultyAttributes, OsuPerformanceAttributes, OsuScoreState, RelaxPerformanceAttributes,
+    RelaxVariant, PERFORMANCE_BASE_MULTIPLIER,
 };
 use crate::{
-    AnyPP, Beatmap, DifficultyAttributes, GameMode, HitResultPriority, Mods, OsuStars,
-    PerformanceAttributes,
+    osu_2019::OsuPP as Osu2019PP, pp::balance_hitresults, AnyPP, Beatmap, DifficultyAttributes,
+    GameMode, HitResultPriority, Mods, ModsMismatch, OsuStars, PerformanceAttributes,
 };
 
 /// Performance calculator on osu!standard maps.
@@ -43,6 +44,7 @@ pub struct OsuPP<'map> {
     pub(crate) mods: u32,
     pub(crate) acc: Option<f64>,
     pub(crate) combo: Option<usize>,
+    pub(crate) combo_cap: Option<usize>,
 
     pub(crate) n300: Option<usize>,
     pub(crate) n100: Option<usize>,
@@ -51,6 +53,14 @@ pub struct OsuPP<'map> {
     pub(crate) passed_objects: Option<usize>,
     pub(crate) clock_rate: Option<f64>,
     pub(crate) hitresult_priority: Option<HitResultPriority>,
+    pub(crate) relax: Option<bool>,
+    pub(crate) autopilot: Option<bool>,
+    pub(crate) score_v2: Option<bool>,
+    pub(crate) slider_head_accuracy: Option<bool>,
+    pub(crate) large_tick_hits: Option<usize>,
+    pub(crate) slider_end_hits: Option<usize>,
+    pub(crate) hidden_ar_scaling: bool,
+    pub(crate) relax_variant: RelaxVariant,
 }
 
 impl<'map> OsuPP<'map> {
@@ -63,6 +73,7 @@ impl<'map> OsuPP<'map> {
             mods: 0,
             acc: None,
             combo: None,
+            combo_cap: None,
 
             n300: None,
             n100: None,
@@ -71,6 +82,14 @@ impl<'map> OsuPP<'map> {
             passed_objects: None,
             clock_rate: None,
             hitresult_priority: None,
+            relax: None,
+            autopilot: None,
+            score_v2: None,
+            slider_head_accuracy: None,
+            large_tick_hits: None,
+            slider_end_hits: None,
+            hidden_ar_scaling: false,
+            relax_variant: RelaxVariant::default(),
         }
     }
 
@@ -115,6 +134,19 @@ impl<'map> OsuPP<'map> {
         self
     }
 
+    /// Cap the maximum achievable combo that the play's combo is compared against,
+    /// e.g. when the play started from a checkpoint after the map's intro.
+    ///
+    /// Only the combo scaling of the aim, speed, and flashlight values considers the cap;
+    /// the estimated amount of slider breaks is still based on the map's max combo.
+    /// If no combo is specified, the cap is used as the play's combo.
+    #[inline]
+    pub fn combo_cap(mut self, combo_cap: usize) -> Self {
+        self.combo_cap = Some(combo_cap);
+
+        self
+    }
+
     /// Specify how hitresults should be generated.
     ///
     /// Defauls to [`HitResultPriority::BestCase`].
@@ -179,7 +211,112 @@ impl<'map> OsuPP<'map> {
         self
     }
 
+    /// Specify whether the relax pp algorithm should be used regardless of the mods.
+    ///
+    /// If not specified, it will be used if the mods contain RX.
+    /// Unless difficulty attributes are provided, the difficulty calculation is affected too.
+    #[inline]
+    pub fn relax(mut self, relax: bool) -> Self {
+        self.relax = Some(relax);
+
+        self
+    }
+
+    /// Specify which pp algorithm [`calculate_relax`](OsuPP::calculate_relax) uses.
+    ///
+    /// Defaults to [`RelaxVariant::Standard`]. Does not affect [`calculate`](OsuPP::calculate).
+    #[inline]
+    pub fn relax_variant(mut self, relax_variant: RelaxVariant) -> Self {
+        self.relax_variant = relax_variant;
+
+        self
+    }
+
+    /// Specify whether the score should be treated as autopilot regardless of the mods.
+    ///
+    /// There is no dedicated autopilot pp model since no reference formula exists
+    /// to source its multipliers from. Autopilot only removes the aim and flashlight
+    /// values so that the pp consist of speed and accuracy.
+    ///
+    /// If not specified, it will be used if the mods contain AP.
+    /// Unless difficulty attributes are provided, the difficulty calculation is affected too.
+    #[inline]
+    pub fn autopilot(mut self, autopilot: bool) -> Self {
+        self.autopilot = Some(autopilot);
+
+        self
+    }
+
+    /// Specify the score version, i.e. `1` for ScoreV1 and `2` for ScoreV2.
+    ///
+    /// With ScoreV2, slider heads are judged on their timing so they are included
+    /// in the accuracy value alongside circles. Additionally, slider ticks and ends
+    /// are weighted into the accuracy and dropping them counts towards the misses,
+    /// see [`large_tick_hits`](OsuPP::large_tick_hits) and
+    /// [`slider_end_hits`](OsuPP::slider_end_hits).
+    /// If not specified, ScoreV2 is used if the mods contain it.
+    #[inline]
+    pub fn score_version(mut self, version: u8) -> Self {
+        self.score_v2 = Some(version >= 2);
+
+        self
+    }
+
+    /// Specify whether slider heads are judged on their timing and hence
+    /// included in the accuracy value alongside circles.
+    ///
+    /// This also enables the slider tick and end handling of ScoreV2.
+    ///
+    /// Useful for score importers that know how the client judged slider heads,
+    /// e.g. if the score was set on osu!lazer without the classic mod but the
+    /// mods don't reflect that. If not specified, slider heads are included
+    /// for ScoreV2, see [`score_version`](OsuPP::score_version).
+    #[inline]
+    pub fn slider_head_accuracy(mut self, slider_head_accuracy: bool) -> Self {
+        self.slider_head_accuracy = Some(slider_head_accuracy);
+
+        self
+    }
+
+    /// Specify the amount of hit slider ticks and repeats.
+    ///
+    /// Only relevant for ScoreV2, see [`score_version`](OsuPP::score_version).
+    /// If not specified, all slider ticks and repeats are considered hit.
+    #[inline]
+    pub fn large_tick_hits(mut self, large_tick_hits: usize) -> Self {
+        self.large_tick_hits = Some(large_tick_hits);
+
+        self
+    }
+
+    /// Specify the amount of hit slider ends.
+    ///
+    /// Only relevant for ScoreV2, see [`score_version`](OsuPP::score_version).
+    /// If not specified, all slider ends are considered hit.
+    #[inline]
+    pub fn slider_end_hits(mut self, slider_end_hits: usize) -> Self {
+        self.slider_end_hits = Some(slider_end_hits);
+
+        self
+    }
+
+    /// Specify whether the HD bonus of the accuracy value should scale with AR.
+    ///
+    /// Without approach circles, the timing of lower AR is harder to read so the bonus
+    /// grows for lower AR and shrinks for higher AR, matching the regular 8% at AR9.
+    /// Defaults to `false`, i.e. the regular flat bonus.
+    #[inline]
+    pub fn hidden_ar_scaling(mut self, hidden_ar_scaling: bool) -> Self {
+        self.hidden_ar_scaling = hidden_ar_scaling;
+
+        self
+    }
+
     /// Provide parameters through an [`OsuScoreState`].
+    ///
+    /// Values can be overridden by calling the individual setters afterwards,
+    /// e.g. `.state(state).combo(max_combo)` to calculate an if-FC
+    /// with the same hitresults.
     #[inline]
     pub fn state(mut self, state: OsuScoreState) -> Self {
         let OsuScoreState {
@@ -209,8 +346,30 @@ impl<'map> OsuPP<'map> {
     }
 
     fn generate_hitresults(&self, max_combo: usize) -> OsuScoreState {
+        match self.hitresult_priority.unwrap_or_default() {
+            HitResultPriority::BestCase => self.generate_hitresults_with(max_combo, true),
+            HitResultPriority::WorstCase => self.generate_hitresults_with(max_combo, false),
+            HitResultPriority::Balanced => {
+                let best = self.generate_hitresults_with(max_combo, true);
+                let worst = self.generate_hitresults_with(max_combo, false);
+
+                let [n300, n100, n50] = balance_hitresults(
+                    [best.n300, best.n100, best.n50],
+                    [worst.n300, worst.n100, worst.n50],
+                );
+
+                OsuScoreState {
+                    n300,
+                    n100,
+                    n50,
+                    ..best
+                }
+            }
+        }
+    }
+
+    fn generate_hitresults_with(&self, max_combo: usize, best_case: bool) -> OsuScoreState {
         let n_objects = self.passed_objects.unwrap_or(self.map.hit_objects.len());
-        let priority = self.hitresult_priority.unwrap_or_default();
 
         let mut n300 = self.n300.unwrap_or(0);
         let mut n100 = self.n100.unwrap_or(0);
@@ -224,9 +383,10 @@ impl<'map> OsuPP<'map> {
                 (Some(_), Some(_), Some(_)) => {
                     let remaining = n_objects.saturating_sub(n300 + n100 + n50 + n_misses);
 
-                    match priority {
-                        HitResultPriority::BestCase => n300 += remaining,
-                        HitResultPriority::WorstCase => n50 += remaining,
+                    if best_case {
+                        n300 += remaining;
+                    } else {
+                        n50 += remaining;
                     }
                 }
                 (Some(_), Some(_), None) => n50 = n_objects.saturating_sub(n300 + n100 + n_misses),
@@ -301,7 +461,7 @@ impl<'map> OsuPP<'map> {
                     n100 = delta % 5;
                     n50 = n_objects.saturating_sub(n300 + n100 + n_misses);
 
-                    if let HitResultPriority::BestCase = priority {
+                    if best_case {
                         // Shift n50 to n100 by sacrificing n300
                         let n = n300.min(n50 / 4);
                         n300 -= n;
@@ -313,28 +473,25 @@ impl<'map> OsuPP<'map> {
         } else {
             let remaining = n_objects.saturating_sub(n300 + n100 + n50 + n_misses);
 
-            match priority {
-                HitResultPriority::BestCase => {
-                    if self.n300.is_none() {
-                        n300 = remaining;
-                    } else if self.n100.is_none() {
-                        n100 = remaining;
-                    } else if self.n50.is_none() {
-                        n50 = remaining;
-                    } else {
-                        n300 += remaining;
-                    }
+            if best_case {
+                if self.n300.is_none() {
+                    n300 = remaining;
+                } else if self.n100.is_none() {
+                    n100 = remaining;
+                } else if self.n50.is_none() {
+                    n50 = remaining;
+                } else {
+                    n300 += remaining;
                 }
-                HitResultPriority::WorstCase => {
-                    if self.n50.is_none() {
-                        n50 = remaining;
-                    } else if self.n100.is_none() {
-                        n100 = remaining;
-                    } else if self.n300.is_none() {
-                        n300 = remaining;
-                    } else {
-                        n50 += remaining;
-                    }
+            } else {
+                if self.n50.is_none() {
+                    n50 = remaining;
+                } else if self.n100.is_none() {
+                    n100 = remaining;
+                } else if self.n300.is_none() {
+                    n300 = remaining;
+                } else {
+                    n50 += remaining;
                 }
             }
         }
@@ -348,31 +505,148 @@ impl<'map> OsuPP<'map> {
         }
     }
 
-    /// Calculate all performance related values, including pp and stars.
-    pub fn calculate(mut self) -> OsuPerformanceAttributes {
-        let attrs = self.attributes.take().unwrap_or_else(|| {
-            let mut calculator = OsuStars::new(self.map).mods(self.mods);
+    /// The mods with RX, AP, and ScoreV2 adjusted to the forced algorithms.
+    fn pp_mods(&self) -> u32 {
+        let mut mods = self.mods;
+
+        let forced_mods = [
+            (self.relax, u32::RX),
+            (self.autopilot, u32::AP),
+            (self.score_v2, u32::V2),
+        ];
+
+        for (forced, bits) in forced_mods.iter().copied() {
+            match forced {
+                Some(true) => mods |= bits,
+                Some(false) => mods &= !bits,
+                None => {}
+            }
+        }
+
+        mods
+    }
 
-            if let Some(passed_objects) = self.passed_objects {
-                calculator = calculator.passed_objects(passed_objects);
+    /// Same as [`OsuPP::calculate`] but returns an error if the attributes
+    /// provided through [`OsuPP::attributes`] were calculated with different mods.
+    pub fn try_calculate(self) -> Result<OsuPerformanceAttributes, ModsMismatch> {
+        if let Some(ref attrs) = self.attributes {
+            ModsMismatch::check(attrs.mods, self.pp_mods(), GameMode::Osu)?;
+        }
+
+        Ok(self.calculate())
+    }
+
+    /// Calculate the performance of a relax score with the algorithm specified
+    /// through [`relax_variant`](OsuPP::relax_variant).
+    ///
+    /// RX is applied regardless of the mods. For [`RelaxVariant::Akatsuki`],
+    /// provided attributes and a custom clock rate are ignored since the
+    /// 2019 algorithm calculates its own difficulty based on the mods.
+    pub fn calculate_relax(self) -> RelaxPerformanceAttributes {
+        match self.relax_variant {
+            RelaxVariant::Standard => {
+                RelaxPerformanceAttributes::Standard(self.relax(true).calculate())
             }
+            RelaxVariant::Akatsuki => {
+                let mods = (self.pp_mods() | u32::RX) & !u32::AP;
+
+                let mut calc = Osu2019PP::new(self.map)
+                    .mods(mods)
+                    .misses(self.n_misses.unwrap_or(0));
+
+                if let Some(passed_objects) = self.passed_objects {
+                    calc = calc.passed_objects(passed_objects);
+                }
+
+                if let Some(combo) = self.combo {
+                    calc = calc.combo(combo);
+                }
 
-            if let Some(clock_rate) = self.clock_rate {
-                calculator = calculator.clock_rate(clock_rate);
+                if let Some(n300) = self.n300 {
+                    calc = calc.n300(n300);
+                }
+
+                if let Some(n100) = self.n100 {
+                    calc = calc.n100(n100);
+                }
+
+                if let Some(n50) = self.n50 {
+                    calc = calc.n50(n50);
+                }
+
+                // Accuracy has to be set last
+                if let Some(acc) = self.acc {
+                    calc = calc.accuracy((acc * 100.0) as f32);
+                }
+
+                RelaxPerformanceAttributes::Akatsuki(calc.calculate())
             }
+        }
+    }
 
-            calculator.calculate()
-        });
+    /// Generate the hitresults that [`calculate`](OsuPP::calculate) would use.
+    ///
+    /// Hitresults that were not specified are generated to match the accuracy,
+    /// if given, and distributed according to the [`HitResultPriority`].
+    /// If no attributes were provided, they're calculated and kept
+    /// so that a subsequent calculation can re-use them.
+    pub fn generate_state(&mut self) -> OsuScoreState {
+        if self.attributes.is_none() {
+            self.attributes = Some(self.difficulty());
+        }
+
+        let max_combo = self.attributes.as_ref().map_or(0, |attrs| attrs.max_combo);
+        let max_combo = self.combo_cap.map_or(max_combo, |cap| cap.min(max_combo));
+
+        self.generate_hitresults(max_combo)
+    }
+
+    fn difficulty(&self) -> OsuDifficultyAttributes {
+        let mut calculator = OsuStars::new(self.map).mods(self.pp_mods());
+
+        if let Some(passed_objects) = self.passed_objects {
+            calculator = calculator.passed_objects(passed_objects);
+        }
+
+        if let Some(clock_rate) = self.clock_rate {
+            calculator = calculator.clock_rate(clock_rate);
+        }
+
+        calculator.calculate()
+    }
+
+    /// Calculate all performance related values, including pp and stars.
+    pub fn calculate(mut self) -> OsuPerformanceAttributes {
+        let mods = self.pp_mods();
+        let attrs = self.attributes.take().unwrap_or_else(|| self.difficulty());
+
+        let max_combo = self
+            .combo_cap
+            .map_or(attrs.max_combo, |cap| cap.min(attrs.max_combo));
+
+        let state = self.generate_hitresults(max_combo);
+
+        let slider_hits = self
+            .slider_head_accuracy
+            .unwrap_or_else(|| mods.v2())
+            .then(|| SliderHits::new(&attrs, self.large_tick_hits, self.slider_end_hits));
 
-        let state = self.generate_hitresults(attrs.max_combo);
-        let effective_miss_count = calculate_effective_misses(&attrs, &state);
+        let effective_miss_count = calculate_effective_misses(&attrs, &state, slider_hits.as_ref());
+
+        let acc = match slider_hits {
+            Some(ref slider_hits) => slider_hits.accuracy(&state),
+            None => state.accuracy(),
+        };
 
         let inner = OsuPpInner {
             attrs,
-            mods: self.mods,
-            acc: state.accuracy(),
+            mods,
+            slider_hits,
+            acc,
             state,
             effective_miss_count,
+            max_combo,
+            hidden_ar_scaling: self.hidden_ar_scaling,
         };
 
         inner.calculate()
@@ -382,9 +656,13 @@ impl<'map> OsuPP<'map> {
 struct OsuPpInner {
     attrs: OsuDifficultyAttributes,
     mods: u32,
+    // Only available with ScoreV2 slider judgements
+    slider_hits: Option<SliderHits>,
     acc: f64,
     state: OsuScoreState,
     effective_miss_count: f64,
+    max_combo: usize,
+    hidden_ar_scaling: bool,
 }
 
 impl OsuPpInner {
@@ -394,6 +672,8 @@ impl OsuPpInner {
         if total_hits == 0 {
             return OsuPerformanceAttributes {
                 difficulty: self.attrs,
+                combo: self.state.max_combo,
+                autopilot: self.mods.ap(),
                 ..Default::default()
             };
         }
@@ -432,17 +712,32 @@ impl OsuPpInner {
                 .min(total_hits);
         }
 
-        let aim_value = self.compute_aim_value();
-        let speed_value = self.compute_speed_value();
+        let len_bonus = self.length_bonus();
+        let aim_value = self.compute_aim_value(len_bonus);
+        let speed_value = self.compute_speed_value(len_bonus);
         let acc_value = self.compute_accuracy_value();
         let flashlight_value = self.compute_flashlight_value();
 
-        let pp = (aim_value.powf(1.1)
-            + speed_value.powf(1.1)
-            + acc_value.powf(1.1)
-            + flashlight_value.powf(1.1))
-        .powf(1.0 / 1.1)
-            * multiplier;
+        let pp =
+            Self::combine_values(aim_value, speed_value, acc_value, flashlight_value) * multiplier;
+
+        let pp_reading = if self.mods.hd() || self.mods.fl() {
+            let mods = self.mods;
+            self.mods &= !(u32::HD | u32::FL);
+
+            let pp_without_reading = Self::combine_values(
+                self.compute_aim_value(len_bonus),
+                self.compute_speed_value(len_bonus),
+                self.compute_accuracy_value(),
+                0.0,
+            ) * multiplier;
+
+            self.mods = mods;
+
+            (pp - pp_without_reading).max(0.0)
+        } else {
+            0.0
+        };
 
         OsuPerformanceAttributes {
             difficulty: self.attrs,
@@ -450,12 +745,29 @@ impl OsuPpInner {
             pp_aim: aim_value,
             pp_flashlight: flashlight_value,
             pp_speed: speed_value,
+            pp_reading,
             pp,
+            length_bonus: len_bonus,
             effective_miss_count: self.effective_miss_count,
+            accuracy: self.acc,
+            combo: self.state.max_combo,
+            autopilot: self.mods.ap(),
         }
     }
 
-    fn compute_aim_value(&self) -> f64 {
+    fn combine_values(aim: f64, speed: f64, acc: f64, flashlight: f64) -> f64 {
+        (aim.powf(1.1) + speed.powf(1.1) + acc.powf(1.1) + flashlight.powf(1.1)).powf(1.0 / 1.1)
+    }
+
+    /// Bonus multiplier for the aim and speed values of longer maps.
+    fn length_bonus(&self) -> f64 {
+        let total_hits = self.total_hits();
+
+        0.95 + 0.4 * (total_hits / 2000.0).min(1.0)
+            + (total_hits > 2000.0) as u8 as f64 * (total_hits / 2000.0).log10() * 0.5
+    }
+
+    fn compute_aim_value(&self, len_bonus: f64) -> f64 {
         if self.mods.ap() {
             return 0.0;
         }
@@ -464,10 +776,6 @@ impl OsuPpInner {
 
         let total_hits = self.total_hits();
 
-        let len_bonus = 0.95
-            + 0.4 * (total_hits / 2000.0).min(1.0)
-            + (total_hits > 2000.0) as u8 as f64 * (total_hits / 2000.0).log10() * 0.5;
-
         aim_value *= len_bonus;
 
         // * Penalize misses by assessing # of misses relative to the total # of objects.
@@ -502,10 +810,16 @@ impl OsuPpInner {
         let estimate_diff_sliders = self.attrs.n_sliders as f64 * 0.15;
 
         if self.attrs.n_sliders > 0 {
-            let estimate_slider_ends_dropped =
-                ((self.state.n100 + self.state.n50 + self.state.n_misses)
-                    .min(self.attrs.max_combo - self.state.max_combo) as f64)
-                    .clamp(0.0, estimate_diff_sliders);
+            let estimate_slider_ends_dropped = match self.slider_hits {
+                // With ScoreV2 the dropped slider ends and ticks are known
+                Some(ref slider_hits) => {
+                    (slider_hits.slider_ends_dropped() + slider_hits.large_tick_misses()) as f64
+                }
+                None => (self.state.n100 + self.state.n50 + self.state.n_misses)
+                    .min(self.attrs.max_combo - self.state.max_combo)
+                    as f64,
+            }
+            .clamp(0.0, estimate_diff_sliders);
             let slider_nerf_factor = (1.0 - self.attrs.slider_factor)
                 * (1.0 - estimate_slider_ends_dropped / estimate_diff_sliders).powi(3)
                 + self.attrs.slider_factor;
@@ -520,7 +834,7 @@ impl OsuPpInner {
         aim_value
     }
 
-    fn compute_speed_value(&self) -> f64 {
+    fn compute_speed_value(&self, len_bonus: f64) -> f64 {
         if self.mods.rx() {
             return 0.0;
         }
@@ -530,10 +844,6 @@ impl OsuPpInner {
 
         let total_hits = self.total_hits();
 
-        let len_bonus = 0.95
-            + 0.4 * (total_hits / 2000.0).min(1.0)
-            + (total_hits > 2000.0) as u8 as f64 * (total_hits / 2000.0).log10() * 0.5;
-
         speed_value *= len_bonus;
 
         // * Penalize misses by assessing # of misses relative to the total # of objects.
@@ -600,7 +910,12 @@ impl OsuPpInner {
 
         // * This percentage only considers HitCircles of any value - in this part
         // * of the calculation we focus on hitting the timing hit window.
-        let amount_hit_objects_with_acc = self.attrs.n_circles;
+        let mut amount_hit_objects_with_acc = self.attrs.n_circles;
+
+        // ScoreV2 judges slider heads on their timing too
+        if self.slider_hits.is_some() {
+            amount_hit_objects_with_acc += self.attrs.n_sliders;
+        }
 
         let better_acc_percentage = if amount_hit_objects_with_acc > 0 {
             let sub = self.state.total_hits() - amount_hit_objects_with_acc;
@@ -627,7 +942,13 @@ impl OsuPpInner {
 
         // * Increasing the accuracy value by object count for Blinds isn't ideal, so the minimum buff is given.
         if self.mods.hd() {
-            acc_value *= 1.08;
+            if self.hidden_ar_scaling {
+                // Without approach circles the timing of lower AR is harder to read.
+                // At AR9 this matches the flat bonus of 8%.
+                acc_value *= 1.0 + 0.08 * ((12.0 - self.attrs.ar) / 3.0).max(0.0);
+            } else {
+                acc_value *= 1.08;
+            }
         }
 
         if self.mods.fl() {
@@ -638,7 +959,7 @@ impl OsuPpInner {
     }
 
     fn compute_flashlight_value(&self) -> f64 {
-        if !self.mods.fl() {
+        if !self.mods.fl() || self.mods.ap() {
             return 0.0;
         }
 
@@ -669,11 +990,10 @@ impl OsuPpInner {
     }
 
     fn get_combo_scaling_factor(&self) -> f64 {
-        if self.attrs.max_combo == 0 {
+        if self.max_combo == 0 {
             1.0
         } else {
-            ((self.state.max_combo as f64).powf(0.8) / (self.attrs.max_combo as f64).powf(0.8))
-                .min(1.0)
+            ((self.state.max_combo as f64).powf(0.8) / (self.max_combo as f64).powf(0.8)).min(1.0)
         }
     }
 
@@ -682,12 +1002,21 @@ impl OsuPpInner {
     }
 }
 
-fn calculate_effective_misses(attrs: &OsuDifficultyAttributes, state: &OsuScoreState) -> f64 {
+fn calculate_effective_misses(
+    attrs: &OsuDifficultyAttributes,
+    state: &OsuScoreState,
+    slider_hits: Option<&SliderHits>,
+) -> f64 {
     // * Guess the number of misses + slider breaks from combo
     let mut combo_based_miss_count = 0.0;
 
     if attrs.n_sliders > 0 {
-        let full_combo_threshold = attrs.max_combo as f64 - 0.1 * attrs.n_sliders as f64;
+        // Dropped slider ends don't break combo so they're known not to be slider breaks
+        let dropped_ends = slider_hits.map_or(0.1 * attrs.n_sliders as f64, |slider_hits| {
+            slider_hits.slider_ends_dropped() as f64
+        });
+
+        let full_combo_threshold = attrs.max_combo as f64 - dropped_ends;
 
         if (state.max_combo as f64) < full_combo_threshold {
             combo_based_miss_count = full_combo_threshold / (state.max_combo as f64).max(1.0);
@@ -695,12 +1024,83 @@ fn calculate_effective_misses(attrs: &OsuDifficultyAttributes, state: &OsuScoreS
     }
 
     // * Clamp miss count to maximum amount of possible breaks
-    combo_based_miss_count =
-        combo_based_miss_count.min((state.n100 + state.n50 + state.n_misses) as f64);
+    let mut possible_breaks = state.n100 + state.n50 + state.n_misses;
+
+    // Dropped slider ends are not included since they don't break combo
+    if let Some(slider_hits) = slider_hits {
+        possible_breaks += slider_hits.large_tick_misses();
+    }
+
+    combo_based_miss_count = combo_based_miss_count.min(possible_breaks as f64);
 
     combo_based_miss_count.max(state.n_misses as f64)
 }
 
+/// Judgements of slider ticks and ends which only matter for ScoreV2.
+#[derive(Clone, Debug)]
+struct SliderHits {
+    n_large_ticks: usize,
+    large_tick_hits: usize,
+    n_slider_ends: usize,
+    slider_end_hits: usize,
+}
+
+impl SliderHits {
+    // Score values of osu!lazer's judgements
+    const GREAT: usize = 300;
+    const OK: usize = 100;
+    const MEH: usize = 50;
+    const SLIDER_END: usize = 150;
+    const LARGE_TICK: usize = 30;
+
+    fn new(
+        attrs: &OsuDifficultyAttributes,
+        large_tick_hits: Option<usize>,
+        slider_end_hits: Option<usize>,
+    ) -> Self {
+        // Each slider adds its head, ticks, repeats, and end to the max combo
+        let n_large_ticks = attrs
+            .max_combo
+            .saturating_sub(attrs.n_circles + attrs.n_spinners + 2 * attrs.n_sliders);
+
+        let n_slider_ends = attrs.n_sliders;
+
+        Self {
+            n_large_ticks,
+            large_tick_hits: large_tick_hits.map_or(n_large_ticks, |n| n.min(n_large_ticks)),
+            n_slider_ends,
+            slider_end_hits: slider_end_hits.map_or(n_slider_ends, |n| n.min(n_slider_ends)),
+        }
+    }
+
+    fn large_tick_misses(&self) -> usize {
+        self.n_large_ticks - self.large_tick_hits
+    }
+
+    fn slider_ends_dropped(&self) -> usize {
+        self.n_slider_ends - self.slider_end_hits
+    }
+
+    /// The accuracy of the score including slider ticks and ends.
+    fn accuracy(&self, state: &OsuScoreState) -> f64 {
+        let max = Self::GREAT * state.total_hits()
+            + Self::SLIDER_END * self.n_slider_ends
+            + Self::LARGE_TICK * self.n_large_ticks;
+
+        if max == 0 {
+            return 0.0;
+        }
+
+        let score = Self::GREAT * state.n300
+            + Self::OK * state.n100
+            + Self::MEH * state.n50
+            + Self::SLIDER_END * self.slider_end_hits
+            + Self::LARGE_TICK * self.large_tick_hits;
+
+        score as f64 / max as f64
+    }
+}
+
 /// Abstract type to provide flexibility when passing difficulty attributes to a performance calculation.
 pub trait OsuAttributeProvider {
     /// Provide the actual difficulty attributes.
@@ -760,7 +1160,13 @@ mod test {
             speed: 2.533869745015772,
             flashlight: 2.288770487900865,
             slider_factor: 0.9803052946037858,
+            flashlight_slider_factor: 0.9565027947843286,
+            aim_angle_bonus: 2287.0174487547893,
+            aim_peak_variation: 0.41370428774512386,
+            flow_aim_ratio: 0.4502589835120666,
             speed_note_count: 210.36373973116545,
+            speed_spacing_factor: 1.3205422199808583,
+            has_simultaneous_objects: false,
             ar: 9.300000190734863,
             od: 8.800000190734863,
             hp: 5.0,
@@ -769,11 +1175,56 @@ mod test {
             n_spinners: 1,
             stars: 5.669858729379631,
             max_combo: 909,
+            mods: 0,
         };
 
         (map, attrs)
     }
 
+    #[test]
+    fn od_only_affects_hit_windows() {
+        let (map, _) = test_data();
+
+        let mut changed = map.clone();
+        changed.od = 5.0;
+
+        let attrs = OsuPP::new(&map).calculate();
+        let changed_attrs = OsuPP::new(&changed).calculate();
+
+        assert_eq!(attrs.difficulty.aim, changed_attrs.difficulty.aim);
+        assert_eq!(attrs.difficulty.ar, changed_attrs.difficulty.ar);
+        assert_ne!(attrs.difficulty.od, changed_attrs.difficulty.od);
+        assert!(attrs.pp_acc > changed_attrs.pp_acc);
+
+        // The speed skill considers the hit window to nerf doubletappable
+        // patterns so, unlike aim, it is expected to depend on OD
+        assert_ne!(attrs.difficulty.speed, changed_attrs.difficulty.speed);
+    }
+
+    #[test]
+    fn state_with_overrides() {
+        let (map, attrs) = test_data();
+        let max_combo = attrs.max_combo();
+
+        let state = OsuScoreState {
+            max_combo: 500,
+            n300: 550,
+            n100: 40,
+            n50: 9,
+            n_misses: 2,
+        };
+
+        let overridden = OsuPP::new(&map)
+            .attributes(attrs)
+            .state(state.clone())
+            .combo(max_combo)
+            .generate_hitresults(max_combo);
+
+        let expected = OsuScoreState { max_combo, ..state };
+
+        assert_eq!(overridden, expected);
+    }
+
     #[test]
     fn hitresults_n300_n100_n_misses_best() {
         let (map, attrs) = test_data();
@@ -1023,4 +1474,334 @@ mod test {
             expected.accuracy()
         );
     }
+
+    #[test]
+    fn effective_misses_from_combo() {
+        let (map, attrs) = test_data();
+
+        let fc = OsuPP::new(&map).attributes(attrs.clone()).calculate();
+        assert_eq!(fc.effective_miss_count, 0.0);
+
+        let result = OsuPP::new(&map)
+            .attributes(attrs.clone())
+            .combo(200)
+            .n100(10)
+            .n_misses(0)
+            .calculate();
+
+        assert!(result.effective_miss_count > 0.0);
+        assert!(result.pp < fc.pp);
+
+        // 100s count as possible breaks with slider head accuracy as well
+        let v2 = OsuPP::new(&map)
+            .attributes(attrs)
+            .slider_head_accuracy(true)
+            .combo(200)
+            .n100(10)
+            .n_misses(0)
+            .calculate();
+
+        assert!(v2.effective_miss_count > 0.0);
+    }
+
+    #[test]
+    fn reported_accuracy() {
+        let (map, attrs) = test_data();
+        let max_combo = attrs.max_combo;
+
+        let calc = OsuPP::new(&map)
+            .attributes(attrs)
+            .combo(500)
+            .n_misses(2)
+            .accuracy(97.3);
+
+        let state = calc.generate_hitresults(max_combo);
+        let result = calc.calculate();
+
+        assert_eq!(result.accuracy, state.accuracy());
+        assert!((result.accuracy - 0.973).abs() < 0.01);
+    }
+
+    #[test]
+    fn forced_relax_and_autopilot() {
+        let (map, _) = test_data();
+
+        let calculate = |mods: u32| OsuPP::new(&map).mods(mods).accuracy(98.0).n_misses(1);
+
+        let forced_relax = calculate(0).relax(true).calculate();
+        let relax = calculate(u32::RX).calculate();
+
+        assert_eq!(forced_relax, relax);
+        assert_eq!(forced_relax.difficulty.speed, 0.0);
+        assert_ne!(forced_relax, calculate(0).calculate());
+
+        let unforced = calculate(u32::RX).relax(false).calculate();
+        assert_eq!(unforced, calculate(0).calculate());
+
+        let forced_autopilot = calculate(0).autopilot(true).calculate();
+        assert_eq!(forced_autopilot, calculate(u32::AP).calculate());
+        assert_eq!(forced_autopilot.pp_aim, 0.0);
+    }
+
+    #[test]
+    fn hidden_bonus_depends_on_ar() {
+        let (map, attrs) = test_data();
+
+        let hidden_bonus = |ar: f64| {
+            let attrs = OsuDifficultyAttributes {
+                ar,
+                ..attrs.clone()
+            };
+
+            let calculate = |mods: u32, hidden_ar_scaling: bool| {
+                OsuPP::new(&map)
+                    .attributes(attrs.clone())
+                    .mods(mods)
+                    .accuracy(98.0)
+                    .hidden_ar_scaling(hidden_ar_scaling)
+                    .calculate()
+                    .pp_acc
+            };
+
+            let flat = calculate(u32::HD, false) / calculate(0, false);
+            let scaled = calculate(u32::HD, true) / calculate(0, true);
+
+            (flat, scaled)
+        };
+
+        for &ar in [5.0, 9.0, 10.0, 12.0].iter() {
+            assert!((hidden_bonus(ar).0 - 1.08).abs() < 1e-9, "AR{}", ar);
+        }
+
+        assert!(hidden_bonus(5.0).1 > hidden_bonus(10.0).1);
+        assert!((hidden_bonus(9.0).1 - 1.08).abs() < 1e-9);
+        assert!(hidden_bonus(10.0).1 > 1.0);
+    }
+
+    #[test]
+    fn length_bonus() {
+        let (map, attrs) = test_data();
+
+        let calc = OsuPP::new(&map).attributes(attrs.clone()).n_misses(1);
+        let state = calc.generate_hitresults(attrs.max_combo);
+        let long = calc.calculate();
+        assert!(long.length_bonus > 1.0);
+
+        let short = OsuPP::new(&map).passed_objects(20).calculate();
+        assert!((short.length_bonus - 1.0).abs() < 0.05);
+
+        // The AR is too low for the AR bonus which would also scale with the length bonus
+        assert!(attrs.ar > 8.0 && attrs.ar < 10.33);
+
+        let inner = OsuPpInner {
+            effective_miss_count: calculate_effective_misses(&attrs, &state, None),
+            max_combo: attrs.max_combo,
+            attrs,
+            mods: 0,
+            slider_hits: None,
+            acc: state.accuracy(),
+            state,
+            hidden_ar_scaling: false,
+        };
+
+        let unbonused_aim = inner.compute_aim_value(1.0);
+        let unbonused_speed = inner.compute_speed_value(1.0);
+
+        assert!((long.pp_aim / long.length_bonus - unbonused_aim).abs() < 1e-10);
+        assert!((long.pp_speed / long.length_bonus - unbonused_speed).abs() < 1e-10);
+    }
+
+    #[test]
+    fn reading_pp() {
+        let (map, _) = test_data();
+
+        let calculate = |mods: u32| OsuPP::new(&map).mods(mods).accuracy(98.0).calculate();
+
+        let nomod = calculate(0);
+        assert_eq!(nomod.pp_reading, 0.0);
+        assert_eq!(calculate(16).pp_reading, 0.0);
+
+        let hidden = calculate(8);
+        let flashlight = calculate(1024);
+        let hdfl = calculate(8 + 1024);
+
+        assert!(hidden.pp_reading > 0.0);
+        assert!(flashlight.pp_reading > 0.0);
+        assert!(hdfl.pp_reading > hidden.pp_reading.max(flashlight.pp_reading));
+        assert!((hdfl.pp - hdfl.pp_reading - nomod.pp).abs() < 1e-9);
+    }
+
+    #[test]
+    fn combo_cap() {
+        let (map, attrs) = test_data();
+        let cap = attrs.max_combo - 200;
+
+        let calculate = |combo: usize, combo_cap: Option<usize>| {
+            let mut calc = OsuPP::new(&map)
+                .attributes(attrs.clone())
+                .accuracy(98.0)
+                .combo(combo);
+
+            if let Some(combo_cap) = combo_cap {
+                calc = calc.combo_cap(combo_cap);
+            }
+
+            calc.calculate()
+        };
+
+        let full_combo = calculate(attrs.max_combo, None);
+        let capped = calculate(cap, Some(cap));
+        let uncapped = calculate(cap, None);
+
+        assert!(capped.pp < full_combo.pp);
+        assert!(capped.pp > uncapped.pp);
+        assert_eq!(capped.combo, cap);
+
+        // Without combo the cap is considered a full combo
+        let implicit = OsuPP::new(&map)
+            .attributes(attrs.clone())
+            .accuracy(98.0)
+            .combo_cap(cap)
+            .calculate();
+        assert_eq!(implicit, capped);
+
+        // Caps above the map's max combo have no effect
+        let large_cap = calculate(attrs.max_combo, Some(2 * attrs.max_combo));
+        assert_eq!(large_cap, full_combo);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    fn relax_variants() {
+        let (map, _) = test_data();
+
+        let calculate = |variant: RelaxVariant| {
+            OsuPP::new(&map)
+                .mods(8)
+                .accuracy(98.0)
+                .n_misses(2)
+                .relax_variant(variant)
+                .calculate_relax()
+        };
+
+        let standard = calculate(RelaxVariant::Standard);
+        let akatsuki = calculate(RelaxVariant::Akatsuki);
+
+        assert_eq!(standard.variant(), RelaxVariant::Standard);
+        assert_eq!(akatsuki.variant(), RelaxVariant::Akatsuki);
+
+        let expected = OsuPP::new(&map)
+            .mods(8)
+            .accuracy(98.0)
+            .n_misses(2)
+            .relax(true)
+            .calculate();
+
+        match standard {
+            RelaxPerformanceAttributes::Standard(ref attrs) => assert_eq!(attrs, &expected),
+            RelaxPerformanceAttributes::Akatsuki(_) => panic!("expected standard attributes"),
+        }
+
+        assert!(akatsuki.pp() > 0.0);
+        assert!((akatsuki.pp() - standard.pp()).abs() > f64::EPSILON);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    fn autopilot_values() {
+        let (map, _) = test_data();
+
+        let calculate = |mods: u32| OsuPP::new(&map).mods(mods).accuracy(98.0).calculate();
+
+        let nomod = calculate(0);
+        assert!(!nomod.autopilot);
+
+        let autopilot = calculate(u32::AP + u32::FL);
+        assert!(autopilot.autopilot);
+        assert_eq!(autopilot.pp_aim, 0.0);
+        assert_eq!(autopilot.pp_flashlight, 0.0);
+        assert!(autopilot.pp_speed > 0.0);
+
+        let expected = (autopilot.pp_speed.powf(1.1) + autopilot.pp_acc.powf(1.1)).powf(1.0 / 1.1)
+            * PERFORMANCE_BASE_MULTIPLIER;
+
+        assert!((autopilot.pp - expected).abs() < 1e-9);
+        assert!(autopilot.pp < nomod.pp);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    fn score_v2() {
+        let (map, attrs) = test_data();
+
+        let calculate = |mods: u32| OsuPP::new(&map).mods(mods).accuracy(98.0).n_misses(1);
+
+        let v1 = calculate(0).calculate();
+        let v2 = calculate(0).score_version(2).calculate();
+
+        assert_eq!(v2, calculate(u32::V2).calculate());
+        assert_eq!(v1, calculate(u32::V2).score_version(1).calculate());
+
+        // Slider heads count towards the accuracy value
+        assert!(attrs.n_sliders > 0);
+        assert!((v1.pp_acc - v2.pp_acc).abs() > f64::EPSILON);
+
+        // Fully hit slider ticks and ends raise the accuracy
+        assert!(v2.accuracy > v1.accuracy);
+
+        let all_hit = calculate(u32::V2)
+            .large_tick_hits(usize::MAX)
+            .slider_end_hits(attrs.n_sliders)
+            .calculate();
+
+        assert_eq!(all_hit, v2);
+
+        // Dropped slider ends lower the accuracy and count as slider breaks
+        let dropped = calculate(u32::V2)
+            .combo(attrs.max_combo / 2)
+            .slider_end_hits(attrs.n_sliders - 20)
+            .calculate();
+
+        let fc = calculate(u32::V2).combo(attrs.max_combo / 2).calculate();
+
+        assert!(dropped.accuracy < fc.accuracy);
+        assert!(dropped.pp_aim < fc.pp_aim);
+        assert!(dropped.pp < fc.pp);
+
+        // Missed slider ticks break combo
+        let ss_calc = calculate(u32::V2)
+            .accuracy(100.0)
+            .combo(attrs.max_combo / 2);
+        let ss = ss_calc.clone().calculate();
+        let tick_misses = ss_calc.large_tick_hits(0).calculate();
+
+        assert!(tick_misses.effective_miss_count > ss.effective_miss_count);
+        assert!(tick_misses.pp < ss.pp);
+
+        // Without ScoreV2 the slider ticks and ends are ignored
+        let ignored = calculate(0)
+            .slider_end_hits(0)
+            .large_tick_hits(0)
+            .calculate();
+        assert_eq!(ignored, v1);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    fn slider_head_accuracy() {
+        let (map, _) = test_data();
+
+        let calculate = |mods: u32| OsuPP::new(&map).mods(mods).accuracy(98.0).n_misses(1);
+
+        let v1 = calculate(0).calculate();
+        let v2 = calculate(u32::V2).calculate();
+
+        let heads = calculate(0).slider_head_accuracy(true).calculate();
+        assert_eq!(heads.pp, v2.pp);
+        assert_eq!(heads.pp_acc, v2.pp_acc);
+
+        let no_heads = calculate(u32::V2).slider_head_accuracy(false).calculate();
+        assert_eq!(no_heads.pp, v1.pp);
+        assert_eq!(no_heads.pp_acc, v1.pp_acc);
+    }
 }
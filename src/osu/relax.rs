@@ -0,0 +1,73 @@
+use crate::osu_2019::stars::OsuPerformanceAttributes as Osu2019PerformanceAttributes;
+
+use super::OsuPerformanceAttributes;
+
+/// The pp algorithm that is used for relax scores, see [`OsuPP::relax_variant`](super::OsuPP::relax_variant).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RelaxVariant {
+    /// The regular osu!standard performance calculation with RX applied.
+    Standard,
+    /// Akatsuki's relax curve which is based on the 2019 osu!standard
+    /// algorithm, see [`osu_2019`](crate::osu_2019).
+    ///
+    /// It reweights aim and speed and depresses the accuracy scaling.
+    Akatsuki,
+}
+
+impl Default for RelaxVariant {
+    #[inline]
+    fn default() -> Self {
+        Self::Standard
+    }
+}
+
+/// The result of a relax performance calculation, see [`OsuPP::calculate_relax`](super::OsuPP::calculate_relax).
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "variant", rename_all = "snake_case"))]
+pub enum RelaxPerformanceAttributes {
+    /// Result of [`RelaxVariant::Standard`].
+    Standard(OsuPerformanceAttributes),
+    /// Result of [`RelaxVariant::Akatsuki`].
+    Akatsuki(Osu2019PerformanceAttributes),
+}
+
+impl RelaxPerformanceAttributes {
+    /// The pp value.
+    #[inline]
+    pub fn pp(&self) -> f64 {
+        match self {
+            Self::Standard(attrs) => attrs.pp,
+            Self::Akatsuki(attrs) => attrs.pp,
+        }
+    }
+
+    /// The star value.
+    #[inline]
+    pub fn stars(&self) -> f64 {
+        match self {
+            Self::Standard(attrs) => attrs.difficulty.stars,
+            Self::Akatsuki(attrs) => attrs.difficulty.stars,
+        }
+    }
+
+    /// The maximum combo of the map.
+    #[inline]
+    pub fn max_combo(&self) -> usize {
+        match self {
+            Self::Standard(attrs) => attrs.difficulty.max_combo,
+            Self::Akatsuki(attrs) => attrs.difficulty.max_combo,
+        }
+    }
+
+    /// The variant that was used for the calculation.
+    #[inline]
+    pub fn variant(&self) -> RelaxVariant {
+        match self {
+            Self::Standard(_) => RelaxVariant::Standard,
+            Self::Akatsuki(_) => RelaxVariant::Akatsuki,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::f64::consts::{FRAC_PI_2, PI};
 
-use crate::osu::difficulty_object::OsuDifficultyObject;
+use crate::osu::{difficulty_object::OsuDifficultyObject, SECTION_LEN};
 
 use super::{previous, previous_start_time, OsuStrainSkill, Skill, StrainSkill};
 
@@ -9,7 +9,11 @@ pub(crate) struct Aim {
     curr_strain: f64,
     curr_section_peak: f64,
     curr_section_end: f64,
+    section_len: f64,
     pub(crate) strain_peaks: Vec<f64>,
+    pub(crate) angle_bonus: f64,
+    flow_strain: f64,
+    angled_strain: f64,
     with_sliders: bool,
 }
 
@@ -22,11 +26,55 @@ impl Aim {
             curr_strain: 0.0,
             curr_section_peak: 0.0,
             curr_section_end: 0.0,
+            section_len: SECTION_LEN,
             strain_peaks: Vec::new(),
+            angle_bonus: 0.0,
+            flow_strain: 0.0,
+            angled_strain: 0.0,
             with_sliders,
         }
     }
 
+    /// The strain of the most recently processed object.
+    pub(crate) fn curr_strain(&self) -> f64 {
+        self.curr_strain
+    }
+
+    /// The coefficient of variation of all non-zero strain peaks, including the current section.
+    pub(crate) fn peak_variation(&self) -> f64 {
+        let peaks = self
+            .strain_peaks
+            .iter()
+            .chain(Some(&self.curr_section_peak))
+            .filter(|&&peak| peak > 0.0);
+
+        let (count, sum) = peaks
+            .clone()
+            .fold((0, 0.0), |(count, sum), peak| (count + 1, sum + peak));
+
+        if count == 0 {
+            return 0.0;
+        }
+
+        let mean = sum / count as f64;
+
+        let variance = peaks.map(|peak| (peak - mean) * (peak - mean)).sum::<f64>() / count as f64;
+
+        variance.sqrt() / mean
+    }
+
+    /// The share of aim strain in `[0, 1]` that stems from flow aim rather than snap aim.
+    ///
+    /// Each object's strain is split up according to its angle: wide angles count
+    /// as flow aim and acute angles, e.g. back-and-forth jumps, as snap aim.
+    pub(crate) fn flow_aim_ratio(&self) -> f64 {
+        if self.angled_strain > 0.0 {
+            self.flow_strain / self.angled_strain
+        } else {
+            0.0
+        }
+    }
+
     fn strain_decay(ms: f64) -> f64 {
         Self::STRAIN_DECAY_BASE.powf(ms / 1000.0)
     }
@@ -64,15 +112,29 @@ impl StrainSkill for Aim {
         &mut self.curr_section_end
     }
 
+    #[inline]
+    fn section_len(&mut self) -> &mut f64 {
+        &mut self.section_len
+    }
+
     #[inline]
     fn strain_value_at(
         &mut self,
         curr: &OsuDifficultyObject<'_>,
         diff_objects: &[OsuDifficultyObject<'_>],
     ) -> f64 {
+        let (aim_strain, angle_bonus) =
+            AimEvaluator::evaluate_diff_of(curr, diff_objects, self.with_sliders);
+
+        self.angle_bonus += angle_bonus * Self::SKILL_MULTIPLIER;
+
+        if let Some(angle) = curr.dists.angle {
+            self.flow_strain += aim_strain * AimEvaluator::calc_wide_angle_bonus(angle);
+            self.angled_strain += aim_strain;
+        }
+
         self.curr_strain *= Self::strain_decay(curr.delta_time);
-        self.curr_strain += AimEvaluator::evaluate_diff_of(curr, diff_objects, self.with_sliders)
-            * Self::SKILL_MULTIPLIER;
+        self.curr_strain += aim_strain * Self::SKILL_MULTIPLIER;
 
         self.curr_strain
     }
@@ -103,11 +165,12 @@ impl AimEvaluator {
     const SLIDER_MULTIPLIER: f64 = 1.35;
     const VELOCITY_CHANGE_MULTIPLIER: f64 = 0.75;
 
+    /// Returns the aim strain of the object alongside the part of it that stems from angle bonuses.
     fn evaluate_diff_of(
         curr: &OsuDifficultyObject<'_>,
         diff_objects: &[OsuDifficultyObject<'_>],
         with_sliders: bool,
-    ) -> f64 {
+    ) -> (f64, f64) {
         let osu_curr_obj = curr;
 
         let (osu_last_last_obj, osu_last_obj) = if let Some(tuple) =
@@ -117,7 +180,7 @@ impl AimEvaluator {
         {
             tuple
         } else {
-            return 0.0;
+            return (0.0, 0.0);
         };
 
         // * Calculate the velocity to the current hitobject, which starts
@@ -237,18 +300,27 @@ impl AimEvaluator {
             slider_bonus = osu_last_obj.dists.travel_dist / osu_last_obj.dists.travel_time
         }
 
+        let acute_bonus = acute_angle_bonus * Self::ACUTE_ANGLE_MULTIPLIER;
+        let wide_bonus = wide_angle_bonus * Self::WIDE_ANGLE_MULTIPLIER;
+        let vel_change_bonus = vel_change_bonus * Self::VELOCITY_CHANGE_MULTIPLIER;
+
         // * Add in acute angle bonus or wide angle bonus + velocity change bonus, whichever is larger.
-        aim_strain += (acute_angle_bonus * Self::ACUTE_ANGLE_MULTIPLIER).max(
-            wide_angle_bonus * Self::WIDE_ANGLE_MULTIPLIER
-                + vel_change_bonus * Self::VELOCITY_CHANGE_MULTIPLIER,
-        );
+        let angle_bonus = if acute_bonus > wide_bonus + vel_change_bonus {
+            aim_strain += acute_bonus;
+
+            acute_bonus
+        } else {
+            aim_strain += wide_bonus + vel_change_bonus;
+
+            wide_bonus
+        };
 
         // * Add in additional slider velocity bonus.
         if with_sliders {
             aim_strain += slider_bonus * Self::SLIDER_MULTIPLIER;
         }
 
-        aim_strain
+        (aim_strain, angle_bonus)
     }
 
     fn calc_wide_angle_bonus(angle: f64) -> f64 {
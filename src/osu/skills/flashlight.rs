@@ -1,5 +1,5 @@
 use crate::{
-    osu::{difficulty_object::OsuDifficultyObject, osu_object::OsuObjectKind},
+    osu::{difficulty_object::OsuDifficultyObject, osu_object::OsuObjectKind, SECTION_LEN},
     Mods,
 };
 
@@ -10,30 +10,45 @@ pub(crate) struct Flashlight {
     curr_strain: f64,
     curr_section_peak: f64,
     curr_section_end: f64,
+    section_len: f64,
     pub(crate) strain_peaks: Vec<f64>,
     has_hidden_mod: bool,
     scaling_factor: f64,
     time_preempt: f64,
     time_fade_in: f64,
+    with_sliders: bool,
 }
 
 impl Flashlight {
     const SKILL_MULTIPLIER: f64 = 0.052;
     const STRAIN_DECAY_BASE: f64 = 0.15;
 
-    pub(crate) fn new(mods: u32, radius: f32, time_preempt: f64, time_fade_in: f64) -> Self {
+    pub(crate) fn new(
+        mods: u32,
+        radius: f32,
+        time_preempt: f64,
+        time_fade_in: f64,
+        with_sliders: bool,
+    ) -> Self {
         Self {
             curr_strain: 0.0,
             curr_section_peak: 0.0,
             curr_section_end: 0.0,
+            section_len: SECTION_LEN,
             strain_peaks: Vec::new(),
             has_hidden_mod: mods.hd(),
             scaling_factor: 52.0 / radius as f64,
             time_preempt,
             time_fade_in,
+            with_sliders,
         }
     }
 
+    /// The strain of the most recently processed object.
+    pub(crate) fn curr_strain(&self) -> f64 {
+        self.curr_strain
+    }
+
     fn strain_decay(ms: f64) -> f64 {
         Self::STRAIN_DECAY_BASE.powf(ms / 1000.0)
     }
@@ -73,6 +88,11 @@ impl StrainSkill for Flashlight {
         &mut self.curr_section_end
     }
 
+    #[inline]
+    fn section_len(&mut self) -> &mut f64 {
+        &mut self.section_len
+    }
+
     #[inline]
     fn strain_value_at(
         &mut self,
@@ -87,6 +107,7 @@ impl StrainSkill for Flashlight {
             self.scaling_factor,
             self.time_preempt,
             self.time_fade_in,
+            self.with_sliders,
         ) * Self::SKILL_MULTIPLIER;
 
         self.curr_strain
@@ -128,6 +149,7 @@ impl FlashlightEvaluator {
         scaling_factor: f64,
         time_preempt: f64,
         time_fade_in: f64,
+        with_sliders: bool,
     ) -> f64 {
         if curr.base.is_spinner() {
             return 0.0;
@@ -230,7 +252,9 @@ impl FlashlightEvaluator {
             }
         }
 
-        result += slider_bonus * Self::SLIDER_MULTIPLIER;
+        if with_sliders {
+            result += slider_bonus * Self::SLIDER_MULTIPLIER;
+        }
 
         result
     }
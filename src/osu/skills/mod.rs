@@ -3,7 +3,7 @@ mod flashlight;
 mod speed;
 mod traits;
 
-use crate::osu::difficulty_object::OsuDifficultyObject;
+use crate::{osu::difficulty_object::OsuDifficultyObject, Mods};
 
 pub(crate) use self::{
     aim::Aim,
@@ -18,6 +18,10 @@ pub(crate) struct Skills {
     pub aim_no_sliders: Aim,
     pub speed: Speed,
     pub flashlight: Flashlight,
+    /// Only processed with FL since it's just required for the flashlight slider factor.
+    pub flashlight_no_sliders: Option<Flashlight>,
+    /// Flashlight skill as if HD was enabled, only processed when requested.
+    pub hidden_flashlight: Option<Flashlight>,
 }
 
 impl Skills {
@@ -27,12 +31,33 @@ impl Skills {
         time_preempt: f64,
         time_fade_in: f64,
         hit_window: f64,
+        alternating: bool,
     ) -> Self {
         Self {
             aim: Aim::new(true),
             aim_no_sliders: Aim::new(false),
-            speed: Speed::new(hit_window, mods),
-            flashlight: Flashlight::new(mods, radius, time_preempt, time_fade_in),
+            speed: Speed::new(hit_window, mods, alternating),
+            flashlight: Flashlight::new(mods, radius, time_preempt, time_fade_in, true),
+            flashlight_no_sliders: mods
+                .fl()
+                .then(|| Flashlight::new(mods, radius, time_preempt, time_fade_in, false)),
+            hidden_flashlight: None,
+        }
+    }
+
+    /// Adjust the time in ms that each strain peak covers.
+    pub(crate) fn set_section_len(&mut self, section_len: f64) {
+        *self.aim.section_len() = section_len;
+        *self.aim_no_sliders.section_len() = section_len;
+        *self.speed.section_len() = section_len;
+        *self.flashlight.section_len() = section_len;
+
+        if let Some(ref mut flashlight_no_sliders) = self.flashlight_no_sliders {
+            *flashlight_no_sliders.section_len() = section_len;
+        }
+
+        if let Some(ref mut hidden_flashlight) = self.hidden_flashlight {
+            *hidden_flashlight.section_len() = section_len;
         }
     }
 
@@ -45,6 +70,14 @@ impl Skills {
         <Aim as Skill>::process(&mut self.aim_no_sliders, curr, diff_objects);
         <Speed as Skill>::process(&mut self.speed, curr, diff_objects);
         <Flashlight as Skill>::process(&mut self.flashlight, curr, diff_objects);
+
+        if let Some(ref mut flashlight_no_sliders) = self.flashlight_no_sliders {
+            <Flashlight as Skill>::process(flashlight_no_sliders, curr, diff_objects);
+        }
+
+        if let Some(ref mut hidden_flashlight) = self.hidden_flashlight {
+            <Flashlight as Skill>::process(hidden_flashlight, curr, diff_objects);
+        }
     }
 }
 
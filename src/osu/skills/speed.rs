@@ -1,6 +1,9 @@
 use std::{cmp::Ordering, f64::consts::PI};
 
-use crate::{osu::difficulty_object::OsuDifficultyObject, Mods};
+use crate::{
+    osu::{difficulty_object::OsuDifficultyObject, SECTION_LEN},
+    Mods,
+};
 
 use super::{next, previous, previous_start_time, OsuStrainSkill, Skill, StrainSkill};
 
@@ -9,27 +12,52 @@ pub(crate) struct Speed {
     curr_strain: f64,
     curr_section_peak: f64,
     curr_section_end: f64,
+    section_len: f64,
     curr_rhythm: f64,
     pub(crate) strain_peaks: Vec<f64>,
     object_strains: Vec<f64>,
+    spacing_sum: f64,
+    spacing_weight: f64,
     hit_window: f64,
     mods: u32,
+    pub(crate) alternating: bool,
 }
 
 impl Speed {
     const SKILL_MULTIPLIER: f64 = 1375.0;
     const STRAIN_DECAY_BASE: f64 = 0.3;
 
-    pub(crate) fn new(hit_window: f64, mods: u32) -> Self {
+    pub(crate) fn new(hit_window: f64, mods: u32, alternating: bool) -> Self {
         Self {
             curr_strain: 0.0,
             curr_section_peak: 0.0,
             curr_section_end: 0.0,
+            section_len: SECTION_LEN,
             curr_rhythm: 0.0,
             strain_peaks: Vec::new(),
             object_strains: Vec::new(),
+            spacing_sum: 0.0,
+            spacing_weight: 0.0,
             hit_window,
             mods,
+            alternating,
+        }
+    }
+
+    /// The strain of the most recently processed object, including its rhythm complexity.
+    pub(crate) fn curr_strain(&self) -> f64 {
+        self.object_strains.last().copied().unwrap_or(0.0)
+    }
+
+    /// The average multiplier in `[1, 2]` that the spacing between objects applied
+    /// to the speed strain, weighted by each object's speed difficulty without it.
+    ///
+    /// Tightly packed streams are close to `1.0` while spaced streams are higher.
+    pub(crate) fn spacing_factor(&self) -> f64 {
+        if self.spacing_weight > 0.0 {
+            self.spacing_sum / self.spacing_weight
+        } else {
+            1.0
         }
     }
 
@@ -83,16 +111,31 @@ impl StrainSkill for Speed {
         &mut self.curr_section_end
     }
 
+    #[inline]
+    fn section_len(&mut self) -> &mut f64 {
+        &mut self.section_len
+    }
+
     #[inline]
     fn strain_value_at(
         &mut self,
         curr: &OsuDifficultyObject<'_>,
         diff_objects: &[OsuDifficultyObject<'_>],
     ) -> f64 {
+        let (speed_strain, spacing_factor) = SpeedEvaluator::evaluate_diff_of(
+            curr,
+            diff_objects,
+            self.hit_window,
+            self.mods,
+            self.alternating,
+        );
+
+        let unspaced_strain = speed_strain / spacing_factor;
+        self.spacing_sum += spacing_factor * unspaced_strain;
+        self.spacing_weight += unspaced_strain;
+
         self.curr_strain *= Self::strain_decay(curr.strain_time);
-        self.curr_strain +=
-            SpeedEvaluator::evaluate_diff_of(curr, diff_objects, self.hit_window, self.mods)
-                * Self::SKILL_MULTIPLIER;
+        self.curr_strain += speed_strain * Self::SKILL_MULTIPLIER;
         self.curr_rhythm = RhythmEvaluator::evaluate_diff_of(curr, diff_objects, self.hit_window);
 
         let total_strain = self.curr_strain * self.curr_rhythm;
@@ -130,14 +173,16 @@ impl SpeedEvaluator {
     const MIN_SPEED_BONUS: f64 = 75.0; // ~200BPM
     const SPEED_BALANCING_FACTOR: f64 = 40.;
 
+    /// Returns the speed strain of the object alongside the multiplier that its spacing applied.
     fn evaluate_diff_of(
         curr: &OsuDifficultyObject<'_>,
         diff_objects: &[OsuDifficultyObject<'_>],
         hit_window: f64,
         mods: u32,
-    ) -> f64 {
+        alternating: bool,
+    ) -> (f64, f64) {
         if curr.base.is_spinner() {
-            return 0.0;
+            return (0.0, 1.0);
         }
 
         // * derive strainTime for calculation
@@ -161,7 +206,10 @@ impl SpeedEvaluator {
 
         // * Cap deltatime to the OD 300 hitwindow.
         // * 0.93 is derived from making sure 260bpm OD8 streams aren't nerfed harshly, whilst 0.92 limits the effect of the cap.
-        strain_time /= ((strain_time / hit_window) / 0.93).clamp(0.92, 1.0);
+        // The cap models single-tapping limits so alternated streams skip it.
+        if !alternating {
+            strain_time /= ((strain_time / hit_window) / 0.93).clamp(0.92, 1.0);
+        }
 
         // * derive speedBonus for calculation
         let speed_bonus = if strain_time < Self::MIN_SPEED_BONUS {
@@ -180,9 +228,12 @@ impl SpeedEvaluator {
             }
         };
 
-        (speed_bonus + speed_bonus * (dist / Self::SINGLE_SPACING_THRESHOLD).powf(3.5))
-            * doubletapness
-            / strain_time
+        let spacing_factor = 1.0 + (dist / Self::SINGLE_SPACING_THRESHOLD).powf(3.5);
+
+        (
+            speed_bonus * spacing_factor * doubletapness / strain_time,
+            spacing_factor,
+        )
     }
 }
 
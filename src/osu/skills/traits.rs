@@ -1,6 +1,6 @@
 use std::{cmp::Ordering, mem};
 
-use crate::osu::{difficulty_object::OsuDifficultyObject, SECTION_LEN};
+use crate::osu::difficulty_object::OsuDifficultyObject;
 
 pub(crate) trait Skill {
     fn process(&mut self, curr: &OsuDifficultyObject<'_>, diff_objects: &[OsuDifficultyObject<'_>]);
@@ -13,6 +13,7 @@ pub(crate) trait StrainSkill: Skill + Sized {
     fn strain_peaks_mut(&mut self) -> &mut Vec<f64>;
     fn curr_section_peak(&mut self) -> &mut f64;
     fn curr_section_end(&mut self) -> &mut f64;
+    fn section_len(&mut self) -> &mut f64;
 
     fn strain_value_at(
         &mut self,
@@ -34,7 +35,7 @@ pub(crate) trait StrainSkill: Skill + Sized {
     ) {
         // * The first object doesn't generate a strain, so we begin with an incremented section end
         if curr.idx == 0 {
-            let section_len = SECTION_LEN as f64;
+            let section_len = *self.section_len();
             *self.curr_section_end() = (curr.start_time / section_len).ceil() * section_len;
         }
 
@@ -46,7 +47,8 @@ pub(crate) trait StrainSkill: Skill + Sized {
                 self.start_new_section_from(section_end, curr, diff_objects);
             }
 
-            *self.curr_section_end() += SECTION_LEN as f64;
+            let section_len = *self.section_len();
+            *self.curr_section_end() += section_len;
         }
 
         *self.curr_section_peak() = self
@@ -23,6 +23,10 @@ pub enum ParseError {
     MissingField(&'static str),
     /// Failed to recognized specified type for hitobjects.
     UnknownHitObjectKind,
+    /// No `.osu` file of an `.osz` archive matched the difficulty filter.
+    MissingOsuFile,
+    /// An `.osz` archive contains multiple `.osu` files but no difficulty filter was specified.
+    AmbiguousOsuFile,
 }
 
 impl fmt::Display for ParseError {
@@ -38,6 +42,10 @@ impl fmt::Display for ParseError {
             Self::InvalidMode => f.write_str("invalid mode"),
             Self::MissingField(field) => write!(f, "missing field `{}`", field),
             Self::UnknownHitObjectKind => f.write_str("unsupported hitobject kind"),
+            Self::MissingOsuFile => f.write_str("no matching `.osu` file in the archive"),
+            Self::AmbiguousOsuFile => {
+                f.write_str("multiple `.osu` files in the archive, specify a difficulty")
+            }
         }
     }
 }
@@ -53,6 +61,8 @@ impl StdError for ParseError {
             Self::InvalidMode => None,
             Self::MissingField(_) => None,
             Self::UnknownHitObjectKind => None,
+            Self::MissingOsuFile => None,
+            Self::AmbiguousOsuFile => None,
         }
     }
 }
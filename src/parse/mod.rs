@@ -1,6 +1,11 @@
 mod error;
 mod hitobject;
 mod hitsound;
+#[cfg(all(
+    feature = "zip",
+    not(any(feature = "async_std", feature = "async_tokio"))
+))]
+mod osz;
 mod pos2;
 mod reader;
 mod sort;
@@ -29,7 +34,10 @@ use std::path::Path;
 use async_std::{fs::File, io::Read as AsyncRead, path::Path};
 
 use crate::{
-    beatmap::{Beatmap, Break, DifficultyPoint, EffectPoint, GameMode, TimingPoint},
+    beatmap::{
+        Beatmap, BeatmapAttributesBuilder, Break, DifficultyPoint, EffectPoint, GameMode,
+        TimingPoint,
+    },
     util::{SortedVec, TandemSorter},
 };
 
@@ -256,6 +264,12 @@ macro_rules! parse_events_body {
                 break;
             }
 
+            // We're only interested in breaks so storyboard lines etc. are skipped
+            // before validating the line; only break events start with `2` or `B`
+            if !matches!($reader.first_non_whitespace(), Some(b'2' | b'B')) {
+                continue;
+            }
+
             let line = match $reader.get_line() {
                 Ok(line) => line,
                 Err(_) => $reader.get_line_ascii()?, // see ranked map id 49374
@@ -263,8 +277,7 @@ macro_rules! parse_events_body {
 
             let mut split = line.split(',');
 
-            // We're only interested in breaks
-            if let Some(b'2') = split.next().and_then(|value| value.bytes().next()) {
+            if let Some("2") | Some("Break") = split.next().map(str::trim) {
                 let start_time = split
                     .next()
                     .next_field("break start")
@@ -746,7 +759,7 @@ fn parse_custom_sound(sound: &str) -> u8 {
 }
 
 macro_rules! parse_body {
-    ($input:ident) => {{
+    ($input:ident, $attributes_only:literal) => {{
         let mut reader = FileReader::new($input);
         next_line!(reader)?;
 
@@ -777,6 +790,9 @@ macro_rules! parse_body {
 
         loop {
             match section {
+                // Everything relevant for the attributes comes before the hit objects
+                Section::HitObjects if $attributes_only => break,
+                Section::Metadata | Section::Events if $attributes_only => section = Section::None,
                 Section::General => section!(map, parse_general, reader, section),
                 Section::Metadata => section!(map, parse_metadata, reader, section),
                 Section::Difficulty => section!(map, parse_difficulty, reader, section),
@@ -795,6 +811,10 @@ macro_rules! parse_body {
             }
         }
 
+        if !$attributes_only {
+            map.md5_hash = Some(reader.md5());
+        }
+
         Ok(map)
     }};
 }
@@ -972,7 +992,7 @@ impl Beatmap {
     /// You'll likely want to pass (a reference of) a [`File`](std::fs::File)
     /// or the file's content as a slice of bytes (`&[u8]`).
     pub fn parse<R: Read>(input: R) -> ParseResult<Self> {
-        parse_body!(input)
+        parse_body!(input, false)
     }
 
     fn parse_general<R: Read>(
@@ -1036,6 +1056,33 @@ impl Beatmap {
     pub fn from_bytes(bytes: &[u8]) -> ParseResult<Self> {
         Self::parse(bytes)
     }
+
+    /// Parse only the sections up to the hit objects, skipping metadata and events.
+    fn parse_attributes_only<R: Read>(input: R) -> ParseResult<Self> {
+        parse_body!(input, true)
+    }
+}
+
+#[cfg(not(any(feature = "async_std", feature = "async_tokio")))]
+impl BeatmapAttributesBuilder {
+    /// Parse only the general, difficulty, and timing point sections of a `.osu` file.
+    ///
+    /// Considerably faster than parsing the full [`Beatmap`] since hit objects are
+    /// not decoded so it is suitable if only the attributes and BPM are needed.
+    pub fn parse<R: Read>(input: R) -> ParseResult<Self> {
+        Beatmap::parse_attributes_only(input).map(|map| Self::new(&map))
+    }
+
+    /// Pass the path to a `.osu` file, see [`parse`](BeatmapAttributesBuilder::parse).
+    pub fn from_path<P: AsRef<Path>>(path: P) -> ParseResult<Self> {
+        Self::parse(File::open(path)?)
+    }
+
+    /// Parse the content of a `.osu` file in form of a slice of bytes,
+    /// see [`parse`](BeatmapAttributesBuilder::parse).
+    pub fn from_bytes(bytes: &[u8]) -> ParseResult<Self> {
+        Self::parse(bytes)
+    }
 }
 
 #[cfg(any(feature = "async_tokio", feature = "async_std"))]
@@ -1047,7 +1094,7 @@ impl Beatmap {
     /// You'll likely want to pass a `File`
     /// or the file's content as a slice of bytes (`&[u8]`).
     pub async fn parse<R: AsyncRead + Unpin>(input: R) -> ParseResult<Self> {
-        parse_body!(input)
+        parse_body!(input, false)
     }
 
     async fn parse_general<R: AsyncRead + Unpin>(
@@ -1109,6 +1156,44 @@ impl Beatmap {
     pub async fn from_bytes(bytes: &[u8]) -> ParseResult<Self> {
         Self::parse(bytes).await
     }
+
+    /// Parse a beatmap incrementally from any async reader,
+    /// e.g. a response body streamed from an object storage.
+    ///
+    /// Same as [`parse`](Beatmap::parse); lines are read as they arrive
+    /// so no blocking task is needed.
+    pub async fn from_async_read<R: AsyncRead + Unpin>(input: R) -> ParseResult<Self> {
+        Self::parse(input).await
+    }
+
+    /// Parse only the sections up to the hit objects, skipping metadata and events.
+    async fn parse_attributes_only<R: AsyncRead + Unpin>(input: R) -> ParseResult<Self> {
+        parse_body!(input, true)
+    }
+}
+
+#[cfg(any(feature = "async_std", feature = "async_tokio"))]
+impl BeatmapAttributesBuilder {
+    /// Parse only the general, difficulty, and timing point sections of a `.osu` file.
+    ///
+    /// Considerably faster than parsing the full [`Beatmap`] since hit objects are
+    /// not decoded so it is suitable if only the attributes and BPM are needed.
+    pub async fn parse<R: AsyncRead + Unpin>(input: R) -> ParseResult<Self> {
+        Beatmap::parse_attributes_only(input)
+            .await
+            .map(|map| Self::new(&map))
+    }
+
+    /// Pass the path to a `.osu` file, see [`parse`](BeatmapAttributesBuilder::parse).
+    pub async fn from_path<P: AsRef<Path>>(path: P) -> ParseResult<Self> {
+        Self::parse(File::open(path).await?).await
+    }
+
+    /// Parse the content of a `.osu` file in form of a slice of bytes,
+    /// see [`parse`](BeatmapAttributesBuilder::parse).
+    pub async fn from_bytes(bytes: &[u8]) -> ParseResult<Self> {
+        Self::parse(bytes).await
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -1135,3 +1220,28 @@ impl Section {
         }
     }
 }
+
+#[cfg(all(test, feature = "async_tokio"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn async_read() {
+        let bytes = std::fs::read("./maps/2785319.osu").unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let (streamed, from_bytes) = runtime.block_on(async {
+            let file = File::open("./maps/2785319.osu").await.unwrap();
+            let streamed = Beatmap::from_async_read(file).await.unwrap();
+            let from_bytes = Beatmap::from_bytes(&bytes).await.unwrap();
+
+            (streamed, from_bytes)
+        });
+
+        assert_eq!(streamed.hit_objects, from_bytes.hit_objects);
+        assert_eq!(streamed.md5_hash, from_bytes.md5_hash);
+    }
+}
@@ -0,0 +1,132 @@
+use std::{fs::File, io::Error as IoError, path::Path};
+
+use zip::ZipArchive;
+
+use super::{ParseError, ParseResult};
+use crate::Beatmap;
+
+impl Beatmap {
+    /// Parse a beatmap from an `.osz` archive.
+    ///
+    /// If `diff_filter` is specified, the `.osu` file of the difficulty with that
+    /// name is parsed, i.e. the file whose name ends in `[<diff_filter>].osu`.
+    /// Otherwise, the archive must contain exactly one `.osu` file.
+    #[cfg_attr(docsrs, doc(cfg(feature = "zip")))]
+    pub fn from_osz_path<P: AsRef<Path>>(path: P, diff_filter: Option<&str>) -> ParseResult<Self> {
+        let mut archive = ZipArchive::new(File::open(path)?).map_err(IoError::from)?;
+        let mut matching = None;
+
+        for i in 0..archive.len() {
+            let entry = archive.by_index(i).map_err(IoError::from)?;
+
+            if !matches_difficulty(entry.name(), diff_filter) {
+                continue;
+            }
+
+            if matching.replace(i).is_some() {
+                return Err(ParseError::AmbiguousOsuFile);
+            }
+        }
+
+        let idx = matching.ok_or(ParseError::MissingOsuFile)?;
+        let entry = archive.by_index(idx).map_err(IoError::from)?;
+
+        Self::parse(entry)
+    }
+}
+
+fn matches_difficulty(name: &str, diff_filter: Option<&str>) -> bool {
+    let stem = match name.strip_suffix(".osu") {
+        Some(stem) => stem,
+        None => return false,
+    };
+
+    match diff_filter {
+        Some(diff) => matches!(
+            stem.strip_suffix(']').and_then(|stem| stem.strip_suffix(diff)),
+            Some(prefix) if prefix.ends_with('[')
+        ),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::{self, File},
+        io::Write,
+        path::PathBuf,
+    };
+
+    use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+    use super::*;
+    use crate::GameMode;
+
+    fn write_osz(name: &str, entries: &[(&str, &str)]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}.osz", name, std::process::id()));
+        let mut writer = ZipWriter::new(File::create(&path).unwrap());
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        for (name, source) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(&fs::read(source).unwrap()).unwrap();
+        }
+
+        writer.finish().unwrap();
+
+        path
+    }
+
+    #[test]
+    fn select_difficulty() {
+        let path = write_osz(
+            "two-diffs",
+            &[
+                ("Artist - Title (Mapper) [Hard].osu", "./maps/2785319.osu"),
+                (
+                    "Artist - Title (Mapper) [Extra Hard].osu",
+                    "./maps/1028484.osu",
+                ),
+                ("audio.mp3", "./maps/1974394.osu"),
+            ],
+        );
+
+        let hard = Beatmap::from_osz_path(&path, Some("Hard")).unwrap();
+        let extra = Beatmap::from_osz_path(&path, Some("Extra Hard")).unwrap();
+
+        assert_eq!(hard.mode, GameMode::Osu);
+        assert_eq!(extra.mode, GameMode::Taiko);
+        assert_eq!(
+            hard.hit_objects.len(),
+            Beatmap::from_path("./maps/2785319.osu")
+                .unwrap()
+                .hit_objects
+                .len()
+        );
+
+        assert!(matches!(
+            Beatmap::from_osz_path(&path, None),
+            Err(ParseError::AmbiguousOsuFile)
+        ));
+        assert!(matches!(
+            Beatmap::from_osz_path(&path, Some("Insane")),
+            Err(ParseError::MissingOsuFile)
+        ));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn single_difficulty() {
+        let path = write_osz(
+            "single-diff",
+            &[("Artist - Title (Mapper) [Normal].osu", "./maps/2118524.osu")],
+        );
+
+        let map = Beatmap::from_osz_path(&path, None).unwrap();
+        assert_eq!(map.mode, GameMode::Catch);
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -9,7 +9,7 @@ use tokio::io::{AsyncBufReadExt, AsyncRead as Read, BufReader};
 #[cfg(feature = "async_std")]
 use async_std::io::{prelude::BufReadExt, BufReader, Read};
 
-use crate::ParseError;
+use crate::{util::Md5, ParseError};
 
 #[derive(Eq, PartialEq)]
 enum Encoding {
@@ -20,6 +20,7 @@ enum Encoding {
 pub(crate) struct FileReader<R> {
     buf: Vec<u8>,
     encoding: Encoding,
+    md5: Md5,
 
     #[cfg(feature = "async_std")]
     inner: BufReader<R>,
@@ -62,6 +63,7 @@ macro_rules! impl_reader {
                 Self {
                     buf: Vec::with_capacity(32),
                     encoding: Encoding::Utf8,
+                    md5: Md5::new(),
                     inner: BufReader::new(src),
                 }
             }
@@ -85,6 +87,7 @@ macro_rules! impl_reader {
                 return Ok(bytes);
             }
 
+            $self.md5.update(&$self.buf);
             $self.truncate();
 
             if !$self.buf.is_empty() {
@@ -156,6 +159,11 @@ impl<R> FileReader<R> {
             .ok_or(ParseError::IncorrectFileHeader)
     }
 
+    /// MD5 hash of all bytes that have been read so far.
+    pub(crate) fn md5(&self) -> [u8; 16] {
+        self.md5.clone().finalize()
+    }
+
     /// Returns the bytes inbetween '[' and ']'.
     pub(crate) fn get_section(&self) -> Option<&[u8]> {
         if self.buf[0] == b'[' {
@@ -167,6 +175,14 @@ impl<R> FileReader<R> {
         None
     }
 
+    /// Returns the first byte of the buffer that is not whitespace.
+    pub(crate) fn first_non_whitespace(&self) -> Option<u8> {
+        self.buf
+            .iter()
+            .copied()
+            .find(|byte| !matches!(byte, b' ' | b'\t'))
+    }
+
     /// Parse the buffer into a string, returning `None` if the UTF-8 validation fails.
     pub(crate) fn get_line(&self) -> Result<&str, ParseError> {
         std::str::from_utf8(&self.buf)
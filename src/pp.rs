@@ -1,6 +1,9 @@
+use std::{error::Error as StdError, fmt};
+
 use crate::{
     catch::{CatchDifficultyAttributes, CatchPP, CatchPerformanceAttributes},
     mania::{ManiaDifficultyAttributes, ManiaPP, ManiaPerformanceAttributes},
+    mods::difficulty_mods,
     osu::{OsuDifficultyAttributes, OsuPP, OsuPerformanceAttributes},
     taiko::{TaikoDifficultyAttributes, TaikoPP, TaikoPerformanceAttributes},
     Beatmap, DifficultyAttributes, GameMode, PerformanceAttributes, ScoreState,
@@ -72,6 +75,114 @@ impl<'map> AnyPP<'map> {
         }
     }
 
+    /// Same as [`AnyPP::calculate`] but returns an error if previously provided
+    /// difficulty attributes were calculated with different mods.
+    #[inline]
+    pub fn try_calculate(self) -> Result<PerformanceAttributes, ModsMismatch> {
+        match self {
+            Self::Osu(o) => o.try_calculate().map(PerformanceAttributes::Osu),
+            Self::Taiko(t) => t.try_calculate().map(PerformanceAttributes::Taiko),
+            Self::Catch(f) => f.try_calculate().map(PerformanceAttributes::Catch),
+            Self::Mania(m) => m.try_calculate().map(PerformanceAttributes::Mania),
+        }
+    }
+
+    /// Generate the hitresults that [`calculate`](AnyPP::calculate) would use.
+    ///
+    /// Hitresults that were not specified are generated to match the accuracy,
+    /// if given, and distributed according to the [`HitResultPriority`].
+    /// This is useful to see which score an accuracy, e.g. for an if-FC,
+    /// actually corresponds to.
+    ///
+    /// If no attributes were provided, they're calculated and kept
+    /// so that a subsequent calculation can re-use them.
+    #[inline]
+    pub fn generate_state(&mut self) -> ScoreState {
+        match self {
+            Self::Osu(o) => o.generate_state().into(),
+            Self::Taiko(t) => t.generate_state().into(),
+            Self::Catch(f) => f.generate_state().into(),
+            Self::Mania(m) => m.generate_state().into(),
+        }
+    }
+
+    /// Calculate the pp for every miss count from `0` up to and including `max_misses`
+    /// while keeping all other parameters, e.g. the accuracy or combo, the same.
+    ///
+    /// The difficulty attributes are only calculated once and then re-used.
+    pub fn pp_by_misses(self, max_misses: usize) -> Vec<(usize, f64)> {
+        let first = self.clone().n_misses(0).calculate();
+        let mut pps = Vec::with_capacity(max_misses + 1);
+        pps.push((0, first.pp()));
+
+        let attrs = first.attributes();
+
+        for n_misses in 1..=max_misses {
+            let pp = self
+                .clone()
+                .attributes(attrs.clone())
+                .n_misses(n_misses)
+                .calculate()
+                .pp();
+
+            pps.push((n_misses, pp));
+        }
+
+        pps
+    }
+
+    /// Approximate how much the pp change with the accuracy and the combo
+    /// around a score with the given accuracy (`0..=100`) and combo.
+    ///
+    /// Both partial derivatives are estimated through central differences
+    /// while all other parameters stay the same. Previously specified
+    /// accuracy and combo values are overwritten.
+    ///
+    /// The difficulty attributes are only calculated once and then re-used.
+    pub fn pp_gradient(self, acc: f64, combo: usize) -> PpGradient {
+        const ACC_STEP: f64 = 0.5;
+        const COMBO_STEP: usize = 1;
+
+        let attrs = self
+            .clone()
+            .accuracy(acc)
+            .combo(combo)
+            .calculate()
+            .attributes();
+        let max_combo = attrs.max_combo();
+        let combo = combo.min(max_combo);
+        let acc = acc.clamp(0.0, 100.0);
+
+        let pp = |acc: f64, combo: usize| {
+            self.clone()
+                .attributes(attrs.clone())
+                .accuracy(acc)
+                .combo(combo)
+                .calculate()
+                .pp()
+        };
+
+        let acc_low = (acc - ACC_STEP).max(0.0);
+        let acc_high = (acc + ACC_STEP).min(100.0);
+
+        let accuracy = if acc_high > acc_low {
+            (pp(acc_high, combo) - pp(acc_low, combo)) / (acc_high - acc_low)
+        } else {
+            0.0
+        };
+
+        let combo_low = combo.saturating_sub(COMBO_STEP);
+        let combo_high = (combo + COMBO_STEP).min(max_combo);
+
+        let combo = if combo_high > combo_low {
+            (pp(acc, combo_high) - pp(acc, combo_low)) / (combo_high - combo_low) as f64
+        } else {
+            0.0
+        };
+
+        PpGradient { accuracy, combo }
+    }
+
     /// Provide the result of a previous difficulty or performance calculation.
     /// If you already calculated the attributes for the current map-mod combination,
     /// be sure to put them in here so that they don't have to be recalculated.
@@ -141,6 +252,10 @@ impl<'map> AnyPP<'map> {
     }
 
     /// Provide parameters through a [`ScoreState`].
+    ///
+    /// Values can be overridden by calling the individual setters afterwards,
+    /// e.g. `.state(state).combo(max_combo)` to calculate an if-FC
+    /// with the same hitresults.
     #[inline]
     pub fn state(self, state: ScoreState) -> Self {
         match self {
@@ -151,6 +266,20 @@ impl<'map> AnyPP<'map> {
         }
     }
 
+    /// Specify how hitresults should be generated.
+    ///
+    /// Defauls to [`HitResultPriority::BestCase`].
+    /// Irrelevant for osu!catch.
+    #[inline]
+    pub fn hitresult_priority(self, priority: HitResultPriority) -> Self {
+        match self {
+            Self::Osu(o) => Self::Osu(o.hitresult_priority(priority)),
+            Self::Taiko(t) => Self::Taiko(t.hitresult_priority(priority)),
+            Self::Catch(_) => self,
+            Self::Mania(m) => Self::Mania(m.hitresult_priority(priority)),
+        }
+    }
+
     /// Set the accuracy between `0.0` and `100.0`.
     #[inline]
     pub fn accuracy(self, acc: f64) -> Self {
@@ -252,12 +381,16 @@ impl<'map> AnyPP<'map> {
 }
 
 /// While generating remaining hitresults, decide how they should be distributed.
+///
+/// See [`AnyPP::generate_state`] to inspect the generated hitresults.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum HitResultPriority {
     /// Prioritize good hitresults over bad ones
     BestCase,
     /// Prioritize bad hitresults over good ones
     WorstCase,
+    /// Distribute hitresults halfway between the best and the worst case
+    Balanced,
 }
 
 impl Default for HitResultPriority {
@@ -267,6 +400,83 @@ impl Default for HitResultPriority {
     }
 }
 
+/// Blend the hitresults of the best and worst case for [`HitResultPriority::Balanced`].
+///
+/// Hitresults must be ordered from best to worst. Counts that can't be split
+/// evenly are rounded in favor of the better hitresults so that the total stays the same.
+pub(crate) fn balance_hitresults<const N: usize>(
+    best: [usize; N],
+    worst: [usize; N],
+) -> [usize; N] {
+    let mut balanced = [0; N];
+    let mut odd = [false; N];
+
+    for i in 0..N {
+        let sum = best[i] + worst[i];
+        balanced[i] = sum / 2;
+        odd[i] = sum % 2 == 1;
+    }
+
+    let mut leftover = odd.iter().filter(|&&odd| odd).count() / 2;
+
+    for (count, _) in balanced.iter_mut().zip(odd).filter(|(_, odd)| *odd) {
+        if leftover == 0 {
+            break;
+        }
+
+        *count += 1;
+        leftover -= 1;
+    }
+
+    balanced
+}
+
+/// Approximate partial derivatives of the pp of a score, see [`AnyPP::pp_gradient`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PpGradient {
+    /// Change in pp per accuracy percent.
+    pub accuracy: f64,
+    /// Change in pp per additional combo.
+    pub combo: f64,
+}
+
+/// Error returned by `try_calculate` if the provided difficulty attributes
+/// were calculated with different mods than the performance calculator's.
+///
+/// Only mods that affect the difficulty attributes of the mode are compared.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ModsMismatch {
+    /// The mods of the difficulty attributes.
+    pub attributes: u32,
+    /// The mods of the performance calculator.
+    pub performance: u32,
+}
+
+impl ModsMismatch {
+    pub(crate) fn check(attributes: u32, performance: u32, mode: GameMode) -> Result<(), Self> {
+        if difficulty_mods(attributes, mode) == difficulty_mods(performance, mode) {
+            Ok(())
+        } else {
+            Err(Self {
+                attributes,
+                performance,
+            })
+        }
+    }
+}
+
+impl fmt::Display for ModsMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "attributes were calculated with mods {} but the performance calculation uses mods {}",
+            self.attributes, self.performance
+        )
+    }
+}
+
+impl StdError for ModsMismatch {}
+
 /// Abstract type to provide flexibility when passing difficulty attributes to a performance calculation.
 pub trait AttributeProvider {
     /// Provide the actual difficulty attributes.
@@ -314,3 +524,201 @@ impl_attr_provider!(Catch: CatchDifficultyAttributes, CatchPerformanceAttributes
 impl_attr_provider!(Mania: ManiaDifficultyAttributes, ManiaPerformanceAttributes);
 impl_attr_provider!(Osu: OsuDifficultyAttributes, OsuPerformanceAttributes);
 impl_attr_provider!(Taiko: TaikoDifficultyAttributes, TaikoPerformanceAttributes);
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn calculate(path: &str) -> PerformanceAttributes {
+        let map = Beatmap::from_path(path).unwrap();
+
+        AnyPP::new(&map)
+            .accuracy(97.5)
+            .combo(100)
+            .n_misses(1)
+            .calculate()
+    }
+
+    #[test]
+    fn osu_variant() {
+        let attrs = calculate("./maps/2785319.osu");
+
+        assert!(matches!(attrs, PerformanceAttributes::Osu(_)));
+        assert!(attrs.pp() > 0.0);
+        assert!((attrs.accuracy() - 0.975).abs() < 0.01);
+        assert_eq!(attrs.combo(), Some(100));
+    }
+
+    #[test]
+    fn taiko_variant() {
+        let attrs = calculate("./maps/1028484.osu");
+
+        assert!(matches!(attrs, PerformanceAttributes::Taiko(_)));
+        assert!(attrs.pp() > 0.0);
+        assert!((attrs.accuracy() - 0.975).abs() < 0.01);
+        assert_eq!(attrs.combo(), Some(100));
+    }
+
+    #[test]
+    fn catch_variant() {
+        let attrs = calculate("./maps/2118524.osu");
+
+        assert!(matches!(attrs, PerformanceAttributes::Catch(_)));
+        assert!(attrs.pp() > 0.0);
+        assert!((attrs.accuracy() - 0.975).abs() < 0.01);
+        assert_eq!(attrs.combo(), Some(100));
+    }
+
+    #[test]
+    fn mania_variant() {
+        let attrs = calculate("./maps/1974394.osu");
+
+        assert!(matches!(attrs, PerformanceAttributes::Mania(_)));
+        assert!(attrs.pp() > 0.0);
+        assert!((attrs.accuracy() - 0.975).abs() < 0.01);
+        assert_eq!(attrs.combo(), None);
+    }
+
+    #[test]
+    fn pp_by_misses() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let calc = AnyPP::new(&map).mods(8).accuracy(98.0);
+
+        let pps = calc.clone().pp_by_misses(10);
+
+        assert_eq!(pps.len(), 11);
+        assert_eq!(pps[0].1, calc.clone().calculate().pp());
+        assert_eq!(pps[3].1, calc.n_misses(3).calculate().pp());
+
+        for window in pps.windows(2) {
+            assert_eq!(window[0].0 + 1, window[1].0);
+            assert!(window[0].1 > window[1].1, "{:?}", window);
+        }
+    }
+
+    #[test]
+    fn pp_gradient() {
+        let maps = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        for path in maps {
+            let map = Beatmap::from_path(path).unwrap();
+            let gradient = AnyPP::new(&map).n_misses(1).pp_gradient(96.0, 300);
+
+            assert!(gradient.accuracy > 0.0, "{}: {:?}", path, gradient);
+            assert!(gradient.combo >= 0.0, "{}: {:?}", path, gradient);
+        }
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let gradient = AnyPP::new(&map).pp_gradient(100.0, 0);
+
+        assert!(gradient.accuracy > 0.0);
+        assert!(gradient.combo > 0.0);
+    }
+
+    #[test]
+    fn generate_state() {
+        let maps = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        for path in maps {
+            let map = Beatmap::from_path(path).unwrap();
+
+            for &priority in [HitResultPriority::BestCase, HitResultPriority::WorstCase].iter() {
+                let mut calc = AnyPP::new(&map)
+                    .mods(8)
+                    .accuracy(98.0)
+                    .n_misses(2)
+                    .hitresult_priority(priority);
+
+                let state = calc.generate_state();
+                let attrs = calc.clone().calculate();
+
+                assert_eq!(state.n_misses, 2, "{}", path);
+                assert_eq!(state.validate(&attrs.clone().into()), Ok(()), "{}", path);
+                assert!((attrs.accuracy() - 0.98).abs() < 0.01, "{}", path);
+
+                let from_state = AnyPP::new(&map)
+                    .attributes(attrs.clone())
+                    .mods(8)
+                    .state(state)
+                    .calculate();
+
+                assert_eq!(from_state.pp(), attrs.pp(), "{} {:?}", path, priority);
+            }
+        }
+    }
+
+    #[test]
+    fn mods_mismatch() {
+        let maps = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        for path in maps {
+            let map = Beatmap::from_path(path).unwrap();
+            let attrs = AnyPP::new(&map).mods(64).calculate();
+
+            let err = AnyPP::new(&map)
+                .attributes(attrs.clone())
+                .mods(0)
+                .try_calculate()
+                .unwrap_err();
+
+            assert_eq!(
+                err,
+                ModsMismatch {
+                    attributes: 64,
+                    performance: 0
+                }
+            );
+
+            // NC results in the same attributes as DT
+            let nightcore = AnyPP::new(&map)
+                .attributes(attrs)
+                .mods(64 + 512)
+                .try_calculate();
+
+            assert!(nightcore.is_ok(), "{}", path);
+        }
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        // HD only matters alongside FL
+        let hidden = AnyPP::new(&map).mods(8).calculate();
+
+        assert!(AnyPP::new(&map)
+            .attributes(hidden.clone())
+            .try_calculate()
+            .is_ok());
+
+        assert!(AnyPP::new(&map)
+            .attributes(hidden)
+            .mods(8 + 1024)
+            .try_calculate()
+            .is_err());
+
+        let flashlight = AnyPP::new(&map).mods(1024).calculate();
+
+        assert!(AnyPP::new(&map)
+            .attributes(flashlight)
+            .mods(8 + 1024)
+            .try_calculate()
+            .is_err());
+
+        // Without attributes there is nothing to mismatch
+        assert!(AnyPP::new(&map).mods(16).try_calculate().is_ok());
+    }
+}
@@ -0,0 +1,189 @@
+use std::borrow::Cow;
+
+use crate::{
+    mods::difficulty_mods, osu::OsuObjects, AnyStars, Beatmap, CatchStars, DifficultyAttributes,
+    GameMode, ManiaStars, OsuStars, TaikoStars,
+};
+
+/// Difficulty calculator for many mod combinations on the same map.
+///
+/// The map is converted only once and mod combinations that lead to the same
+/// difficulty, e.g. `NM` and `HD` in osu!taiko or `DT` and `NC`, are calculated only once.
+///
+/// On osu!standard maps, the hit objects including their slider paths are also
+/// created only once since they don't depend on the mods or the clock rate.
+/// Only stacking and the difficulty objects are processed for each combination.
+///
+/// For osu!taiko, osu!catch, and osu!mania, the hit objects are still processed
+/// for each combination. osu!catch applies HR's position offsets while creating
+/// its objects and neither osu!taiko nor osu!mania objects contain slider paths.
+///
+/// # Example
+///
+/// ```
+/// use rosu_pp::{Beatmap, GameMode, Recalculator};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+///
+/// let results = Recalculator::new(&map)
+///     .mode(GameMode::Taiko)
+///     .calculate(&[0, 8, 16, 64, 8 + 64]);
+///
+/// for attrs in results {
+///     println!("Stars: {}", attrs.stars());
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Recalculator<'map> {
+    map: Cow<'map, Beatmap>,
+    mode: GameMode,
+}
+
+impl<'map> Recalculator<'map> {
+    /// Create a new recalculator for the map in its own mode.
+    #[inline]
+    pub fn new(map: &'map Beatmap) -> Self {
+        Self {
+            map: Cow::Borrowed(map),
+            mode: map.mode,
+        }
+    }
+
+    /// If the map is an osu!standard map, convert it to another mode.
+    pub fn mode(self, mode: GameMode) -> Self {
+        if self.mode != GameMode::Osu || mode == GameMode::Osu {
+            return self;
+        }
+
+        let map = match self.map {
+            Cow::Borrowed(map) => map.convert_mode(mode),
+            Cow::Owned(map) => Cow::Owned(map.convert_mode(mode).into_owned()),
+        };
+
+        Self { map, mode }
+    }
+
+    /// Calculate the difficulty attributes for each of the given mods in the same order.
+    pub fn calculate(&self, mods: &[u32]) -> Vec<DifficultyAttributes> {
+        let map = self.map.as_ref();
+
+        let osu_objects = (self.mode == GameMode::Osu && !mods.is_empty())
+            .then(|| OsuObjects::new(map, map.hit_objects.len(), false));
+
+        let mut calculated: Vec<(u32, DifficultyAttributes)> = Vec::with_capacity(mods.len());
+
+        mods.iter()
+            .map(|&mods| {
+                let key = difficulty_mods(mods, self.mode);
+
+                let mut attrs = match calculated.iter().find(|(prev, _)| *prev == key) {
+                    Some((_, attrs)) => attrs.clone(),
+                    None => {
+                        let attrs = self.stars(osu_objects.as_ref()).mods(mods).calculate();
+                        calculated.push((key, attrs.clone()));
+
+                        attrs
+                    }
+                };
+
+                set_mods(&mut attrs, mods);
+
+                attrs
+            })
+            .collect()
+    }
+
+    fn stars<'a>(&'a self, osu_objects: Option<&'a OsuObjects>) -> AnyStars<'a> {
+        let map = self.map.as_ref();
+        let is_convert = map.mode != self.mode || matches!(self.map, Cow::Owned(_));
+
+        match self.mode {
+            GameMode::Osu => AnyStars::Osu(OsuStars {
+                objects: osu_objects,
+                ..OsuStars::new(map)
+            }),
+            GameMode::Taiko => AnyStars::Taiko(TaikoStars::new(map).is_convert(is_convert)),
+            GameMode::Catch => AnyStars::Catch(CatchStars::new(map)),
+            GameMode::Mania => AnyStars::Mania(ManiaStars::new(map).is_convert(is_convert)),
+        }
+    }
+}
+
+pub(crate) fn set_mods(attrs: &mut DifficultyAttributes, mods: u32) {
+    match attrs {
+        DifficultyAttributes::Osu(attrs) => attrs.mods = mods,
+        DifficultyAttributes::Taiko(attrs) => attrs.mods = mods,
+        DifficultyAttributes::Catch(attrs) => attrs.mods = mods,
+        DifficultyAttributes::Mania(attrs) => attrs.mods = mods,
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_individual_calculations() {
+        let osu = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let mania = Beatmap::from_path("./maps/1974394.osu").unwrap();
+
+        let mods = [0, 8, 16, 64, 512, 8 + 64];
+
+        let cases = [
+            (&osu, GameMode::Osu),
+            (&osu, GameMode::Taiko),
+            (&osu, GameMode::Catch),
+            (&osu, GameMode::Mania),
+            (&mania, GameMode::Mania),
+        ];
+
+        for &(map, mode) in cases.iter() {
+            let results = Recalculator::new(map).mode(mode).calculate(&mods);
+            assert_eq!(results.len(), mods.len());
+
+            for (&mods, attrs) in mods.iter().zip(results.iter()) {
+                let expected = AnyStars::new(map).mode(mode).mods(mods).calculate();
+
+                assert_eq!(attrs.stars(), expected.stars(), "{:?} +{}", mode, mods);
+                assert_eq!(
+                    attrs.max_combo(),
+                    expected.max_combo(),
+                    "{:?} +{}",
+                    mode,
+                    mods
+                );
+
+                let attrs_mods = match attrs {
+                    DifficultyAttributes::Osu(attrs) => attrs.mods,
+                    DifficultyAttributes::Taiko(attrs) => attrs.mods,
+                    DifficultyAttributes::Catch(attrs) => attrs.mods,
+                    DifficultyAttributes::Mania(attrs) => attrs.mods,
+                };
+
+                assert_eq!(attrs_mods, mods, "{:?}", mode);
+            }
+        }
+    }
+
+    #[test]
+    fn shared_osu_objects() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        // HR flips the shared objects and EZ changes their stacking
+        let mods = [0, 2, 16, 64, 16 + 64, 2 + 256, 1024];
+        let results = Recalculator::new(&map).calculate(&mods);
+
+        for (&mods, attrs) in mods.iter().zip(results) {
+            let expected = OsuStars::new(&map).mods(mods).calculate();
+
+            match attrs {
+                DifficultyAttributes::Osu(attrs) => assert_eq!(attrs, expected, "+{}", mods),
+                _ => panic!("expected osu!standard attributes"),
+            }
+        }
+    }
+}
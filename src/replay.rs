@@ -0,0 +1,331 @@
+use std::{error::Error as StdError, fmt, fs, io::Error as IoError, path::Path};
+
+use crate::{AnyPP, Beatmap, GameMode, ScoreState};
+
+/// Replays of osu!lazer are exported with versions starting at this value
+/// whereas osu!stable uses the date of the client build, e.g. `20230621`.
+const FIRST_LAZER_VERSION: u32 = 30_000_000;
+
+/// The score data of an `.osr` replay file.
+///
+/// Only the header of the file is parsed, the compressed replay frames are skipped.
+///
+/// # Example
+///
+/// ```no_run
+/// use rosu_pp::{Beatmap, Replay};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let replay = Replay::from_path("./replay.osr").unwrap();
+///
+/// let pp_result = replay.performance(&map).calculate();
+///
+/// println!("PP: {}", pp_result.pp());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+pub struct Replay {
+    /// The mode the score was set in.
+    pub mode: GameMode,
+    /// The version of the client that created the replay.
+    pub version: u32,
+    /// The MD5 hash of the map's `.osu` file as lowercase hex.
+    pub beatmap_md5: String,
+    /// The name of the player.
+    pub player_name: String,
+    /// The MD5 hash of the replay.
+    pub replay_md5: String,
+    /// Amount of 300s (fruits for osu!catch).
+    pub n300: usize,
+    /// Amount of 100s (droplets for osu!catch).
+    pub n100: usize,
+    /// Amount of 50s (tiny droplets for osu!catch).
+    pub n50: usize,
+    /// Amount of gekis (n320 for osu!mania).
+    pub n_geki: usize,
+    /// Amount of katus (tiny droplet misses for osu!catch / n200 for osu!mania).
+    pub n_katu: usize,
+    /// Amount of misses.
+    pub n_misses: usize,
+    /// The total score.
+    pub score: u32,
+    /// The maximum combo of the score.
+    pub max_combo: usize,
+    /// Whether the score is a full combo.
+    pub perfect: bool,
+    /// The mods as bit values.
+    pub mods: u32,
+}
+
+impl Replay {
+    /// Parse a replay from the content of an `.osr` file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = Reader(bytes);
+
+        let mode = reader.u8()?;
+        let mode = GameMode::from_int(mode).ok_or(ReplayError::InvalidMode(mode))?;
+
+        Ok(Self {
+            mode,
+            version: reader.u32()?,
+            beatmap_md5: reader.string()?,
+            player_name: reader.string()?,
+            replay_md5: reader.string()?,
+            n300: reader.u16()? as usize,
+            n100: reader.u16()? as usize,
+            n50: reader.u16()? as usize,
+            n_geki: reader.u16()? as usize,
+            n_katu: reader.u16()? as usize,
+            n_misses: reader.u16()? as usize,
+            score: reader.u32()?,
+            max_combo: reader.u16()? as usize,
+            perfect: reader.u8()? != 0,
+            mods: reader.u32()?,
+        })
+    }
+
+    /// Read and parse an `.osr` file.
+    ///
+    /// The file is read synchronously, even with an async feature enabled.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Whether the replay was created by osu!lazer rather than osu!stable.
+    ///
+    /// Note that the mods of osu!lazer replays only contain mods that exist on
+    /// osu!stable so e.g. whether classic mod was enabled can not be determined.
+    #[inline]
+    pub fn is_lazer(&self) -> bool {
+        self.version >= FIRST_LAZER_VERSION
+    }
+
+    /// Whether the replay was set on the given map, i.e. the MD5 hashes match.
+    ///
+    /// Returns `false` if the map has no hash, e.g. because it was constructed manually.
+    pub fn matches_map(&self, map: &Beatmap) -> bool {
+        matches!(map.md5(), Some(md5) if md5.eq_ignore_ascii_case(&self.beatmap_md5))
+    }
+
+    /// The hitresults and combo of the replay.
+    #[inline]
+    pub fn score_state(&self) -> ScoreState {
+        ScoreState {
+            max_combo: self.max_combo,
+            n_geki: self.n_geki,
+            n_katu: self.n_katu,
+            n300: self.n300,
+            n100: self.n100,
+            n50: self.n50,
+            n_misses: self.n_misses,
+        }
+    }
+
+    /// Create a performance calculator for the replay on the given map.
+    ///
+    /// The map is converted to the replay's mode if necessary, and the mods
+    /// and hitresults of the replay are already applied.
+    #[inline]
+    pub fn performance<'map>(&self, map: &'map Beatmap) -> AnyPP<'map> {
+        AnyPP::new(map)
+            .mode(self.mode)
+            .mods(self.mods)
+            .state(self.score_state())
+    }
+}
+
+/// Error when parsing a [`Replay`].
+#[derive(Debug)]
+#[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+pub enum ReplayError {
+    /// Some IO operation failed.
+    IoError(IoError),
+    /// The file ended before the header was parsed completely.
+    Truncated,
+    /// The mode byte does not belong to any mode.
+    InvalidMode(u8),
+    /// A string was neither empty nor valid UTF-8 with a length prefix.
+    InvalidString,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IoError(_) => f.write_str("IO error"),
+            Self::Truncated => f.write_str("replay file is incomplete"),
+            Self::InvalidMode(mode) => write!(f, "invalid mode {}", mode),
+            Self::InvalidString => f.write_str("invalid string in replay file"),
+        }
+    }
+}
+
+impl StdError for ReplayError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::IoError(err) => Some(err),
+            Self::Truncated | Self::InvalidMode(_) | Self::InvalidString => None,
+        }
+    }
+}
+
+impl From<IoError> for ReplayError {
+    #[inline]
+    fn from(err: IoError) -> Self {
+        Self::IoError(err)
+    }
+}
+
+struct Reader<'b>(&'b [u8]);
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], ReplayError> {
+        if self.0.len() < n {
+            return Err(ReplayError::Truncated);
+        }
+
+        let (bytes, rest) = self.0.split_at(n);
+        self.0 = rest;
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ReplayError> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ReplayError> {
+        self.take(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, ReplayError> {
+        self.take(4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn uleb128(&mut self) -> Result<usize, ReplayError> {
+        let mut value = 0;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.u8()?;
+
+            if shift >= usize::BITS {
+                return Err(ReplayError::InvalidString);
+            }
+
+            value |= ((byte & 0x7F) as usize) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+
+            shift += 7;
+        }
+    }
+
+    // Strings are either `0x00` if they're empty, or `0x0B`
+    // followed by the ULEB128 encoded length and the UTF-8 content
+    fn string(&mut self) -> Result<String, ReplayError> {
+        match self.u8()? {
+            0x00 => Ok(String::new()),
+            0x0B => {
+                let len = self.uleb128()?;
+                let bytes = self.take(len)?;
+
+                String::from_utf8(bytes.to_vec()).map_err(|_| ReplayError::InvalidString)
+            }
+            _ => Err(ReplayError::InvalidString),
+        }
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write_string(bytes: &mut Vec<u8>, s: &str) {
+        bytes.push(0x0B);
+        bytes.push(s.len() as u8);
+        bytes.extend_from_slice(s.as_bytes());
+    }
+
+    fn replay_bytes(mode: u8, version: u32, md5: &str, mods: u32) -> Vec<u8> {
+        let mut bytes = vec![mode];
+        bytes.extend_from_slice(&version.to_le_bytes());
+        write_string(&mut bytes, md5);
+        write_string(&mut bytes, "player");
+        bytes.push(0x00);
+
+        for count in [700_u16, 40, 3, 10, 20, 2].iter() {
+            bytes.extend_from_slice(&count.to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&12_345_678_u32.to_le_bytes());
+        bytes.extend_from_slice(&500_u16.to_le_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(&mods.to_le_bytes());
+
+        // Life bar, timestamp, and compressed frames are not parsed
+        bytes.extend_from_slice(&[0x00, 1, 2, 3, 4, 5, 6, 7, 8]);
+
+        bytes
+    }
+
+    #[test]
+    fn parse_header() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let md5 = map.md5().unwrap();
+
+        let bytes = replay_bytes(0, 20230621, &md5, 8 + 16);
+        let replay = Replay::from_bytes(&bytes).unwrap();
+
+        assert_eq!(replay.mode, GameMode::Osu);
+        assert_eq!(replay.player_name, "player");
+        assert_eq!(replay.replay_md5, "");
+        assert_eq!((replay.n300, replay.n100, replay.n50), (700, 40, 3));
+        assert_eq!((replay.n_geki, replay.n_katu, replay.n_misses), (10, 20, 2));
+        assert_eq!((replay.score, replay.max_combo), (12_345_678, 500));
+        assert_eq!(replay.mods, 8 + 16);
+        assert!(!replay.perfect);
+        assert!(!replay.is_lazer());
+        assert!(replay.matches_map(&map));
+        assert!(!replay.matches_map(&Beatmap::default()));
+
+        let lazer = Replay::from_bytes(&replay_bytes(0, 30000016, &md5, 0)).unwrap();
+        assert!(lazer.is_lazer());
+
+        let err = Replay::from_bytes(&bytes[..40]).unwrap_err();
+        assert!(matches!(err, ReplayError::Truncated));
+
+        let err = Replay::from_bytes(&replay_bytes(4, 20230621, &md5, 0)).unwrap_err();
+        assert!(matches!(err, ReplayError::InvalidMode(4)));
+    }
+
+    #[test]
+    fn performance() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let bytes = replay_bytes(0, 20230621, &map.md5().unwrap(), 8 + 16);
+        let replay = Replay::from_bytes(&bytes).unwrap();
+
+        let expected = AnyPP::new(&map)
+            .mods(8 + 16)
+            .combo(500)
+            .n300(700)
+            .n100(40)
+            .n50(3)
+            .n_misses(2)
+            .calculate();
+
+        assert_eq!(replay.performance(&map).calculate().pp(), expected.pp());
+
+        // Converts are handled through the replay's mode
+        let taiko = Replay::from_bytes(&replay_bytes(1, 20230621, "", 0)).unwrap();
+        let attrs = taiko.performance(&map).calculate();
+        assert!(matches!(attrs, crate::PerformanceAttributes::Taiko(_)));
+    }
+}
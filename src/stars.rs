@@ -1,7 +1,20 @@
+use std::{error::Error as StdError, fmt};
+
+#[cfg(feature = "parallel")]
+use std::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
 use crate::{
-    Beatmap, CatchStars, DifficultyAttributes, GameMode, ManiaStars, OsuStars, Strains, TaikoStars,
+    Beatmap, CatchStars, DifficultyAttributes, GameMode, ManiaStars, Mods, OsuStars, Strains,
+    TaikoStars, TimedStrains,
 };
 
+#[cfg(feature = "parallel")]
+use crate::Recalculator;
+
 /// Difficulty calculator on maps of any mode.
 ///
 /// # Example
@@ -111,6 +124,151 @@ impl<'map> AnyStars<'map> {
         }
     }
 
+    /// Calculate the difficulty attributes of each map for each of the given mods in parallel.
+    ///
+    /// The result contains one entry per map, in the same order as `maps`, and each entry
+    /// contains the attributes for each of `mods` in the same order.
+    ///
+    /// Maps are distributed across as many threads as the system provides. All mods of a
+    /// map are calculated on the same thread through a [`Recalculator`] so that mods with
+    /// the same difficulty are only calculated once.
+    #[cfg(feature = "parallel")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+    pub fn calculate_many(maps: &[&Beatmap], mods: &[u32]) -> Vec<Vec<DifficultyAttributes>> {
+        let n_threads = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(maps.len());
+
+        let calculate = |map: &Beatmap| Recalculator::new(map).calculate(mods);
+
+        if n_threads <= 1 {
+            return maps.iter().map(|map| calculate(map)).collect();
+        }
+
+        let next = AtomicUsize::new(0);
+
+        let mut results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..n_threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+
+                        loop {
+                            let idx = next.fetch_add(1, Ordering::Relaxed);
+
+                            match maps.get(idx) {
+                                Some(map) => results.push((idx, calculate(map))),
+                                None => break results,
+                            }
+                        }
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        results.sort_unstable_by_key(|(idx, _)| *idx);
+
+        results.into_iter().map(|(_, attrs)| attrs).collect()
+    }
+
+    /// Same as [`AnyStars::calculate`] but first checks that the map's stats
+    /// and the clock rate are finite.
+    ///
+    /// Malformed maps may contain e.g. a NaN OD which would otherwise
+    /// silently lead to NaN stars.
+    pub fn calculate_strict(self) -> Result<DifficultyAttributes, NonFiniteStat> {
+        let (map, mods, clock_rate) = match &self {
+            Self::Osu(o) => (o.map, o.mods, o.clock_rate),
+            Self::Taiko(t) => (t.map.as_ref(), t.mods, t.clock_rate),
+            Self::Catch(f) => (f.map, f.mods, f.clock_rate),
+            Self::Mania(m) => (m.map.as_ref(), m.mods, m.clock_rate),
+        };
+
+        let clock_rate = clock_rate.unwrap_or_else(|| mods.clock_rate());
+
+        let stats = [
+            ("AR", map.ar as f64),
+            ("OD", map.od as f64),
+            ("CS", map.cs as f64),
+            ("HP", map.hp as f64),
+            ("clock rate", clock_rate),
+            ("slider multiplier", map.slider_mult),
+            ("tick rate", map.tick_rate),
+        ];
+
+        if let Some((stat, value)) = stats.iter().copied().find(|(_, value)| !value.is_finite()) {
+            return Err(NonFiniteStat { stat, value });
+        }
+
+        Ok(self.calculate())
+    }
+
+    /// Consume the difficulty calculator and find the strain section with the highest strain.
+    ///
+    /// Returns the section's start time in ms, unaffected by the clock rate, alongside its strain.
+    /// The strain is the sum of aim and speed for osu!standard, the combined strain for osu!taiko,
+    /// the movement strain for osu!catch, and the strain for osu!mania.
+    ///
+    /// Returns `None` if the map has too few objects to produce strains.
+    pub fn hardest_section(self) -> Option<(f64, f64)> {
+        let timed = self.strains_with_time();
+
+        let combined = match timed.strains {
+            Strains::Osu(strains) => strains
+                .aim
+                .iter()
+                .zip(strains.speed.iter())
+                .map(|(aim, speed)| aim + speed)
+                .collect(),
+            Strains::Taiko(strains) => strains.combined,
+            Strains::Catch(strains) => strains.movement,
+            Strains::Mania(strains) => strains.strains,
+        };
+
+        let (idx, strain) =
+            combined
+                .into_iter()
+                .enumerate()
+                .fold(None, |hardest, (i, strain)| match hardest {
+                    Some((_, max)) if max >= strain => hardest,
+                    _ => Some((i, strain)),
+                })?;
+
+        Some((timed.start_time + idx as f64 * timed.section_len, strain))
+    }
+
+    /// Same as [`strains`](AnyStars::strains) but alongside the song time of the sections,
+    /// i.e. the clock rate is already applied.
+    ///
+    /// The start time is `0.0` if the map has too few objects to produce strains.
+    pub fn strains_with_time(self) -> TimedStrains {
+        let strains = self.clone().strains();
+        let section_len = strains.section_len();
+
+        let (first_section_end, mods, clock_rate) = match &self {
+            Self::Osu(o) => (o.first_section_end(section_len), o.mods, o.clock_rate),
+            Self::Taiko(t) => (t.first_section_end(section_len), t.mods, t.clock_rate),
+            Self::Catch(f) => (f.first_section_end(section_len), f.mods, f.clock_rate),
+            Self::Mania(m) => (m.first_section_end(section_len), m.mods, m.clock_rate),
+        };
+
+        let clock_rate = clock_rate.unwrap_or_else(|| mods.clock_rate());
+
+        // Section `i` covers the time range `(end - len, end]`
+        let start_time = first_section_end.map_or(0.0, |end| end - section_len);
+
+        TimedStrains {
+            start_time: start_time * clock_rate,
+            section_len: section_len * clock_rate,
+            strains,
+        }
+    }
+
     /// Consume the difficulty calculator and calculate
     /// skill strains for the given parameters.
     ///
@@ -124,4 +282,250 @@ impl<'map> AnyStars<'map> {
             Self::Mania(m) => Strains::Mania(m.strains()),
         }
     }
+
+    /// Same as [`strains`](AnyStars::strains) but each strain peak
+    /// covers `section_len` ms instead of the mode's default.
+    #[inline]
+    pub fn strains_with_section_len(self, section_len: f64) -> Strains {
+        match self {
+            Self::Osu(o) => Strains::Osu(o.strains_with_section_len(section_len)),
+            Self::Taiko(t) => Strains::Taiko(t.strains_with_section_len(section_len)),
+            Self::Catch(f) => Strains::Catch(f.strains_with_section_len(section_len)),
+            Self::Mania(m) => Strains::Mania(m.strains_with_section_len(section_len)),
+        }
+    }
+}
+
+/// Error returned by [`AnyStars::calculate_strict`] if a map stat is NaN or infinite.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NonFiniteStat {
+    /// The name of the stat, e.g. `"OD"`.
+    pub stat: &'static str,
+    /// The stat's value as specified in the map or calculator.
+    pub value: f64,
+}
+
+impl fmt::Display for NonFiniteStat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "map stat {} is not finite ({})", self.stat, self.value)
+    }
+}
+
+impl StdError for NonFiniteStat {}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BeatmapExt;
+
+    #[test]
+    fn beatmap_shortcut() {
+        let maps = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        for path in maps {
+            let map = Beatmap::from_path(path).unwrap();
+
+            let shortcut = map.stars().mods(64).calculate();
+            let explicit = AnyStars::new(&map).mods(64).calculate();
+
+            assert_eq!(shortcut.stars(), explicit.stars(), "{}", path);
+            assert_eq!(shortcut.max_combo(), explicit.max_combo(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn custom_section_len() {
+        let maps = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        let no_nan = |strains: &Strains| match strains {
+            Strains::Osu(s) => s.aim.iter().chain(&s.speed).all(|x| !x.is_nan()),
+            Strains::Taiko(s) => s.combined.iter().all(|x| !x.is_nan()),
+            Strains::Catch(s) => s.movement.iter().all(|x| !x.is_nan()),
+            Strains::Mania(s) => s.strains.iter().all(|x| !x.is_nan()),
+        };
+
+        for path in maps {
+            let map = Beatmap::from_path(path).unwrap();
+
+            let default = AnyStars::new(&map).strains();
+            let half_len = default.section_len() / 2.0;
+            let half = AnyStars::new(&map).strains_with_section_len(half_len);
+
+            assert_eq!(half.section_len(), half_len, "{}", path);
+            assert!(half.len().abs_diff(2 * default.len()) <= 2, "{}", path);
+            assert!(no_nan(&half), "{}", path);
+
+            for section_len in [0.0, -5.0, f64::NAN] {
+                let tiny = AnyStars::new(&map).strains_with_section_len(section_len);
+
+                assert_eq!(tiny.section_len(), 1.0, "{}", path);
+                assert!(tiny.len() > half.len(), "{}", path);
+                assert!(no_nan(&tiny), "{}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn strict_calculation() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let strict = AnyStars::new(&map).mods(64).calculate_strict().unwrap();
+        let lenient = AnyStars::new(&map).mods(64).calculate();
+        assert_eq!(strict.stars(), lenient.stars());
+
+        let malformed = Beatmap {
+            od: f32::NAN,
+            ..map.clone()
+        };
+
+        let err = AnyStars::new(&malformed).calculate_strict().unwrap_err();
+        assert_eq!(err.stat, "OD");
+        assert!(err.value.is_nan());
+        assert_eq!(err.to_string(), "map stat OD is not finite (NaN)");
+
+        let err = AnyStars::new(&map)
+            .clock_rate(f64::INFINITY)
+            .calculate_strict()
+            .unwrap_err();
+        assert_eq!(err.stat, "clock rate");
+
+        for mode in [GameMode::Taiko, GameMode::Catch].iter().copied() {
+            let err = AnyStars::new(&malformed)
+                .mode(mode)
+                .calculate_strict()
+                .unwrap_err();
+            assert_eq!(err.stat, "OD", "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn hardest_section() {
+        let maps = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        for path in maps.iter() {
+            let map = Beatmap::from_path(path).unwrap();
+
+            for &mods in [0, 64].iter() {
+                let (time, strain) = AnyStars::new(&map).mods(mods).hardest_section().unwrap();
+
+                let strains = AnyStars::new(&map).mods(mods).strains();
+                let section_len = strains.section_len() * mods.clock_rate();
+
+                let max = match strains {
+                    Strains::Osu(strains) => strains
+                        .aim
+                        .iter()
+                        .zip(strains.speed.iter())
+                        .map(|(aim, speed)| aim + speed)
+                        .fold(0.0, f64::max),
+                    Strains::Taiko(strains) => strains.combined.iter().copied().fold(0.0, f64::max),
+                    Strains::Catch(strains) => strains.movement.iter().copied().fold(0.0, f64::max),
+                    Strains::Mania(strains) => strains.strains.iter().copied().fold(0.0, f64::max),
+                };
+
+                assert_eq!(strain, max, "{} +{}", path, mods);
+
+                let first = map.hit_objects.first().unwrap().start_time;
+                let last = map.hit_objects.last().unwrap().start_time;
+                assert!(
+                    time >= first - section_len && time <= last,
+                    "{} +{}",
+                    path,
+                    mods
+                );
+
+                // The hardest section contains an object
+                assert!(
+                    map.hit_objects
+                        .iter()
+                        .any(|h| h.start_time > time && h.start_time <= time + section_len),
+                    "{} +{}",
+                    path,
+                    mods
+                );
+            }
+        }
+
+        let empty = Beatmap::default();
+        assert!(AnyStars::new(&empty).hardest_section().is_none());
+    }
+
+    #[test]
+    fn strains_with_time() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        for &mods in [0, 64, 256].iter() {
+            let timed = AnyStars::new(&map).mods(mods).strains_with_time();
+            let strains = AnyStars::new(&map).mods(mods).strains();
+
+            let clock_rate = mods.clock_rate();
+            assert_eq!(timed.section_len, strains.section_len() * clock_rate);
+
+            let first = map.hit_objects.first().unwrap().start_time;
+            // The first section ends after the first object
+            assert!(timed.time(1) >= first);
+
+            let aim = match timed.strains {
+                Strains::Osu(ref strains) => strains.aim.clone(),
+                _ => unreachable!(),
+            };
+
+            let points: Vec<_> = timed.points(&aim).collect();
+            assert_eq!(points.len(), aim.len());
+            assert_eq!(points[3].time, timed.time(3));
+            assert_eq!(points[3].value, aim[3]);
+            assert!((points[1].time - points[0].time - timed.section_len).abs() < 1e-9);
+        }
+
+        let empty = Beatmap::default();
+        assert_eq!(AnyStars::new(&empty).strains_with_time().start_time, 0.0);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn calculate_many() {
+        let maps: Vec<_> = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ]
+        .iter()
+        .map(|path| Beatmap::from_path(path).unwrap())
+        .collect();
+
+        let map_refs: Vec<_> = maps.iter().collect();
+        let mods = [0, 8 + 64, 16];
+
+        let results = AnyStars::calculate_many(&map_refs, &mods);
+        assert_eq!(results.len(), maps.len());
+
+        for (map, attrs) in maps.iter().zip(results) {
+            assert_eq!(attrs.len(), mods.len());
+
+            for (&mods, attrs) in mods.iter().zip(attrs) {
+                let expected = AnyStars::new(map).mods(mods).calculate();
+                assert_eq!(attrs.stars(), expected.stars());
+                assert_eq!(attrs.max_combo(), expected.max_combo());
+            }
+        }
+
+        assert!(AnyStars::calculate_many(&[], &mods).is_empty());
+    }
 }
@@ -70,9 +70,12 @@ impl TaikoGradualDifficultyAttributes {
             rhythm: 0.0,
             colour: 0.0,
             peak: 0.0,
+            finisher_difficulty: 0.0,
             hit_window,
             stars: 0.0,
             max_combo: 0,
+            n_finishers: 0,
+            mods,
         };
 
         if map.hit_objects.len() < 2 {
@@ -87,8 +90,11 @@ impl TaikoGradualDifficultyAttributes {
             };
         }
 
-        attrs.max_combo += map.hit_objects[0].is_circle() as usize;
-        attrs.max_combo += map.hit_objects[1].is_circle() as usize;
+        for (h, _) in map.taiko_objects().take(2) {
+            attrs.max_combo += h.is_hit as usize;
+            attrs.n_finishers += h.is_finisher as usize;
+        }
+
         let mut total_hits = attrs.max_combo;
 
         let mut diff_objects = map
@@ -155,6 +161,7 @@ impl Iterator for TaikoGradualDifficultyAttributes {
 
             if borrowed.base.is_hit {
                 self.attrs.max_combo += 1;
+                self.attrs.n_finishers += borrowed.base.is_finisher as usize;
 
                 break;
             }
@@ -165,12 +172,14 @@ impl Iterator for TaikoGradualDifficultyAttributes {
             mut rhythm_rating,
             mut stamina_rating,
             mut combined_rating,
+            mut finisher_rating,
         } = self.peaks.clone().difficulty_values();
 
         colour_rating *= DIFFICULTY_MULTIPLIER;
         rhythm_rating *= DIFFICULTY_MULTIPLIER;
         stamina_rating *= DIFFICULTY_MULTIPLIER;
         combined_rating *= DIFFICULTY_MULTIPLIER;
+        finisher_rating *= DIFFICULTY_MULTIPLIER;
 
         let mut star_rating = rescale(combined_rating * 1.4);
 
@@ -190,6 +199,7 @@ impl Iterator for TaikoGradualDifficultyAttributes {
         self.attrs.colour = colour_rating;
         self.attrs.rhythm = rhythm_rating;
         self.attrs.peak = combined_rating;
+        self.attrs.finisher_difficulty = finisher_rating;
         self.attrs.stars = star_rating;
 
         Some(self.attrs.clone())
@@ -215,6 +225,7 @@ impl Iterator for TaikoGradualDifficultyAttributes {
 
                 if borrowed.base.is_hit {
                     self.attrs.max_combo += 1;
+                    self.attrs.n_finishers += borrowed.base.is_finisher as usize;
 
                     break;
                 }
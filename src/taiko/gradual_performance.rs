@@ -7,6 +7,7 @@ use super::{TaikoGradualDifficultyAttributes, TaikoPerformanceAttributes};
 ///
 /// This struct is used for [`TaikoGradualPerformanceAttributes`].
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaikoScoreState {
     /// Maximum combo that the score has had so far.
     /// **Not** the maximum possible combo of the map so far.
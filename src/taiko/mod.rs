@@ -11,7 +11,10 @@ use std::{borrow::Cow, cell::RefCell, rc::Rc};
 
 pub use self::{gradual_difficulty::*, gradual_performance::*, pp::*};
 
-use crate::{beatmap::BeatmapHitWindows, Beatmap, GameMode, Mods, OsuStars};
+use crate::{
+    beatmap::BeatmapHitWindows, Beatmap, GameMode, Mods, OsuStars, PerformanceBreakdown,
+    PerformanceComponent,
+};
 
 use self::{
     colours::ColourDifficultyPreprocessor,
@@ -21,6 +24,8 @@ use self::{
 };
 
 const SECTION_LEN: usize = 400;
+// Lower bound for custom section lengths so that sections always advance.
+const MIN_SECTION_LEN: f64 = 1.0;
 
 const DIFFICULTY_MULTIPLIER: f64 = 1.35;
 
@@ -44,10 +49,10 @@ const DIFFICULTY_MULTIPLIER: f64 = 1.35;
 /// ```
 #[derive(Clone, Debug)]
 pub struct TaikoStars<'map> {
-    map: Cow<'map, Beatmap>,
-    mods: u32,
+    pub(crate) map: Cow<'map, Beatmap>,
+    pub(crate) mods: u32,
     passed_objects: Option<usize>,
-    clock_rate: Option<f64>,
+    pub(crate) clock_rate: Option<f64>,
     is_convert: bool,
 }
 
@@ -120,20 +125,23 @@ impl<'map> TaikoStars<'map> {
             .hit_windows();
 
         let is_convert = self.is_convert || matches!(self.map, Cow::Owned(_));
+        let mods = self.mods;
 
-        let (peaks, max_combo) = calculate_skills(self);
+        let (peaks, max_combo, n_finishers) = calculate_skills(self, SECTION_LEN as f64);
 
         let PeaksDifficultyValues {
             mut colour_rating,
             mut rhythm_rating,
             mut stamina_rating,
             mut combined_rating,
+            mut finisher_rating,
         } = peaks.difficulty_values();
 
         colour_rating *= DIFFICULTY_MULTIPLIER;
         rhythm_rating *= DIFFICULTY_MULTIPLIER;
         stamina_rating *= DIFFICULTY_MULTIPLIER;
         combined_rating *= DIFFICULTY_MULTIPLIER;
+        finisher_rating *= DIFFICULTY_MULTIPLIER;
 
         let mut star_rating = rescale(combined_rating * 1.4);
 
@@ -154,9 +162,12 @@ impl<'map> TaikoStars<'map> {
             rhythm: rhythm_rating,
             colour: colour_rating,
             peak: combined_rating,
+            finisher_difficulty: finisher_rating,
             hit_window,
             stars: star_rating,
             max_combo,
+            n_finishers,
+            mods,
         }
     }
 
@@ -165,26 +176,118 @@ impl<'map> TaikoStars<'map> {
     /// Suitable to plot the difficulty of a map over time.
     #[inline]
     pub fn strains(self) -> TaikoStrains {
-        let (peaks, _) = calculate_skills(self);
+        self.strains_with_section_len(SECTION_LEN as f64)
+    }
+
+    /// Same as [`strains`](TaikoStars::strains) but each strain peak covers
+    /// `section_len` ms instead of the default 400ms.
+    ///
+    /// The section length is clamped to be at least 1ms.
+    pub fn strains_with_section_len(self, section_len: f64) -> TaikoStrains {
+        let section_len = section_len.max(MIN_SECTION_LEN);
+        let (peaks, ..) = calculate_skills(self, section_len);
 
         let PeaksRaw {
             colour,
             rhythm,
             stamina,
+            combined,
         } = peaks.into_raw();
 
         TaikoStrains {
-            section_len: SECTION_LEN as f64,
+            section_len,
             color: colour,
             rhythm,
             stamina,
+            combined,
         }
     }
+
+    /// Calculate the strain of each skill for every hit object.
+    ///
+    /// Contrary to [`strains`](TaikoStars::strains), which only keeps the peak of each
+    /// section, this is suitable to find the hardest objects of a map or to draw
+    /// a heatmap with per-object precision.
+    pub fn object_strains(self) -> TaikoObjectStrains {
+        let DifficultyObjects {
+            diff_objects,
+            n_objects,
+            ..
+        } = difficulty_objects(&self);
+
+        let times: Vec<_> = self
+            .map
+            .taiko_objects()
+            .take(n_objects)
+            .map(|(_, start_time)| start_time)
+            .collect();
+
+        let mut peaks = Peaks::new();
+
+        // The first two objects don't generate a strain
+        let init = || {
+            let mut strains = Vec::with_capacity(n_objects);
+            strains.extend(times.iter().take(2).map(|_| 0.0));
+
+            strains
+        };
+
+        let mut color = init();
+        let mut rhythm = init();
+        let mut stamina = init();
+        let mut combined = init();
+
+        for hit_object in diff_objects.all.iter() {
+            peaks.process(&hit_object.borrow(), &diff_objects);
+
+            let (curr_colour, curr_rhythm, curr_stamina) = peaks.curr_strains();
+
+            color.push(curr_colour);
+            rhythm.push(curr_rhythm);
+            stamina.push(curr_stamina);
+            combined.push(Peaks::combined_peak(curr_colour, curr_rhythm, curr_stamina));
+        }
+
+        TaikoObjectStrains {
+            times,
+            color,
+            rhythm,
+            stamina,
+            combined,
+        }
+    }
+
+    /// End time of the first strain section, adjusted by the clock rate.
+    pub(crate) fn first_section_end(&self, section_len: f64) -> Option<f64> {
+        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+
+        // The first two objects don't have a difficulty object
+        self.map
+            .taiko_objects()
+            .nth(2)
+            .map(|(_, start_time)| (start_time / clock_rate / section_len).ceil() * section_len)
+    }
+
+    /// The start time and hit type of each note, `true` for kats and `false` for dons.
+    ///
+    /// Kats are notes with a clap or whistle hitsound, which includes converted objects.
+    /// Drumrolls and swells are skipped.
+    pub fn hit_types(self) -> Vec<(f64, bool)> {
+        let take = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+
+        self.map
+            .taiko_objects()
+            .filter(|(h, _)| h.is_hit)
+            .take(take)
+            .map(|(h, start_time)| (start_time, h.is_rim))
+            .collect()
+    }
 }
 
 /// The result of calculating the strains on a osu!taiko map.
 /// Suitable to plot the difficulty of a map over time.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaikoStrains {
     /// Time in ms inbetween two strains.
     pub section_len: f64,
@@ -194,6 +297,12 @@ pub struct TaikoStrains {
     pub rhythm: Vec<f64>,
     /// Strain peaks of the stamina skill.
     pub stamina: Vec<f64>,
+    /// Weighted combination of the color, rhythm, and stamina peaks per section.
+    ///
+    /// These are the section values from which the star rating is built i.e.
+    /// [`TaikoDifficultyAttributes::peak`] is their decaying weighted sum
+    /// scaled by the difficulty multiplier.
+    pub combined: Vec<f64>,
 }
 
 impl TaikoStrains {
@@ -205,7 +314,56 @@ impl TaikoStrains {
     }
 }
 
-fn calculate_skills(params: TaikoStars<'_>) -> (Peaks, usize) {
+/// The strain of each skill for every hit object of an osu!taiko map,
+/// see [`TaikoStars::object_strains`].
+///
+/// All vectors contain one value per hit object, including drumrolls and swells.
+/// The first two objects have no strain so their values are always `0.0`.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaikoObjectStrains {
+    /// Start time in ms of each hit object as specified in the map,
+    /// i.e. the clock rate is not applied.
+    pub times: Vec<f64>,
+    /// Strains of the color skill.
+    pub color: Vec<f64>,
+    /// Strains of the rhythm skill.
+    pub rhythm: Vec<f64>,
+    /// Strains of the stamina skill.
+    pub stamina: Vec<f64>,
+    /// Weighted combination of the color, rhythm, and stamina strains,
+    /// see [`TaikoStrains::combined`].
+    pub combined: Vec<f64>,
+}
+
+impl TaikoObjectStrains {
+    /// Returns the number of hit objects.
+    #[inline]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+}
+
+fn calculate_skills(params: TaikoStars<'_>, section_len: f64) -> (Peaks, usize, usize) {
+    let DifficultyObjects {
+        diff_objects,
+        max_combo,
+        n_finishers,
+        ..
+    } = difficulty_objects(&params);
+
+    let mut peaks = Peaks::new();
+    peaks.set_section_len(section_len);
+
+    for hit_object in diff_objects.all.iter() {
+        peaks.process(&hit_object.borrow(), &diff_objects);
+    }
+
+    (peaks, max_combo, n_finishers)
+}
+
+fn difficulty_objects(params: &TaikoStars<'_>) -> DifficultyObjects {
     let TaikoStars {
         map,
         mods,
@@ -217,8 +375,9 @@ fn calculate_skills(params: TaikoStars<'_>) -> (Peaks, usize) {
     let mut take = passed_objects.unwrap_or(map.hit_objects.len());
     let clock_rate = clock_rate.unwrap_or_else(|| mods.clock_rate());
 
-    let mut peaks = Peaks::new();
+    let mut n_objects = 0;
     let mut max_combo = 0;
+    let mut n_finishers = 0;
 
     let mut diff_objects = map
         .taiko_objects()
@@ -229,9 +388,12 @@ fn calculate_skills(params: TaikoStars<'_>) -> (Peaks, usize) {
                 }
 
                 max_combo += 1;
+                n_finishers += h.is_finisher as usize;
                 take -= 1;
             }
 
+            n_objects += 1;
+
             true
         })
         .skip(2)
@@ -269,11 +431,20 @@ fn calculate_skills(params: TaikoStars<'_>) -> (Peaks, usize) {
 
     ColourDifficultyPreprocessor::process_and_assign(&mut diff_objects);
 
-    for hit_object in diff_objects.all.iter() {
-        peaks.process(&hit_object.borrow(), &diff_objects);
+    DifficultyObjects {
+        diff_objects,
+        n_objects,
+        max_combo,
+        n_finishers,
     }
+}
 
-    (peaks, max_combo)
+struct DifficultyObjects {
+    diff_objects: ObjectLists,
+    // Including the first two objects which have no difficulty object
+    n_objects: usize,
+    max_combo: usize,
+    n_finishers: usize,
 }
 
 #[inline]
@@ -287,6 +458,7 @@ fn rescale(stars: f64) -> f64 {
 
 /// The result of a difficulty calculation on an osu!taiko map.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaikoDifficultyAttributes {
     /// The difficulty corresponding to the stamina skill.
     pub stamina: f64,
@@ -296,12 +468,19 @@ pub struct TaikoDifficultyAttributes {
     pub colour: f64,
     /// The difficulty corresponding to the hardest parts of the map.
     pub peak: f64,
+    /// The part of [`peak`](TaikoDifficultyAttributes::peak) that stems
+    /// from strain sections containing finishers i.e. big dons and kats.
+    pub finisher_difficulty: f64,
     /// The perceived hit window for an n300 inclusive of rate-adjusting mods (DT/HT/etc)
     pub hit_window: f64,
     /// The final star rating.
     pub stars: f64,
     /// The maximum combo.
     pub max_combo: usize,
+    /// The amount of finishers i.e. big dons and kats.
+    pub n_finishers: usize,
+    /// The mods that the attributes were calculated with.
+    pub mods: u32,
 }
 
 impl TaikoDifficultyAttributes {
@@ -314,6 +493,7 @@ impl TaikoDifficultyAttributes {
 
 /// The result of a performance calculation on an osu!taiko map.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaikoPerformanceAttributes {
     /// The difficulty attributes that were used for the performance calculation
     pub difficulty: TaikoDifficultyAttributes,
@@ -324,7 +504,14 @@ pub struct TaikoPerformanceAttributes {
     /// The strain portion of the final pp.
     pub pp_difficulty: f64,
     /// Scaled miss count based on total hits.
+    ///
+    /// Only misses break the combo in osu!taiko so unlike osu!standard
+    /// the combo is not considered; a score without misses has `0.0`.
     pub effective_miss_count: f64,
+    /// The accuracy between `0.0` and `1.0` of the hitresults that were used.
+    pub accuracy: f64,
+    /// The combo of the score that was used.
+    pub combo: usize,
 }
 
 impl TaikoPerformanceAttributes {
@@ -340,6 +527,18 @@ impl TaikoPerformanceAttributes {
         self.pp
     }
 
+    /// Return the portions of the final pp per skill.
+    #[inline]
+    pub fn breakdown(&self) -> PerformanceBreakdown {
+        PerformanceBreakdown {
+            pp: self.pp,
+            components: vec![
+                (PerformanceComponent::Difficulty, self.pp_difficulty),
+                (PerformanceComponent::Accuracy, self.pp_acc),
+            ],
+        }
+    }
+
     /// Return the maximum combo of the map.
     #[inline]
     pub fn max_combo(&self) -> usize {
@@ -362,6 +561,7 @@ impl<'map> From<OsuStars<'map>> for TaikoStars<'map> {
             mods,
             passed_objects,
             clock_rate,
+            ..
         } = osu;
 
         Self {
@@ -373,3 +573,132 @@ impl<'map> From<OsuStars<'map>> for TaikoStars<'map> {
         }
     }
 }
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::HitSound;
+
+    #[test]
+    fn combined_strains() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+
+        let strains = TaikoStars::new(&map).strains();
+        let attrs = TaikoStars::new(&map).calculate();
+
+        assert_eq!(strains.combined.len(), strains.len());
+        assert_eq!(strains.combined.len(), strains.rhythm.len());
+        assert_eq!(strains.combined.len(), strains.stamina.len());
+
+        let max = strains.combined.iter().copied().fold(0.0, f64::max);
+
+        // The weighted sum contains the hardest section with weight 1.0
+        assert!(max > 0.0);
+        assert!(max * DIFFICULTY_MULTIPLIER <= attrs.peak);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        let strains = TaikoStars::new(&map).strains();
+
+        let json = serde_json::to_string(&strains).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        for key in ["section_len", "color", "rhythm", "stamina", "combined"] {
+            assert!(value.get(key).is_some(), "{}", key);
+        }
+
+        let deserialized: TaikoStrains = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized.section_len, strains.section_len);
+        assert_eq!(deserialized.color, strains.color);
+        assert_eq!(deserialized.rhythm, strains.rhythm);
+        assert_eq!(deserialized.stamina, strains.stamina);
+        assert_eq!(deserialized.combined, strains.combined);
+    }
+
+    #[test]
+    fn hit_types() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        let hit_types = TaikoStars::new(&map).hit_types();
+
+        let expected: Vec<_> = map
+            .hit_objects
+            .iter()
+            .zip(map.sounds.iter())
+            .filter(|(h, _)| h.is_circle())
+            .map(|(h, sound)| (h.start_time, sound.clap() || sound.whistle()))
+            .collect();
+
+        assert_eq!(hit_types, expected);
+        assert!(hit_types.iter().any(|(_, is_kat)| *is_kat));
+        assert!(hit_types.iter().any(|(_, is_kat)| !*is_kat));
+
+        let passed = TaikoStars::new(&map).passed_objects(10).hit_types();
+        assert_eq!(passed[..], hit_types[..10]);
+
+        let converted = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let converted_types = TaikoStars::new(&converted).hit_types();
+        assert!(converted_types.iter().any(|(_, is_kat)| *is_kat));
+    }
+
+    #[test]
+    fn finishers() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        let attrs = TaikoStars::new(&map).calculate();
+
+        let expected = map
+            .hit_objects
+            .iter()
+            .zip(map.sounds.iter())
+            .filter(|(h, sound)| h.is_circle() && sound.finish())
+            .count();
+
+        assert_eq!(attrs.n_finishers, 52);
+        assert_eq!(attrs.n_finishers, expected);
+        assert!(attrs.finisher_difficulty > 0.0);
+        assert!(attrs.finisher_difficulty < attrs.peak);
+
+        let gradual = TaikoGradualDifficultyAttributes::new(&map, 0)
+            .last()
+            .unwrap();
+
+        assert_eq!(gradual.n_finishers, attrs.n_finishers);
+        assert!((gradual.finisher_difficulty - attrs.finisher_difficulty).abs() < 1e-10);
+
+        let partial = TaikoStars::new(&map).passed_objects(10).calculate();
+        assert!(partial.n_finishers <= 10);
+
+        let converted = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let converted_attrs = TaikoStars::new(&converted).calculate();
+        assert!(converted_attrs.finisher_difficulty <= converted_attrs.peak);
+    }
+
+    #[test]
+    fn object_strains() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+
+        let objects = TaikoStars::new(&map).object_strains();
+        let peaks = TaikoStars::new(&map).strains();
+
+        assert_eq!(objects.len(), map.hit_objects.len());
+        assert_eq!(objects.rhythm.len(), objects.len());
+        assert_eq!(objects.combined.len(), objects.len());
+        assert_eq!(objects.stamina[..2], [0.0, 0.0]);
+        assert_eq!(objects.times[2], map.hit_objects[2].start_time);
+
+        // Section peaks are the highest object strain or a decayed strain of a previous object
+        let max = |strains: &[f64]| strains.iter().copied().fold(0.0, f64::max);
+
+        assert!((max(&objects.color) - max(&peaks.color)).abs() < 1e-10);
+        assert!((max(&objects.rhythm) - max(&peaks.rhythm)).abs() < 1e-10);
+        assert!((max(&objects.stamina) - max(&peaks.stamina)).abs() < 1e-10);
+
+        let passed = TaikoStars::new(&map).passed_objects(10).object_strains();
+        assert_eq!(passed.len(), 10);
+        assert_eq!(passed.stamina[..], objects.stamina[..10]);
+    }
+}
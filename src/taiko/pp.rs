@@ -2,7 +2,8 @@ use std::borrow::Cow;
 
 use super::{TaikoDifficultyAttributes, TaikoPerformanceAttributes, TaikoScoreState, TaikoStars};
 use crate::{
-    Beatmap, DifficultyAttributes, GameMode, HitResultPriority, Mods, OsuPP, PerformanceAttributes,
+    pp::balance_hitresults, Beatmap, DifficultyAttributes, GameMode, HitResultPriority, Mods,
+    ModsMismatch, OsuPP, PerformanceAttributes,
 };
 
 /// Performance calculator on osu!taiko maps.
@@ -45,6 +46,7 @@ pub struct TaikoPP<'map> {
     passed_objects: Option<usize>,
     clock_rate: Option<f64>,
     hitresult_priority: Option<HitResultPriority>,
+    accuracy_weights: Option<(f64, f64)>,
 
     pub(crate) n300: Option<usize>,
     pub(crate) n100: Option<usize>,
@@ -67,6 +69,7 @@ impl<'map> TaikoPP<'map> {
             n300: None,
             n100: None,
             hitresult_priority: None,
+            accuracy_weights: None,
         }
     }
 
@@ -165,7 +168,37 @@ impl<'map> TaikoPP<'map> {
         self
     }
 
+    /// Specify whether the pp should be weighted towards accuracy and away from the strain.
+    ///
+    /// Suitable for relax leaderboards where hitting the right colour does not matter.
+    /// Uses a difficulty multiplier of `0.6` and an accuracy multiplier of `1.4`,
+    /// see [`accuracy_weights`](TaikoPP::accuracy_weights) to specify them manually.
+    ///
+    /// If not specified, the pp will not be weighted.
+    #[inline]
+    pub fn accuracy_weighted(mut self, accuracy_weighted: bool) -> Self {
+        self.accuracy_weights = accuracy_weighted.then_some((
+            TaikoPpInner::DEFAULT_DIFFICULTY_WEIGHT,
+            TaikoPpInner::DEFAULT_ACCURACY_WEIGHT,
+        ));
+
+        self
+    }
+
+    /// Specify the multipliers for the difficulty and accuracy pp of
+    /// [`accuracy_weighted`](TaikoPP::accuracy_weighted) calculations.
+    #[inline]
+    pub fn accuracy_weights(mut self, difficulty: f64, accuracy: f64) -> Self {
+        self.accuracy_weights = Some((difficulty, accuracy));
+
+        self
+    }
+
     /// Provide parameters through a [`TaikoScoreState`].
+    ///
+    /// Values can be overridden by calling the individual setters afterwards,
+    /// e.g. `.state(state).combo(max_combo)` to calculate an if-FC
+    /// with the same hitresults.
     #[inline]
     pub fn state(mut self, state: TaikoScoreState) -> Self {
         let TaikoScoreState {
@@ -183,27 +216,56 @@ impl<'map> TaikoPP<'map> {
         self
     }
 
-    /// Calculate all performance related values, including pp and stars.
-    pub fn calculate(mut self) -> TaikoPerformanceAttributes {
-        let attrs = self.attributes.take().unwrap_or_else(|| {
-            let mut calculator = TaikoStars::new(self.map.as_ref())
-                .mods(self.mods)
-                .is_convert(matches!(self.map, Cow::Owned(_)));
+    /// Same as [`TaikoPP::calculate`] but returns an error if the attributes
+    /// provided through [`TaikoPP::attributes`] were calculated with different mods.
+    pub fn try_calculate(self) -> Result<TaikoPerformanceAttributes, ModsMismatch> {
+        if let Some(ref attrs) = self.attributes {
+            ModsMismatch::check(attrs.mods, self.mods, GameMode::Taiko)?;
+        }
 
-            if let Some(passed_objects) = self.passed_objects {
-                calculator = calculator.passed_objects(passed_objects);
-            }
+        Ok(self.calculate())
+    }
 
-            if let Some(clock_rate) = self.clock_rate {
-                calculator = calculator.clock_rate(clock_rate);
-            }
+    /// Generate the hitresults that [`calculate`](TaikoPP::calculate) would use.
+    ///
+    /// Hitresults that were not specified are generated to match the accuracy,
+    /// if given, and distributed according to the [`HitResultPriority`].
+    /// If no attributes were provided, they're calculated and kept
+    /// so that a subsequent calculation can re-use them.
+    pub fn generate_state(&mut self) -> TaikoScoreState {
+        if self.attributes.is_none() {
+            self.attributes = Some(self.difficulty());
+        }
 
-            calculator.calculate()
-        });
+        let max_combo = self.attributes.as_ref().map_or(0, |attrs| attrs.max_combo);
+
+        self.generate_hitresults(max_combo)
+    }
+
+    fn difficulty(&self) -> TaikoDifficultyAttributes {
+        let mut calculator = TaikoStars::new(self.map.as_ref())
+            .mods(self.mods)
+            .is_convert(matches!(self.map, Cow::Owned(_)));
+
+        if let Some(passed_objects) = self.passed_objects {
+            calculator = calculator.passed_objects(passed_objects);
+        }
+
+        if let Some(clock_rate) = self.clock_rate {
+            calculator = calculator.clock_rate(clock_rate);
+        }
+
+        calculator.calculate()
+    }
+
+    /// Calculate all performance related values, including pp and stars.
+    pub fn calculate(mut self) -> TaikoPerformanceAttributes {
+        let attrs = self.attributes.take().unwrap_or_else(|| self.difficulty());
 
         let inner = TaikoPpInner {
             mods: self.mods,
             state: self.generate_hitresults(attrs.max_combo),
+            accuracy_weights: self.accuracy_weights,
             attrs,
         };
 
@@ -211,14 +273,27 @@ impl<'map> TaikoPP<'map> {
     }
 
     fn generate_hitresults(&self, max_combo: usize) -> TaikoScoreState {
+        match self.hitresult_priority.unwrap_or_default() {
+            HitResultPriority::BestCase => self.generate_hitresults_with(max_combo, true),
+            HitResultPriority::WorstCase => self.generate_hitresults_with(max_combo, false),
+            HitResultPriority::Balanced => {
+                let best = self.generate_hitresults_with(max_combo, true);
+                let worst = self.generate_hitresults_with(max_combo, false);
+                let [n300, n100] =
+                    balance_hitresults([best.n300, best.n100], [worst.n300, worst.n100]);
+
+                TaikoScoreState { n300, n100, ..best }
+            }
+        }
+    }
+
+    fn generate_hitresults_with(&self, max_combo: usize, best_case: bool) -> TaikoScoreState {
         let total_result_count = if let Some(passed_objects) = self.passed_objects {
             max_combo.min(passed_objects)
         } else {
             max_combo
         };
 
-        let priority = self.hitresult_priority.unwrap_or_default();
-
         let mut n300 = self.n300.unwrap_or(0);
         let mut n100 = self.n100.unwrap_or(0);
         let n_misses = self.n_misses.unwrap_or(0);
@@ -228,9 +303,10 @@ impl<'map> TaikoPP<'map> {
                 (Some(_), Some(_)) => {
                     let remaining = total_result_count.saturating_sub(n300 + n100 + n_misses);
 
-                    match priority {
-                        HitResultPriority::BestCase => n300 += remaining,
-                        HitResultPriority::WorstCase => n100 += remaining,
+                    if best_case {
+                        n300 += remaining;
+                    } else {
+                        n100 += remaining;
                     }
                 }
                 (Some(_), None) => n100 += total_result_count.saturating_sub(n300 + n_misses),
@@ -244,17 +320,18 @@ impl<'map> TaikoPP<'map> {
         } else {
             let remaining = total_result_count.saturating_sub(n300 + n100 + n_misses);
 
-            match priority {
-                HitResultPriority::BestCase => match (self.n300, self.n100) {
+            if best_case {
+                match (self.n300, self.n100) {
                     (Some(_), None) => n100 = remaining,
                     (Some(_), Some(_)) => n300 += remaining,
                     (None, _) => n300 = remaining,
-                },
-                HitResultPriority::WorstCase => match (self.n300, self.n100) {
+                }
+            } else {
+                match (self.n300, self.n100) {
                     (None, Some(_)) => n300 = remaining,
                     (Some(_), Some(_)) => n100 += remaining,
                     (_, None) => n100 = remaining,
-                },
+                }
             }
         }
 
@@ -273,9 +350,13 @@ struct TaikoPpInner {
     attrs: TaikoDifficultyAttributes,
     mods: u32,
     state: TaikoScoreState,
+    accuracy_weights: Option<(f64, f64)>,
 }
 
 impl TaikoPpInner {
+    const DEFAULT_DIFFICULTY_WEIGHT: f64 = 0.6;
+    const DEFAULT_ACCURACY_WEIGHT: f64 = 1.4;
+
     fn calculate(self) -> TaikoPerformanceAttributes {
         // * The effectiveMissCount is calculated by gaining a ratio for totalSuccessfulHits
         // * and increasing the miss penalty for shorter object counts lower than 1000.
@@ -297,8 +378,13 @@ impl TaikoPpInner {
             multiplier *= 0.975;
         }
 
-        let diff_value = self.compute_difficulty_value(effective_miss_count);
-        let acc_value = self.compute_accuracy_value();
+        let mut diff_value = self.compute_difficulty_value(effective_miss_count);
+        let mut acc_value = self.compute_accuracy_value();
+
+        if let Some((diff_weight, acc_weight)) = self.accuracy_weights {
+            diff_value *= diff_weight;
+            acc_value *= acc_weight;
+        }
 
         let pp = (diff_value.powf(1.1) + acc_value.powf(1.1)).powf(1.0 / 1.1) * multiplier;
 
@@ -308,6 +394,8 @@ impl TaikoPpInner {
             pp_acc: acc_value,
             pp_difficulty: diff_value,
             effective_miss_count,
+            accuracy: self.state.accuracy(),
+            combo: self.state.max_combo,
         }
     }
 
@@ -395,6 +483,7 @@ impl<'map> From<OsuPP<'map>> for TaikoPP<'map> {
             mods,
             acc,
             combo,
+            combo_cap: _,
             n300,
             n100,
             n50: _,
@@ -402,6 +491,14 @@ impl<'map> From<OsuPP<'map>> for TaikoPP<'map> {
             passed_objects,
             clock_rate,
             hitresult_priority,
+            relax: _,
+            autopilot: _,
+            score_v2: _,
+            slider_head_accuracy: _,
+            large_tick_hits: _,
+            slider_end_hits: _,
+            hidden_ar_scaling: _,
+            relax_variant: _,
         } = osu;
 
         Self {
@@ -413,6 +510,7 @@ impl<'map> From<OsuPP<'map>> for TaikoPP<'map> {
             passed_objects,
             clock_rate,
             hitresult_priority,
+            accuracy_weights: None,
             n300,
             n100,
             n_misses,
@@ -479,14 +577,70 @@ mod test {
             rhythm: 0.20130047251681948,
             colour: 1.0487315549761433,
             peak: 1.8881824429738323,
+            finisher_difficulty: 0.1946559364898085,
             hit_window: 35.0,
             stars: 2.9778030386845606,
             max_combo: 289,
+            n_finishers: 52,
+            mods: 0,
         };
 
         (map, attrs)
     }
 
+    #[test]
+    fn accuracy_weighted() {
+        let (map, attrs) = test_data();
+
+        let calculate = |acc: f64, mods: u32, accuracy_weighted: Option<bool>| {
+            let mut calc = TaikoPP::new(&map)
+                .attributes(attrs.clone())
+                .mods(mods)
+                .accuracy(acc);
+
+            if let Some(accuracy_weighted) = accuracy_weighted {
+                calc = calc.accuracy_weighted(accuracy_weighted);
+            }
+
+            calc.calculate()
+        };
+
+        let regular = calculate(100.0, 0, None);
+        let weighted = calculate(100.0, 0, Some(true));
+
+        assert_eq!(regular, calculate(100.0, 0, Some(false)));
+        assert!(weighted.pp_acc > regular.pp_acc);
+        assert!(weighted.pp_difficulty < regular.pp_difficulty);
+
+        // Losing accuracy costs a larger share of the pp
+        let regular_ratio = calculate(95.0, 0, None).pp / regular.pp;
+        let weighted_ratio = calculate(95.0, 0, Some(true)).pp / weighted.pp;
+        assert!(weighted_ratio < regular_ratio);
+
+        // Disabled by default, even for RX
+        assert_eq!(
+            calculate(100.0, 128, None),
+            calculate(100.0, 128, Some(false))
+        );
+
+        let custom = TaikoPP::new(&map)
+            .attributes(attrs.clone())
+            .accuracy(100.0)
+            .accuracy_weights(0.6, 1.4)
+            .calculate();
+
+        assert_eq!(custom, weighted);
+
+        let custom = TaikoPP::new(&map)
+            .attributes(attrs.clone())
+            .accuracy(100.0)
+            .accuracy_weights(0.5, 2.0)
+            .calculate();
+
+        assert!(custom.pp_acc > weighted.pp_acc);
+        assert!(custom.pp_difficulty < weighted.pp_difficulty);
+    }
+
     #[test]
     fn hitresults_n300_n_misses_best() {
         let (map, attrs) = test_data();
@@ -560,4 +714,43 @@ mod test {
             expected.accuracy()
         );
     }
+
+    #[test]
+    fn reported_accuracy() {
+        let (map, attrs) = test_data();
+        let max_combo = attrs.max_combo();
+
+        let calc = TaikoPP::new(&map)
+            .attributes(attrs)
+            .combo(100)
+            .n_misses(2)
+            .accuracy(97.2);
+
+        let state = calc.generate_hitresults(max_combo);
+        let result = calc.calculate();
+
+        assert_eq!(result.accuracy, state.accuracy());
+        assert!((result.accuracy - 0.972).abs() < 0.01);
+    }
+
+    #[test]
+    fn effective_miss_count() {
+        let (map, attrs) = test_data();
+
+        let calculate = |n_misses: usize| {
+            TaikoPP::new(&map)
+                .attributes(attrs.clone())
+                .combo(50)
+                .n_misses(n_misses)
+                .calculate()
+        };
+
+        assert_eq!(calculate(0).effective_miss_count, 0.0);
+
+        // Fewer than 1000 successful hits scale the penalty up
+        let total_hits = attrs.max_combo as f64;
+        let two_misses = calculate(2);
+        assert!((two_misses.effective_miss_count - 2.0 * 1000.0 / (total_hits - 2.0)).abs() < 1e-9);
+        assert!(two_misses.pp < calculate(0).pp);
+    }
 }
@@ -6,6 +6,7 @@ use std::{
 use crate::taiko::{
     colours::{AlternatingMonoPattern, MonoStreak, RepeatingHitPatterns},
     difficulty_object::{ObjectLists, TaikoDifficultyObject},
+    SECTION_LEN,
 };
 
 use super::{Skill, StrainDecaySkill, StrainSkill};
@@ -15,6 +16,7 @@ pub(crate) struct Colour {
     curr_strain: f64,
     curr_section_peak: f64,
     curr_section_end: f64,
+    section_len: f64,
     pub(crate) strain_peaks: Vec<f64>,
 }
 
@@ -24,6 +26,7 @@ impl Colour {
             curr_strain: 0.0,
             curr_section_peak: 0.0,
             curr_section_end: 0.0,
+            section_len: SECTION_LEN as f64,
             strain_peaks: Vec::new(),
         }
     }
@@ -34,11 +37,6 @@ impl Skill for Colour {
     fn process(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) {
         <Self as StrainSkill>::process(self, curr, hit_objects)
     }
-
-    #[inline]
-    fn difficulty_value(self) -> f64 {
-        <Self as StrainSkill>::difficulty_value(self)
-    }
 }
 
 impl StrainSkill for Colour {
@@ -57,6 +55,11 @@ impl StrainSkill for Colour {
         &mut self.curr_section_end
     }
 
+    #[inline]
+    fn section_len(&mut self) -> &mut f64 {
+        &mut self.section_len
+    }
+
     #[inline]
     fn strain_value_at(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) -> f64 {
         <Self as StrainDecaySkill>::strain_value_at(self, curr, hit_objects)
@@ -1,14 +1,23 @@
 use std::cmp::Ordering;
 
-use crate::taiko::difficulty_object::{ObjectLists, TaikoDifficultyObject};
+use crate::taiko::{
+    difficulty_object::{ObjectLists, TaikoDifficultyObject},
+    SECTION_LEN,
+};
 
-use super::{colour::Colour, rhythm::Rhythm, stamina::Stamina, Skill, StrainSkill};
+use super::{
+    colour::Colour, rhythm::Rhythm, stamina::Stamina, Skill, StrainDecaySkill, StrainSkill,
+};
 
 #[derive(Clone, Debug)]
 pub(crate) struct Peaks {
     colour: Colour,
     rhythm: Rhythm,
     stamina: Stamina,
+    section_len: f64,
+    curr_section_end: f64,
+    curr_section_finisher: bool,
+    finisher_sections: Vec<bool>,
 }
 
 impl Peaks {
@@ -23,9 +32,21 @@ impl Peaks {
             colour: Colour::new(),
             rhythm: Rhythm::new(),
             stamina: Stamina::new(),
+            section_len: SECTION_LEN as f64,
+            curr_section_end: 0.0,
+            curr_section_finisher: false,
+            finisher_sections: Vec::new(),
         }
     }
 
+    /// Adjust the time in ms that each strain peak covers.
+    pub(crate) fn set_section_len(&mut self, section_len: f64) {
+        *self.colour.section_len() = section_len;
+        *self.rhythm.section_len() = section_len;
+        *self.stamina.section_len() = section_len;
+        self.section_len = section_len;
+    }
+
     pub(crate) fn difficulty_values(self) -> PeaksDifficultyValues {
         let colour_rating = <Colour as StrainSkill>::difficulty_value(self.colour.clone())
             * Self::COLOUR_SKILL_MULTIPLIER;
@@ -34,77 +55,133 @@ impl Peaks {
         let stamina_rating = <Stamina as StrainSkill>::difficulty_value(self.stamina.clone())
             * Self::STAMINA_SKILL_MULTIPLIER;
 
+        let (combined_rating, finisher_rating) = self.weighted_peaks();
+
         PeaksDifficultyValues {
             colour_rating,
             rhythm_rating,
             stamina_rating,
-            combined_rating: self.difficulty_value(),
+            combined_rating,
+            finisher_rating,
         }
     }
 
     pub(crate) fn into_raw(self) -> PeaksRaw {
+        let combined = self
+            .colour
+            .strain_peaks
+            .iter()
+            .zip(self.rhythm.strain_peaks.iter())
+            .zip(self.stamina.strain_peaks.iter())
+            .map(|((&colour, &rhythm), &stamina)| Self::combined_peak(colour, rhythm, stamina))
+            .collect();
+
         PeaksRaw {
             colour: self.colour.strain_peaks,
             rhythm: self.rhythm.strain_peaks,
             stamina: self.stamina.strain_peaks,
+            combined,
         }
     }
 
-    fn norm(p: f64, values: impl IntoIterator<Item = f64>) -> f64 {
-        values
-            .into_iter()
-            .fold(0.0, |sum, x| sum + x.powf(p))
-            .powf(p.recip())
+    /// The colour, rhythm, and stamina strain of the most recently processed object.
+    pub(crate) fn curr_strains(&self) -> (f64, f64, f64) {
+        (
+            self.colour.curr_strain(),
+            self.rhythm.curr_strain(),
+            self.stamina.curr_strain(),
+        )
     }
-}
 
-impl Skill for Peaks {
-    #[inline]
-    fn process(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) {
-        <Colour as Skill>::process(&mut self.colour, curr, hit_objects);
-        <Rhythm as Skill>::process(&mut self.rhythm, curr, hit_objects);
-        <Stamina as Skill>::process(&mut self.stamina, curr, hit_objects);
+    pub(crate) fn combined_peak(colour_peak: f64, rhythm_peak: f64, stamina_peak: f64) -> f64 {
+        let colour_peak = colour_peak * Self::COLOUR_SKILL_MULTIPLIER;
+        let rhythm_peak = rhythm_peak * Self::RHYTHM_SKILL_MULTIPLIER;
+        let stamina_peak = stamina_peak * Self::STAMINA_SKILL_MULTIPLIER;
+
+        let peak = Self::norm(1.5, [colour_peak, stamina_peak]);
+
+        Self::norm(2.0, [peak, rhythm_peak])
     }
 
-    fn difficulty_value(self) -> f64 {
+    /// Keep track of which sections contain finishers, using
+    /// the same section boundaries as the strain skills.
+    fn process_finisher(&mut self, curr: &TaikoDifficultyObject) {
+        if curr.idx == 0 {
+            self.curr_section_end = (curr.start_time / self.section_len).ceil() * self.section_len;
+        }
+
+        while curr.start_time > self.curr_section_end {
+            self.finisher_sections.push(self.curr_section_finisher);
+            self.curr_section_finisher = false;
+            self.curr_section_end += self.section_len;
+        }
+
+        self.curr_section_finisher |= curr.base.is_finisher;
+    }
+
+    /// The weighted sum of all combined peaks, and the part
+    /// of that sum that stems from sections with finishers.
+    fn weighted_peaks(self) -> (f64, f64) {
         let mut peaks = Vec::new();
 
         let colour_peaks = self.colour.get_curr_strain_peaks();
         let rhythm_peaks = self.rhythm.get_curr_strain_peaks();
         let stamina_peaks = self.stamina.get_curr_strain_peaks();
 
+        let mut finisher_sections = self.finisher_sections;
+        finisher_sections.push(self.curr_section_finisher);
+
         let zip = colour_peaks
             .into_iter()
             .zip(rhythm_peaks)
-            .zip(stamina_peaks);
-
-        for ((mut colour_peak, mut rhythm_peak), mut stamina_peak) in zip {
-            colour_peak *= Self::COLOUR_SKILL_MULTIPLIER;
-            rhythm_peak *= Self::RHYTHM_SKILL_MULTIPLIER;
-            stamina_peak *= Self::STAMINA_SKILL_MULTIPLIER;
+            .zip(stamina_peaks)
+            .zip(finisher_sections);
 
-            let mut peak = Self::norm(1.5, [colour_peak, stamina_peak]);
-            peak = Self::norm(2.0, [peak, rhythm_peak]);
+        for (((colour_peak, rhythm_peak), stamina_peak), has_finisher) in zip {
+            let peak = Self::combined_peak(colour_peak, rhythm_peak, stamina_peak);
 
             // * Sections with 0 strain are excluded to avoid worst-case
             // * time complexity of the following sort (e.g. /b/2351871).
             // * These sections will not contribute to the difficulty.
             if peak > 0.0 {
-                peaks.push(peak);
+                peaks.push((peak, has_finisher));
             }
         }
 
         let mut difficulty = 0.0;
+        let mut finisher_difficulty = 0.0;
         let mut weight = 1.0;
 
-        peaks.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        peaks.sort_unstable_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
 
-        for strain in peaks {
+        for (strain, has_finisher) in peaks {
             difficulty += strain * weight;
+
+            if has_finisher {
+                finisher_difficulty += strain * weight;
+            }
+
             weight *= 0.9;
         }
 
-        difficulty
+        (difficulty, finisher_difficulty)
+    }
+
+    fn norm(p: f64, values: impl IntoIterator<Item = f64>) -> f64 {
+        values
+            .into_iter()
+            .fold(0.0, |sum, x| sum + x.powf(p))
+            .powf(p.recip())
+    }
+}
+
+impl Skill for Peaks {
+    #[inline]
+    fn process(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) {
+        self.process_finisher(curr);
+        <Colour as Skill>::process(&mut self.colour, curr, hit_objects);
+        <Rhythm as Skill>::process(&mut self.rhythm, curr, hit_objects);
+        <Stamina as Skill>::process(&mut self.stamina, curr, hit_objects);
     }
 }
 
@@ -113,10 +190,12 @@ pub(crate) struct PeaksDifficultyValues {
     pub(crate) rhythm_rating: f64,
     pub(crate) stamina_rating: f64,
     pub(crate) combined_rating: f64,
+    pub(crate) finisher_rating: f64,
 }
 
 pub(crate) struct PeaksRaw {
     pub(crate) colour: Vec<f64>,
     pub(crate) rhythm: Vec<f64>,
     pub(crate) stamina: Vec<f64>,
+    pub(crate) combined: Vec<f64>,
 }
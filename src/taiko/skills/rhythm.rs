@@ -1,5 +1,8 @@
 use crate::{
-    taiko::difficulty_object::{HitObjectRhythm, ObjectLists, TaikoDifficultyObject},
+    taiko::{
+        difficulty_object::{HitObjectRhythm, ObjectLists, TaikoDifficultyObject},
+        SECTION_LEN,
+    },
     util::LimitedQueue,
 };
 
@@ -17,6 +20,7 @@ pub(crate) struct Rhythm {
     history: LimitedQueue<HistoryElement, HISTORY_MAX_LEN>,
     curr_section_peak: f64,
     curr_section_end: f64,
+    section_len: f64,
     pub(crate) strain_peaks: Vec<f64>,
 }
 
@@ -31,6 +35,7 @@ impl Rhythm {
             history: LimitedQueue::new(),
             curr_section_peak: 0.0,
             curr_section_end: 0.0,
+            section_len: SECTION_LEN as f64,
             strain_peaks: Vec::new(),
         }
     }
@@ -104,11 +109,6 @@ impl Skill for Rhythm {
     fn process(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) {
         <Self as StrainSkill>::process(self, curr, hit_objects)
     }
-
-    #[inline]
-    fn difficulty_value(self) -> f64 {
-        <Self as StrainSkill>::difficulty_value(self)
-    }
 }
 
 impl StrainSkill for Rhythm {
@@ -127,6 +127,11 @@ impl StrainSkill for Rhythm {
         &mut self.curr_section_end
     }
 
+    #[inline]
+    fn section_len(&mut self) -> &mut f64 {
+        &mut self.section_len
+    }
+
     #[inline]
     fn strain_value_at(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) -> f64 {
         <Self as StrainDecaySkill>::strain_value_at(self, curr, hit_objects)
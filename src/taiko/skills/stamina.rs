@@ -1,4 +1,7 @@
-use crate::taiko::difficulty_object::{ObjectLists, TaikoDifficultyObject};
+use crate::taiko::{
+    difficulty_object::{ObjectLists, TaikoDifficultyObject},
+    SECTION_LEN,
+};
 
 use super::{Skill, StrainDecaySkill, StrainSkill};
 
@@ -7,6 +10,7 @@ pub(crate) struct Stamina {
     curr_strain: f64,
     curr_section_peak: f64,
     curr_section_end: f64,
+    section_len: f64,
     pub(crate) strain_peaks: Vec<f64>,
 }
 
@@ -16,6 +20,7 @@ impl Stamina {
             curr_strain: 0.0,
             curr_section_peak: 0.0,
             curr_section_end: 0.0,
+            section_len: SECTION_LEN as f64,
             strain_peaks: Vec::new(),
         }
     }
@@ -26,11 +31,6 @@ impl Skill for Stamina {
     fn process(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) {
         <Self as StrainSkill>::process(self, curr, hit_objects)
     }
-
-    #[inline]
-    fn difficulty_value(self) -> f64 {
-        <Self as StrainSkill>::difficulty_value(self)
-    }
 }
 
 impl StrainSkill for Stamina {
@@ -49,6 +49,11 @@ impl StrainSkill for Stamina {
         &mut self.curr_section_end
     }
 
+    #[inline]
+    fn section_len(&mut self) -> &mut f64 {
+        &mut self.section_len
+    }
+
     #[inline]
     fn strain_value_at(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) -> f64 {
         <Self as StrainDecaySkill>::strain_value_at(self, curr, hit_objects)
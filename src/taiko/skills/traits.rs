@@ -1,13 +1,9 @@
 use std::{cmp::Ordering, mem};
 
-use crate::taiko::{
-    difficulty_object::{ObjectLists, TaikoDifficultyObject},
-    SECTION_LEN,
-};
+use crate::taiko::difficulty_object::{ObjectLists, TaikoDifficultyObject};
 
 pub(crate) trait Skill: Sized {
     fn process(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists);
-    fn difficulty_value(self) -> f64;
 }
 
 pub(crate) trait StrainSkill: Skill {
@@ -16,6 +12,7 @@ pub(crate) trait StrainSkill: Skill {
     fn strain_peaks_mut(&mut self) -> &mut Vec<f64>;
     fn curr_section_peak(&mut self) -> &mut f64;
     fn curr_section_end(&mut self) -> &mut f64;
+    fn section_len(&mut self) -> &mut f64;
 
     fn strain_value_at(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) -> f64;
 
@@ -24,7 +21,7 @@ pub(crate) trait StrainSkill: Skill {
     fn process(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) {
         // * The first object doesn't generate a strain, so we begin with an incremented section end
         if curr.idx == 0 {
-            let section_len = SECTION_LEN as f64;
+            let section_len = *self.section_len();
             *self.curr_section_end() = (curr.start_time / section_len).ceil() * section_len;
         }
 
@@ -36,7 +33,8 @@ pub(crate) trait StrainSkill: Skill {
                 self.start_new_section_from(section_end, curr);
             }
 
-            *self.curr_section_end() += SECTION_LEN as f64;
+            let section_len = *self.section_len();
+            *self.curr_section_end() += section_len;
         }
 
         *self.curr_section_peak() = self
@@ -1,6 +1,9 @@
 use std::slice::Iter;
 
-use crate::{parse::HitObject, Beatmap};
+use crate::{
+    parse::{HitObject, HitSound},
+    Beatmap,
+};
 
 use super::rim::Rim;
 
@@ -8,13 +11,17 @@ use super::rim::Rim;
 pub(crate) struct TaikoObject {
     pub(crate) is_hit: bool,
     pub(crate) is_rim: bool,
+    pub(crate) is_finisher: bool,
 }
 
 impl TaikoObject {
     pub(crate) fn new(h: &HitObject, sound: u8) -> Self {
+        let is_hit = h.is_circle();
+
         Self {
-            is_hit: h.is_circle(),
+            is_hit,
             is_rim: sound.is_rim(),
+            is_finisher: is_hit && sound.finish(),
         }
     }
 }
@@ -1,12 +1,13 @@
 mod byte_hasher;
 mod float_ext;
 mod limited_queue;
+mod md5;
 mod sorted_vec;
 mod tandem_sort;
 
 pub use self::sorted_vec::SortedVec;
 
 pub(crate) use self::{
-    byte_hasher::ByteHasher, float_ext::FloatExt, limited_queue::LimitedQueue,
+    byte_hasher::ByteHasher, float_ext::FloatExt, limited_queue::LimitedQueue, md5::Md5,
     tandem_sort::TandemSorter,
 };